@@ -0,0 +1,44 @@
+//! Times `GameOfLife::step` on a few representative patterns. Run with `cargo bench`.
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use game_of_life_rs::{patterns, prelude::*, random::Rng};
+
+/// Generations stepped per iteration
+const GENERATIONS: u32 = 200;
+
+/// Steps a fresh game from `board` with `step` for `GENERATIONS` generations per iteration.
+fn bench(c: &mut Criterion, name: &str, board: &Board, step: fn(&mut GameOfLife) -> StepReport) {
+    let mut group = c.benchmark_group("step");
+    group.throughput(Throughput::Elements(GENERATIONS as u64));
+    group.bench_function(name, |b| {
+        b.iter_batched(
+            || GameOfLife {
+                board: board.clone(),
+                ..Default::default()
+            },
+            |mut game| {
+                for _ in 0..GENERATIONS {
+                    step(&mut game);
+                }
+                game
+            },
+            BatchSize::SmallInput,
+        )
+    });
+    group.finish();
+}
+
+fn step(c: &mut Criterion) {
+    let gun = patterns::get("Gosper glider gun").unwrap();
+    let acorn = patterns::get("Acorn").unwrap();
+    let soup = Board::random(Point::default(), 64, 64, 0.5, &mut Rng::new(1));
+
+    bench(c, "gosper glider gun", &gun, GameOfLife::step);
+    bench(c, "acorn", &acorn, GameOfLife::step);
+    bench(c, "random soup 64x64", &soup, GameOfLife::step);
+    bench(c, "gun (lut)", &gun, GameOfLife::step_lut);
+    bench(c, "soup 64x64 (lut)", &soup, GameOfLife::step_lut);
+}
+
+criterion_group!(benches, step);
+criterion_main!(benches);
@@ -0,0 +1,331 @@
+use std::{fmt::Display, path::PathBuf, str::FromStr};
+
+use crate::{
+    game::{GameError, GameOfLife},
+    point::Point,
+    rule::Rule,
+    theme::CellGlyphs,
+};
+
+/// Commands entered through the command input, e.g. `:gen 10`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Action {
+    /// Jump forward or back to a generation
+    Gen(u32),
+    /// Write population statistics as CSV to a file
+    ExportCsv(PathBuf),
+    /// Load a pattern file, replacing the board
+    Load(PathBuf),
+    /// Browse the pattern files in a directory, picking one to load
+    Gallery(PathBuf),
+    /// Write the cells currently in view to a file, as SVG or PNG if it ends in `.svg` or `.png`
+    /// and RLE otherwise. Images include labels.
+    ExportView(PathBuf),
+    /// Switch to a rule given in B/S notation, keeping the board
+    Rule(Rule),
+    /// Run until the population crosses a threshold, then pause
+    Until(PopulationTarget),
+    /// Draw cells with a named glyph preset, e.g. `cells circles`
+    Cells(&'static CellGlyphs),
+    /// Repeat the pattern waiting to be stamped in a grid, e.g. `tile 3 4 2` for 3 rows and 4
+    /// columns with 2 empty cells between copies
+    Tile { rows: u32, columns: u32, gap: u32 },
+    /// Attach a note to a cell, e.g. `label 10 -4 glider eater here`, or remove it when there's
+    /// no text. Coordinates are as shown, so they follow `--y-down`.
+    Label { at: Point, text: String },
+    /// Step this many generations per tick, e.g. `lapse 10`, or `lapse 1` to turn it off
+    TimeLapse(u32),
+    /// Step the rectangle between two corners on its own and highlight where it disagrees with
+    /// a full step, e.g. `region -10 -10 10 10`. For debugging bounded stepping.
+    Region { from: Point, to: Point },
+    /// Step a single generation, pausing first if running
+    Step,
+    /// Step exactly this many generations at once and pause there, e.g. `freeze 30`, to label
+    /// and export a snapshot for a diagram
+    Freeze(u32),
+    /// Start running, as the space key does
+    Play,
+    /// Pause if running
+    Pause,
+}
+
+/// A population threshold to pause at, written like `>500` or `< 10`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PopulationTarget {
+    Above(usize),
+    Below(usize),
+}
+
+impl PopulationTarget {
+    pub fn reached(&self, game: &GameOfLife) -> bool {
+        match *self {
+            PopulationTarget::Above(n) => game.board.population() > n,
+            PopulationTarget::Below(n) => game.board.population() < n,
+        }
+    }
+}
+
+impl FromStr for PopulationTarget {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let count = |n: &str| n.trim().parse().map_err(|_| ());
+        if let Some(n) = s.strip_prefix('>') {
+            Ok(PopulationTarget::Above(count(n)?))
+        } else if let Some(n) = s.strip_prefix('<') {
+            Ok(PopulationTarget::Below(count(n)?))
+        } else {
+            Err(())
+        }
+    }
+}
+
+impl Display for PopulationTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PopulationTarget::Above(n) => f.write_fmt(format_args!("population > {}", n)),
+            PopulationTarget::Below(n) => f.write_fmt(format_args!("population < {}", n)),
+        }
+    }
+}
+
+/// Writes the action as the command that parses back into it.
+impl Display for Action {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Action::Gen(generation) => f.write_fmt(format_args!("gen {}", generation)),
+            Action::ExportCsv(path) => f.write_fmt(format_args!("csv {}", path.display())),
+            Action::Load(path) => f.write_fmt(format_args!("load {}", path.display())),
+            Action::Gallery(dir) => f.write_fmt(format_args!("gallery {}", dir.display())),
+            Action::ExportView(path) => f.write_fmt(format_args!("export {}", path.display())),
+            Action::Rule(rule) => f.write_fmt(format_args!("rule {}", rule)),
+            Action::Until(PopulationTarget::Above(n)) => f.write_fmt(format_args!("until >{}", n)),
+            Action::Until(PopulationTarget::Below(n)) => f.write_fmt(format_args!("until <{}", n)),
+            Action::Cells(glyphs) => {
+                f.write_fmt(format_args!("cells {}", glyphs.name.to_lowercase()))
+            }
+            Action::Label { at, text } if text.is_empty() => {
+                f.write_fmt(format_args!("label {} {}", at.x, at.y))
+            }
+            Action::Label { at, text } => {
+                f.write_fmt(format_args!("label {} {} {}", at.x, at.y, text))
+            }
+            Action::TimeLapse(generations) => f.write_fmt(format_args!("lapse {}", generations)),
+            Action::Region { from, to } => f.write_fmt(format_args!(
+                "region {} {} {} {}",
+                from.x, from.y, to.x, to.y
+            )),
+            Action::Tile { rows, columns, gap } => {
+                f.write_fmt(format_args!("tile {} {} {}", rows, columns, gap))
+            }
+            Action::Step => f.write_str("step"),
+            Action::Freeze(generations) => f.write_fmt(format_args!("freeze {}", generations)),
+            Action::Play => f.write_str("play"),
+            Action::Pause => f.write_str("pause"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ActionError {
+    Empty,
+    Unknown(String),
+    InvalidArgument {
+        command: String,
+        arg: String,
+    },
+    /// A malformed rule string, with the reason it didn't parse
+    InvalidRule(String),
+}
+
+impl Display for ActionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ActionError::Empty => f.write_str("No command given"),
+            ActionError::Unknown(command) => {
+                f.write_fmt(format_args!("Unknown command `{}`", command))
+            }
+            ActionError::InvalidArgument { command, arg } => {
+                f.write_fmt(format_args!("Invalid argument `{}` for `{}`", arg, command))
+            }
+            ActionError::InvalidRule(reason) => f.write_str(reason),
+        }
+    }
+}
+
+impl std::error::Error for ActionError {}
+
+fn arg<T: FromStr>(command: &str, arg: Option<&str>) -> Result<T, ActionError> {
+    let invalid = |arg: &str| ActionError::InvalidArgument {
+        command: command.to_owned(),
+        arg: arg.to_owned(),
+    };
+    let arg = arg.ok_or_else(|| invalid(""))?;
+    arg.parse().map_err(|_| invalid(arg))
+}
+
+impl FromStr for Action {
+    type Err = ActionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut words = s.split_whitespace();
+        let command = words.next().ok_or(ActionError::Empty)?;
+        match command {
+            "gen" => Ok(Action::Gen(arg(command, words.next())?)),
+            "csv" => Ok(Action::ExportCsv(arg(command, words.next())?)),
+            "load" => Ok(Action::Load(arg(command, words.next())?)),
+            "gallery" => Ok(Action::Gallery(arg(command, words.next())?)),
+            "export" => Ok(Action::ExportView(arg(command, words.next())?)),
+            "rule" => {
+                let s: String = arg(command, words.next())?;
+                s.parse()
+                    .map(Action::Rule)
+                    .map_err(|e: GameError| ActionError::InvalidRule(e.to_string()))
+            }
+            "until" => {
+                // Allow a space after the comparison, e.g. `until > 500`
+                let target: String = words.collect();
+                Ok(Action::Until(arg(command, Some(&target))?))
+            }
+            "label" => {
+                let at = Point::new(arg(command, words.next())?, arg(command, words.next())?);
+                let text = words.collect::<Vec<_>>().join(" ");
+                Ok(Action::Label { at, text })
+            }
+            "lapse" => Ok(Action::TimeLapse(arg(command, words.next())?)),
+            "region" => Ok(Action::Region {
+                from: Point::new(arg(command, words.next())?, arg(command, words.next())?),
+                to: Point::new(arg(command, words.next())?, arg(command, words.next())?),
+            }),
+            "step" => Ok(Action::Step),
+            "freeze" => Ok(Action::Freeze(arg(command, words.next())?)),
+            "play" => Ok(Action::Play),
+            "pause" => Ok(Action::Pause),
+            "tile" => Ok(Action::Tile {
+                rows: arg(command, words.next())?,
+                columns: arg(command, words.next())?,
+                gap: arg(command, words.next())?,
+            }),
+            "cells" => {
+                let name: String = arg(command, words.next())?;
+                CellGlyphs::named(&name)
+                    .map(Action::Cells)
+                    .ok_or(ActionError::InvalidArgument {
+                        command: command.to_owned(),
+                        arg: name,
+                    })
+            }
+            _ => Err(ActionError::Unknown(command.to_owned())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        assert_eq!("gen 10".parse(), Ok(Action::Gen(10)));
+        assert_eq!("  gen   0 ".parse(), Ok(Action::Gen(0)));
+        assert_eq!(
+            "csv pop.csv".parse(),
+            Ok(Action::ExportCsv("pop.csv".into()))
+        );
+        assert_eq!(
+            "export shot.rle".parse(),
+            Ok(Action::ExportView("shot.rle".into()))
+        );
+        assert_eq!("".parse::<Action>(), Err(ActionError::Empty));
+        assert!(matches!(
+            "gen".parse::<Action>(),
+            Err(ActionError::InvalidArgument { .. })
+        ));
+        assert!(matches!(
+            "csv".parse::<Action>(),
+            Err(ActionError::InvalidArgument { .. })
+        ));
+        assert_eq!(
+            "rule B36/S23".parse(),
+            Ok(Action::Rule("B36/S23".parse().unwrap()))
+        );
+        assert_eq!(
+            "rule B3/S2x".parse::<Action>(),
+            Err(ActionError::InvalidRule(
+                "Invalid rule `B3/S2x`, expected e.g. B3/S23".to_owned()
+            ))
+        );
+        assert!(matches!(
+            "rule".parse::<Action>(),
+            Err(ActionError::InvalidArgument { .. })
+        ));
+        assert_eq!(
+            "until >500".parse(),
+            Ok(Action::Until(PopulationTarget::Above(500)))
+        );
+        assert_eq!(
+            "until < 10".parse(),
+            Ok(Action::Until(PopulationTarget::Below(10)))
+        );
+        assert!(matches!(
+            "until 500".parse::<Action>(),
+            Err(ActionError::InvalidArgument { .. })
+        ));
+        assert_eq!(
+            "cells Circles".parse(),
+            Ok(Action::Cells(&crate::theme::CIRCLES))
+        );
+        assert_eq!(
+            "tile 2 3 1".parse(),
+            Ok(Action::Tile {
+                rows: 2,
+                columns: 3,
+                gap: 1
+            })
+        );
+        assert!(matches!(
+            "region 0 0 5".parse::<Action>(),
+            Err(ActionError::InvalidArgument { .. })
+        ));
+        assert!(matches!(
+            "tile 2 3".parse::<Action>(),
+            Err(ActionError::InvalidArgument { .. })
+        ));
+        assert!(matches!(
+            "cells hexagons".parse::<Action>(),
+            Err(ActionError::InvalidArgument { .. })
+        ));
+        assert!(matches!(
+            "jump 10".parse::<Action>(),
+            Err(ActionError::Unknown(_))
+        ));
+    }
+
+    #[test]
+    fn display_round_trips() {
+        for command in [
+            "gen 10",
+            "csv pop.csv",
+            "load glider.rle",
+            "gallery patterns",
+            "export shot.rle",
+            "rule B36/S23",
+            "until >500",
+            "until <10",
+            "cells circles",
+            "tile 3 4 2",
+            "region -10 -10 10 10",
+            "lapse 10",
+            "label 10 -4 glider eater here",
+            "label 3 3",
+            "step",
+            "freeze 30",
+            "play",
+            "pause",
+        ] {
+            let action: Action = command.parse().unwrap();
+            assert_eq!(action.to_string(), command);
+        }
+    }
+}
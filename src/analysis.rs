@@ -0,0 +1,451 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+};
+
+use crate::{
+    board::Board,
+    format::{png, rle},
+    game::{GameError, GameOfLife},
+    point::Point,
+    rule::Rule,
+};
+
+/// Longest period looked for when classifying objects
+pub const MAX_CLASSIFY_PERIOD: u32 = 30;
+
+/// The whole board repeats its shape every `period` generations, moved by `offset`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Drift {
+    pub offset: Point,
+    pub period: u32,
+}
+
+impl Display for Drift {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!(
+            "({}, {})/{}",
+            self.offset.x, self.offset.y, self.period
+        ))
+    }
+}
+
+/// If `to` has the same shape as `from`, returns how far it moved.
+pub fn displacement(from: &Board, to: &Board) -> Option<Point> {
+    if from.population() != to.population() {
+        return None;
+    }
+    let (from_min, from_max) = from.bounding_box()?;
+    let (to_min, to_max) = to.bounding_box()?;
+    if from_max - from_min != to_max - to_min {
+        return None;
+    }
+    let offset = to_min - from_min;
+    from.iter()
+        .all(|p| to.contains(&(*p + offset)))
+        .then_some(offset)
+}
+
+/// Detects when the entire board is translating with a stable shape, e.g. a lone spaceship or a
+/// convoy of identical ones. The shape and displacement must hold for two consecutive periods of
+/// history, so a period `p` drift is reported `2p` generations after it starts.
+pub fn detect_drift(game: &GameOfLife, max_period: u32) -> Option<Drift> {
+    // Rewinds one board through history, keeping the generations that match the current shape
+    // so the one a period before them can be checked once it comes up
+    let mut past = game.board.clone();
+    let mut candidates = Vec::new();
+    for (generations, diff) in (1..=2 * max_period).zip(game.history.iter().rev()) {
+        diff.revert(&mut past);
+        if let Some((period, offset, _)) = candidates
+            .iter()
+            .find(|(period, _, _)| 2 * period == generations)
+            .filter(|(_, offset, previous)| displacement(&past, previous) == Some(*offset))
+        {
+            return Some(Drift {
+                offset: *offset,
+                period: *period,
+            });
+        }
+        if generations <= max_period {
+            if let Some(offset) =
+                displacement(&past, &game.board).filter(|&offset| offset != Point::default())
+            {
+                candidates.push((generations, offset, past.clone()));
+            }
+        }
+    }
+    None
+}
+
+/// Returns the period if the board exactly repeats one of its last `max_period` generations.
+pub fn detect_period(game: &GameOfLife, max_period: u32) -> Option<u32> {
+    // A past board matches when every cell changed an even number of times since
+    let mut changed = HashSet::new();
+    (1..=max_period)
+        .zip(game.history.iter().rev())
+        .find_map(|(period, diff)| {
+            for pos in diff.birthed.iter().chain(&diff.killed) {
+                if !changed.remove(pos) {
+                    changed.insert(*pos);
+                }
+            }
+            changed.is_empty().then_some(period)
+        })
+}
+
+/// The board repeats in place every `period` generations apart from `spaceships` flying away from
+/// the rest of it, e.g. a methuselah that has settled down after shooting off gliders.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Escape {
+    pub period: u32,
+    pub spaceships: usize,
+}
+
+/// Detects when all that still changes on the board is spaceships leaving it. Each spaceship must
+/// be an object of its own that repeated its shape somewhere else `period` generations ago, and be
+/// past the rest of the board along a direction it's moving in so it can never come back. Spaceships
+/// colliding with each other on the way out aren't ruled out.
+pub fn detect_escape(game: &GameOfLife, max_period: u32) -> Option<Escape> {
+    let mut changed = HashSet::new();
+    let mut past = game.board.clone();
+    for (period, diff) in (1..=max_period).zip(game.history.iter().rev()) {
+        for pos in diff.birthed.iter().chain(&diff.killed) {
+            if !changed.remove(pos) {
+                changed.insert(*pos);
+            }
+        }
+        diff.revert(&mut past);
+        // Spaceships keep their population, and so does whatever repeats in place
+        if changed.is_empty() || past.population() != game.board.population() {
+            continue;
+        }
+        if let Some(spaceships) = escaping(&past, &game.board, &changed, period) {
+            return Some(Escape { period, spaceships });
+        }
+    }
+    None
+}
+
+/// Matches the objects of `past` and `now` that contain `changed` cells up as spaceships leaving
+/// the rest of the board, which must be the same in both, returning how many there are. Nothing
+/// moves faster than one cell a generation, so ships are matched within `period` cells.
+fn escaping(past: &Board, now: &Board, changed: &HashSet<Point>, period: u32) -> Option<usize> {
+    let seeds = |board: &Board| {
+        let mut seeds: Vec<_> = changed
+            .iter()
+            .filter(|p| board.contains(p))
+            .copied()
+            .collect();
+        seeds.sort();
+        seeds
+    };
+    let before = grow(past, seeds(past));
+    let after = grow(now, seeds(now));
+    if before.len() != after.len() {
+        return None;
+    }
+    let without = |board: &Board, objects: &[Board]| -> Board {
+        let moving: HashSet<_> = objects.iter().flat_map(Board::iter).collect();
+        board
+            .iter()
+            .filter(|p| !moving.contains(p))
+            .copied()
+            .collect()
+    };
+    let rest = without(now, &after);
+    if rest.is_empty() || rest != without(past, &before) {
+        return None;
+    }
+    let (min, max) = rest.bounding_box()?;
+    let (min, max) = (min - Point::new(2, 2), max + Point::new(2, 2));
+    let period = period as i64;
+    let mut unmatched = before;
+    for ship in &after {
+        let (index, offset) = unmatched
+            .iter()
+            .enumerate()
+            .filter_map(|(i, object)| Some((i, displacement(object, ship)?)))
+            .find(|(_, offset)| {
+                *offset != Point::default() && offset.x.abs() <= period && offset.y.abs() <= period
+            })?;
+        unmatched.swap_remove(index);
+        let (ship_min, ship_max) = ship.bounding_box()?;
+        let leaving = offset.x > 0 && ship_min.x > max.x
+            || offset.x < 0 && ship_max.x < min.x
+            || offset.y > 0 && ship_min.y > max.y
+            || offset.y < 0 && ship_max.y < min.y;
+        if !leaving {
+            return None;
+        }
+    }
+    Some(after.len())
+}
+
+/// How many generations each cell has been alive for over a run, for seeing where activity
+/// concentrates. Unlike the diff overlay nothing fades, counts only grow.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct DensityMap {
+    counts: HashMap<Point, u32>,
+    /// Generations recorded
+    pub generations: u32,
+}
+
+impl DensityMap {
+    /// Counts one generation of `board`.
+    pub fn record(&mut self, board: &Board) {
+        for p in board.iter() {
+            *self.counts.entry(*p).or_default() += 1;
+        }
+        self.generations += 1;
+    }
+
+    /// Generations `p` was alive for.
+    pub fn count(&self, p: &Point) -> u32 {
+        self.counts.get(p).copied().unwrap_or(0)
+    }
+
+    /// Counts as CSV with a header row, one row per cell that was ever alive.
+    pub fn to_csv(&self) -> String {
+        let mut cells: Vec<_> = self.counts.iter().collect();
+        cells.sort();
+        let mut csv = String::from("x,y,count\n");
+        for (p, count) in cells {
+            csv.push_str(&format!("{},{},{}\n", p.x, p.y, count));
+        }
+        csv
+    }
+
+    /// Counts as a grayscale PNG covering every cell that was ever alive, brightest where alive
+    /// longest. The top row is the highest y. Fails if the cells span more than
+    /// `png::MAX_DIMENSION` pixels either way.
+    pub fn to_png(&self) -> Result<Vec<u8>, GameError> {
+        let Some((min, max)) = self
+            .counts
+            .keys()
+            .copied()
+            .collect::<Board>()
+            .bounding_box()
+        else {
+            return Ok(png::grayscale(1, 1, &[0]));
+        };
+        let (width, height) = png::dimensions(min, max, 1)?;
+        let brightest = self.counts.values().copied().max().unwrap_or(1) as u64;
+        let mut pixels = Vec::new();
+        for y in (min.y..=max.y).rev() {
+            for x in min.x..=max.x {
+                let count = self.count(&Point::new(x, y)) as u64;
+                pixels.push((count * 255 / brightest) as u8);
+            }
+        }
+        Ok(png::grayscale(width, height, &pixels))
+    }
+}
+
+/// Splits the board into objects, where cells within two cells of each other belong to the same
+/// object. Objects are returned in a deterministic order.
+pub fn objects(board: &Board) -> Vec<Board> {
+    grow(board, board.sorted())
+}
+
+/// The objects of `board` containing each of `starts`, in their order.
+fn grow(board: &Board, starts: Vec<Point>) -> Vec<Board> {
+    let mut unvisited: HashSet<Point> = board.iter().copied().collect();
+    let mut objects = Vec::new();
+    for start in starts {
+        if !unvisited.remove(&start) {
+            continue;
+        }
+        let mut object = vec![start];
+        let mut frontier = vec![start];
+        while let Some(p) = frontier.pop() {
+            for dx in -2..=2 {
+                for dy in -2..=2 {
+                    let q = p + Point::new(dx, dy);
+                    if unvisited.remove(&q) {
+                        object.push(q);
+                        frontier.push(q);
+                    }
+                }
+            }
+        }
+        objects.push(object.into_iter().collect());
+    }
+    objects
+}
+
+/// Encodes the shape of a board as the body of its RLE, e.g. `2o$2o` for a block.
+fn shape_code(board: &Board) -> String {
+    rle::write(board)
+        .lines()
+        .skip(1)
+        .collect::<String>()
+        .trim_end_matches('!')
+        .to_owned()
+}
+
+/// Names a lone object in the style of apgsearch codes: `xs{population}` for still lifes,
+/// `xp{period}` for oscillators and `xq{period}` for spaceships, followed by the shape of its
+/// canonical phase. Objects that don't settle into one of those are `unstable`.
+pub fn classify(object: &Board, rule: &Rule) -> String {
+    let mut game = GameOfLife {
+        board: object.clone(),
+        rule: *rule,
+        ..Default::default()
+    };
+    let mut phases = vec![object.canonical()];
+    for period in 1..=MAX_CLASSIFY_PERIOD {
+        game.step();
+        if game.board.is_empty() {
+            break;
+        }
+        if let Some(offset) = displacement(object, &game.board) {
+            let canonical = phases
+                .iter()
+                .map(|b| (b.sorted(), b))
+                .min_by(|(a, _), (b, _)| a.cmp(b))
+                .map(|(_, b)| b)
+                .expect("there's at least one phase");
+            let code = shape_code(canonical);
+            return match (period, offset == Point::default()) {
+                (1, true) => format!("xs{}_{}", object.population(), code),
+                (_, true) => format!("xp{}_{}", period, code),
+                (_, false) => format!("xq{}_{}", period, code),
+            };
+        }
+        phases.push(game.board.canonical());
+    }
+    "unstable".to_owned()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn glider_drift() {
+        let mut game: GameOfLife = ".x.\nx..\nxxx".parse().unwrap();
+        for _ in 0..7 {
+            game.step();
+            assert_eq!(detect_drift(&game, 10), None);
+        }
+        game.step();
+        let drift = detect_drift(&game, 10).unwrap();
+        assert_eq!(
+            drift,
+            Drift {
+                offset: Point::new(-1, -1),
+                period: 4
+            }
+        );
+        assert_eq!(drift.to_string(), "(-1, -1)/4");
+    }
+
+    #[test]
+    fn period() {
+        let mut game: GameOfLife = "xxx".parse().unwrap();
+        assert_eq!(detect_period(&game, 10), None);
+        game.step();
+        assert_eq!(detect_period(&game, 10), None);
+        game.step();
+        assert_eq!(detect_period(&game, 10), Some(2));
+    }
+
+    #[test]
+    fn escape() {
+        let block: Board = "xx\nxx".parse().unwrap();
+        let with = |glider: &str, offset| -> GameOfLife {
+            let glider: Board = glider.parse().unwrap();
+            GameOfLife {
+                board: block
+                    .iter()
+                    .chain(glider.translate(offset).iter())
+                    .copied()
+                    .collect(),
+                ..Default::default()
+            }
+        };
+
+        // Heading right, away from the block
+        let mut game = with(".x.\n..x\nxxx", Point::new(10, 0));
+        game.step_n(3);
+        assert_eq!(detect_escape(&game, 10), None);
+        game.step();
+        assert_eq!(
+            detect_escape(&game, 10),
+            Some(Escape {
+                period: 4,
+                spaceships: 1
+            })
+        );
+
+        // Heading left and down towards the block from above it
+        let mut game = with(".x.\nx..\nxxx", Point::new(10, 10));
+        game.step_n(4);
+        assert_eq!(detect_escape(&game, 10), None);
+
+        // A lone glider is a drift, not an escape
+        let mut game: GameOfLife = ".x.\n..x\nxxx".parse().unwrap();
+        game.step_n(8);
+        assert_eq!(detect_escape(&game, 10), None);
+    }
+
+    #[test]
+    fn density() {
+        let mut game: GameOfLife = "xxx".parse().unwrap();
+        let mut density = DensityMap::default();
+        for _ in 0..10 {
+            game.step();
+            density.record(&game.board);
+        }
+        assert_eq!(density.generations, 10);
+        // The blinker's ends are alive every other generation, its center always
+        assert_eq!(density.count(&Point::new(0, 0)), 5);
+        assert_eq!(density.count(&Point::new(1, 0)), 10);
+        assert_eq!(density.count(&Point::new(1, 1)), 5);
+        assert_eq!(density.count(&Point::new(5, 5)), 0);
+
+        let csv = density.to_csv();
+        assert!(csv.starts_with("x,y,count\n"));
+        assert!(csv.contains("\n1,0,10\n"));
+        assert_eq!(csv.lines().count(), 6);
+
+        let png = density.to_png().unwrap();
+        assert_eq!(&png[16..24], &[0, 0, 0, 3, 0, 0, 0, 3]);
+
+        let mut far = DensityMap::default();
+        far.record(&Board::from([Point::new(0, 0), Point::new(i64::MAX, 0)]));
+        assert!(far.to_png().is_err());
+    }
+
+    #[test]
+    fn split_objects() {
+        let board: Board = "xx...x\nxx...x\n.....x\n......\n..x...".parse().unwrap();
+        let objects = objects(&board);
+        let populations: Vec<_> = objects.iter().map(Board::population).collect();
+        assert_eq!(populations, [4, 1, 3]);
+    }
+
+    #[test]
+    fn classification() {
+        let rule = Rule::default();
+        let classify = |s: &str| classify(&s.parse().unwrap(), &rule);
+        assert_eq!(classify("xx\nxx"), "xs4_2o$2o");
+        assert_eq!(classify(".x.\nx.x\n.x."), "xs4_bo$obo$bo");
+        assert_eq!(classify("xxx"), classify("x\nx\nx"));
+        assert_eq!(classify("xxx"), "xp2_o$o$o");
+        assert!(classify(".x.\n..x\nxxx").starts_with("xq4_"));
+        assert_eq!(classify(".x.\n..x\nxxx"), classify("x..\nx.x\nxx."));
+        assert_eq!(classify("x"), "unstable");
+    }
+
+    #[test]
+    fn oscillators_dont_drift() {
+        let mut game: GameOfLife = "xxx".parse().unwrap();
+        game.step_n(10);
+        assert_eq!(detect_drift(&game, 10), None);
+
+        let mut game: GameOfLife = "xx\nxx".parse().unwrap();
+        game.step_n(10);
+        assert_eq!(detect_drift(&game, 10), None);
+    }
+}
@@ -1,16 +1,37 @@
 use std::{collections::HashSet, str::FromStr};
 
 use crate::{
+    format::png,
     game::{GameError, GameErrorKind},
-    point::Point,
+    point::{Axis, Point},
+    random::Rng,
 };
 
+/// Escapes `s` for use as text in XML.
+fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Starts the line giving a pattern's position, see `Board::to_ascii_positioned`
+pub const POSITION_PREFIX: &str = "#P";
+
 #[derive(Debug, PartialEq, Eq)]
 pub enum Cell {
     Dead(Point),
     Alive(Point),
 }
 
+/// Everything about a cell needed to decide its next state.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct CellInfo {
+    pub pos: Point,
+    pub alive: bool,
+    pub live_neighbors: u8,
+}
+
 #[derive(Debug, Default, PartialEq, Clone)]
 pub struct Board {
     board: HashSet<Point>,
@@ -20,51 +41,62 @@ pub struct Neighbors<'a> {
     board: &'a Board,
     pos: Point,
     which: u8,
+    /// Neighbors left to yield, counted up front so the length is exact
+    remaining: u8,
 }
 
 impl<'a> Neighbors<'a> {
     pub fn new(board: &'a Board, pos: Point) -> Neighbors<'a> {
+        // Rows and columns at the limits of the `i64` range have nothing past them
+        let span = |v: i64| 3 - (v == i64::MIN) as u8 - (v == i64::MAX) as u8;
         Neighbors {
             board,
             pos,
             which: 0,
+            remaining: span(pos.x) * span(pos.y) - 1,
         }
     }
 }
 
-/// Iterates over neighbors of X in counterclockwise rotation
+/// Iterates over neighbors of X in counterclockwise rotation. Neighbors that would lie outside
+/// the `i64` coordinate range are skipped, so cells at the extremes have fewer than 8.
 impl Iterator for Neighbors<'_> {
     type Item = Cell;
 
     fn size_hint(&self) -> (usize, Option<usize>) {
-        let len = 8 - self.which as usize;
+        let len = self.remaining as usize;
         (len, Some(len))
     }
 
     fn next(&mut self) -> Option<Self::Item> {
         let Point { x, y } = self.pos;
-        let pos = match self.which {
-            // right
-            0 => (x + 1, y),
-            // up-right
-            1 => (x + 1, y + 1),
-            // up
-            2 => (x, y + 1),
-            // up-left
-            3 => (x - 1, y + 1),
-            // left
-            4 => (x - 1, y),
-            // down-left
-            5 => (x - 1, y - 1),
-            // down
-            6 => (x, y - 1),
-            // down-right
-            7 => (x + 1, y - 1),
-            _ => return None,
-        };
-        self.which += 1;
+        loop {
+            let pos = match self.which {
+                // right
+                0 => (x.checked_add(1), Some(y)),
+                // up-right
+                1 => (x.checked_add(1), y.checked_add(1)),
+                // up
+                2 => (Some(x), y.checked_add(1)),
+                // up-left
+                3 => (x.checked_sub(1), y.checked_add(1)),
+                // left
+                4 => (x.checked_sub(1), Some(y)),
+                // down-left
+                5 => (x.checked_sub(1), y.checked_sub(1)),
+                // down
+                6 => (Some(x), y.checked_sub(1)),
+                // down-right
+                7 => (x.checked_add(1), y.checked_sub(1)),
+                _ => return None,
+            };
+            self.which += 1;
 
-        Some(self.board.query(&pos.into()))
+            if let (Some(x), Some(y)) = pos {
+                self.remaining -= 1;
+                return Some(self.board.query(&(x, y).into()));
+            }
+        }
     }
 }
 
@@ -82,10 +114,34 @@ impl Board {
         }
     }
 
+    pub fn inspect(&self, pos: &Point) -> CellInfo {
+        CellInfo {
+            pos: *pos,
+            alive: self.contains(pos),
+            live_neighbors: self.live_neighbor_count(pos),
+        }
+    }
+
+    pub fn contains(&self, pos: &Point) -> bool {
+        self.board.contains(pos)
+    }
+
     pub fn neighbors(&self, p: &Point) -> Neighbors<'_> {
         Neighbors::new(self, *p)
     }
 
+    /// The Moore neighborhood of `p` including `p` itself, which comes first, for rules that count
+    /// the center cell along with its neighbors.
+    pub fn moore(&self, p: &Point) -> impl Iterator<Item = Cell> + '_ {
+        std::iter::once(self.query(p)).chain(self.neighbors(p))
+    }
+
+    pub fn live_neighbor_count(&self, p: &Point) -> u8 {
+        self.neighbors(p)
+            .filter(|c| matches!(c, Cell::Alive(_)))
+            .count() as u8
+    }
+
     pub fn birth_cell(&mut self, p: &Point) {
         self.board.insert(*p);
     }
@@ -106,6 +162,402 @@ impl Board {
         self.board.iter()
     }
 
+    pub fn population(&self) -> usize {
+        self.board.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.board.is_empty()
+    }
+
+    /// Returns the inclusive `(min, max)` corners of the smallest rectangle containing every live
+    /// cell, or `None` if the board is empty.
+    pub fn bounding_box(&self) -> Option<(Point, Point)> {
+        let mut iter = self.board.iter();
+        let first = *iter.next()?;
+        Some(iter.fold((first, first), |(min, max), p| {
+            (
+                Point::new(min.x.min(p.x), min.y.min(p.y)),
+                Point::new(max.x.max(p.x), max.y.max(p.y)),
+            )
+        }))
+    }
+
+    /// Returns the average position of all live cells rounded down, or `None` if the board is
+    /// empty.
+    pub fn center_of_mass(&self) -> Option<Point> {
+        if self.board.is_empty() {
+            return None;
+        }
+        let n = self.board.len() as i128;
+        let (sum_x, sum_y) = self.board.iter().fold((0i128, 0i128), |(x, y), p| {
+            (x + p.x as i128, y + p.y as i128)
+        });
+        Some(Point::new(
+            sum_x.div_euclid(n) as i64,
+            sum_y.div_euclid(n) as i64,
+        ))
+    }
+
+    /// Renders the bounding box of the board using the same format accepted by `FromStr`, so the
+    /// output can be parsed back into an equivalent board (anchored at the origin). An empty board
+    /// renders as an empty string.
+    pub fn to_ascii(&self) -> String {
+        let Some((min, max)) = self.bounding_box() else {
+            return String::new();
+        };
+        let mut s = String::new();
+        for y in (min.y..=max.y).rev() {
+            for x in min.x..=max.x {
+                s.push(match self.query(&Point::new(x, y)) {
+                    Cell::Alive(_) => 'x',
+                    Cell::Dead(_) => '.',
+                });
+            }
+            s.push('\n');
+        }
+        s
+    }
+
+    /// Like `to_ascii`, but starting with a `#P x y` line giving the bottom left corner of the
+    /// grid so `from_ascii_positioned` puts the cells back exactly where they were. The header is
+    /// a comment to the plain ASCII parser, which places the grid at the origin instead.
+    pub fn to_ascii_positioned(&self) -> String {
+        match self.bounding_box() {
+            Some((min, _)) => format!(
+                "{} {} {}\n{}",
+                POSITION_PREFIX,
+                min.x,
+                min.y,
+                self.to_ascii()
+            ),
+            None => String::new(),
+        }
+    }
+
+    /// Parses the output of `to_ascii_positioned`. Without a `#P` line this is the same as
+    /// parsing plain ASCII. A position that would put cells past the edge of the world is an
+    /// `InvalidPosition` error.
+    pub fn from_ascii_positioned(s: &str) -> Result<Board, GameError> {
+        let board: Board = s.parse()?;
+        let Some(header) = s.lines().find(|line| line.starts_with(POSITION_PREFIX)) else {
+            return Ok(board);
+        };
+        let invalid = || {
+            GameError::new(GameErrorKind::InvalidPosition {
+                header: header.to_owned(),
+            })
+        };
+        let mut coords = header[POSITION_PREFIX.len()..]
+            .split_whitespace()
+            .map(|n| n.parse::<i64>().map_err(|_| invalid()));
+        let corner = match (coords.next(), coords.next(), coords.next()) {
+            (Some(x), Some(y), None) => Point::new(x?, y?),
+            _ => return Err(invalid()),
+        };
+        board
+            .iter()
+            .map(|p| {
+                Some(Point::new(
+                    p.x.checked_add(corner.x)?,
+                    p.y.checked_add(corner.y)?,
+                ))
+            })
+            .collect::<Option<Board>>()
+            .ok_or_else(invalid)
+    }
+
+    /// Draws the rectangle from `min` to `max` as an SVG image, with each cell `cell` pixels
+    /// square on a grid. Live cells outside the rectangle are left out, and an empty rectangle
+    /// gives an empty image. Rows are flipped so +y is up, as on screen.
+    pub fn to_svg(&self, min: Point, max: Point, cell: u32) -> String {
+        self.to_svg_labeled(min, max, cell, &[])
+    }
+
+    /// Like `to_svg`, with each of `labels` written in red just right of its cell, for annotated
+    /// diagrams. Labels on cells outside the rectangle are left out.
+    pub fn to_svg_labeled(
+        &self,
+        min: Point,
+        max: Point,
+        cell: u32,
+        labels: &[(Point, &str)],
+    ) -> String {
+        let cell = cell as i64;
+        let width = (max.x - min.x + 1).max(0) * cell;
+        let height = (max.y - min.y + 1).max(0) * cell;
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" \
+             viewBox=\"0 0 {width} {height}\">\n\
+             <defs><pattern id=\"grid\" width=\"{cell}\" height=\"{cell}\" \
+             patternUnits=\"userSpaceOnUse\"><path d=\"M {cell} 0 L 0 0 0 {cell}\" \
+             fill=\"none\" stroke=\"#ccc\"/></pattern></defs>\n\
+             <rect width=\"100%\" height=\"100%\" fill=\"white\"/>\n\
+             <rect width=\"100%\" height=\"100%\" fill=\"url(#grid)\"/>\n"
+        );
+        for p in self.extract(min, max).sorted() {
+            svg.push_str(&format!(
+                "<rect x=\"{}\" y=\"{}\" width=\"{cell}\" height=\"{cell}\"/>\n",
+                (p.x - min.x) * cell,
+                (max.y - p.y) * cell
+            ));
+        }
+        let inside = |p: &Point| (min.x..=max.x).contains(&p.x) && (min.y..=max.y).contains(&p.y);
+        for (at, text) in labels.iter().filter(|(at, _)| inside(at)) {
+            svg.push_str(&format!(
+                "<text x=\"{}\" y=\"{}\" font-size=\"{cell}\" fill=\"#c00\">{}</text>\n",
+                (at.x - min.x + 1) * cell,
+                (max.y - at.y + 1) * cell,
+                escape_xml(text)
+            ));
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    /// Draws the rectangle from `min` to `max` as a grayscale PNG, with each cell `cell` pixels
+    /// square, live cells black and the rest white. Dead cells in `marked` are gray, which is
+    /// how labels show up without text. Rows are flipped so +y is up, as on screen. Fails if the
+    /// image would be wider or taller than `png::MAX_DIMENSION`.
+    pub fn to_png(
+        &self,
+        min: Point,
+        max: Point,
+        cell: u32,
+        marked: &HashSet<Point>,
+    ) -> Result<Vec<u8>, GameError> {
+        let (width, height) = png::dimensions(min, max, cell)?;
+        if width == 0 || height == 0 {
+            return Ok(png::grayscale(1, 1, &[255]));
+        }
+        let mut pixels = Vec::with_capacity(width as usize * height as usize);
+        for y in (min.y..=max.y).rev() {
+            let row: Vec<u8> = (min.x..=max.x)
+                .flat_map(|x| {
+                    let p = Point::new(x, y);
+                    let shade = match (self.contains(&p), marked.contains(&p)) {
+                        (true, _) => 0,
+                        (false, true) => 160,
+                        (false, false) => 255,
+                    };
+                    std::iter::repeat_n(shade, cell as usize)
+                })
+                .collect();
+            for _ in 0..cell {
+                pixels.extend(&row);
+            }
+        }
+        Ok(png::grayscale(width, height, &pixels))
+    }
+
+    /// Births every cell on the line from `a` to `b` inclusive using Bresenham's algorithm.
+    pub fn draw_line(&mut self, a: Point, b: Point) {
+        let dx = (b.x - a.x).abs();
+        let dy = -(b.y - a.y).abs();
+        let sx = if a.x < b.x { 1 } else { -1 };
+        let sy = if a.y < b.y { 1 } else { -1 };
+        let mut err = dx + dy;
+        let mut p = a;
+        loop {
+            self.birth_cell(&p);
+            if p == b {
+                break;
+            }
+            let e2 = 2 * err;
+            if e2 >= dy {
+                err += dy;
+                p.x += sx;
+            }
+            if e2 <= dx {
+                err += dx;
+                p.y += sy;
+            }
+        }
+    }
+
+    /// Births the outline, or every cell if `filled`, of the inclusive rectangle from `min` to
+    /// `max`.
+    pub fn draw_rect(&mut self, min: Point, max: Point, filled: bool) {
+        for x in min.x..=max.x {
+            for y in min.y..=max.y {
+                if filled || x == min.x || x == max.x || y == min.y || y == max.y {
+                    self.birth_cell(&Point::new(x, y));
+                }
+            }
+        }
+    }
+
+    /// Toggles every cell in the inclusive rectangle from `min` to `max`.
+    pub fn invert_region(&mut self, min: Point, max: Point) {
+        for x in min.x..=max.x {
+            for y in min.y..=max.y {
+                self.toggle_cell(&Point::new(x, y));
+            }
+        }
+    }
+
+    /// Kills every cell in the inclusive rectangle from `min` to `max`.
+    pub fn clear_region(&mut self, min: Point, max: Point) {
+        self.board
+            .retain(|p| !((min.x..=max.x).contains(&p.x) && (min.y..=max.y).contains(&p.y)));
+    }
+
+    /// Births the outline of a circle of radius `r` using the midpoint circle algorithm.
+    pub fn draw_circle(&mut self, center: Point, r: i64) {
+        let (mut x, mut y) = (r, 0);
+        let mut err = 1 - r;
+        while x >= y {
+            for (dx, dy) in [
+                (x, y),
+                (y, x),
+                (-y, x),
+                (-x, y),
+                (-x, -y),
+                (-y, -x),
+                (y, -x),
+                (x, -y),
+            ] {
+                self.birth_cell(&(center + Point::new(dx, dy)));
+            }
+            y += 1;
+            if err < 0 {
+                err += 2 * y + 1;
+            } else {
+                x -= 1;
+                err += 2 * (y - x) + 1;
+            }
+        }
+    }
+
+    /// Returns a copy of the board with every cell moved by `offset`.
+    pub fn translate(&self, offset: Point) -> Board {
+        self.board.iter().map(|p| *p + offset).collect()
+    }
+
+    /// Returns `rows`×`columns` copies of the board laid out in a grid with `gap` empty cells
+    /// between neighbouring copies. Columns run towards +x and rows towards -y, down the screen.
+    pub fn tile(&self, rows: u32, columns: u32, gap: u32) -> Board {
+        let Some((min, max)) = self.bounding_box() else {
+            return Board::default();
+        };
+        let step = max - min + Point::new(1 + gap as i64, 1 + gap as i64);
+        let mut tiled = Board::default();
+        for row in 0..rows as i64 {
+            for column in 0..columns as i64 {
+                tiled.union_with(&self.translate(Point::new(column * step.x, -row * step.y)));
+            }
+        }
+        tiled
+    }
+
+    /// Returns a copy of the board rotated 90° clockwise about the origin.
+    pub fn rotate_cw(&self) -> Board {
+        self.board.iter().map(|p| Point::new(p.y, -p.x)).collect()
+    }
+
+    /// Returns a copy of the board mirrored left to right about the origin.
+    pub fn flip_horizontal(&self) -> Board {
+        self.reflect(Axis::Vertical, 0)
+    }
+
+    /// Returns a representative of the board's shape that's the same for every translation,
+    /// rotation, and reflection of it.
+    pub fn canonical(&self) -> Board {
+        let mut candidates = Vec::with_capacity(8);
+        let mut board = self.clone();
+        for _ in 0..4 {
+            board = board.rotate_cw();
+            candidates.push(board.normalize());
+            candidates.push(board.flip_horizontal().normalize());
+        }
+        candidates
+            .into_iter()
+            .map(|b| (b.sorted(), b))
+            .min_by(|(a, _), (b, _)| a.cmp(b))
+            .map(|(_, b)| b)
+            .unwrap_or_default()
+    }
+
+    /// Hash of the live cells that's the same across runs and doesn't depend on the order cells
+    /// were inserted in. Unlike `canonical`, moving the pattern changes the hash.
+    pub fn stable_hash(&self) -> u64 {
+        // Summing mixed per-cell hashes makes the result independent of iteration order
+        self.board
+            .iter()
+            .map(|p| Rng::new(Rng::new(p.x as u64).next_u64() ^ p.y as u64).next_u64())
+            .fold(0, u64::wrapping_add)
+    }
+
+    /// Live cells in a deterministic order.
+    pub fn sorted(&self) -> Vec<Point> {
+        let mut points: Vec<_> = self.board.iter().copied().collect();
+        points.sort();
+        points
+    }
+
+    /// Fills a `width` by `height` rectangle starting at `min` with cells alive with probability
+    /// `density`.
+    pub fn random(min: Point, width: u16, height: u16, density: f64, rng: &mut Rng) -> Board {
+        let mut board = Board::default();
+        for y in 0..height as i64 {
+            for x in 0..width as i64 {
+                if rng.chance(density) {
+                    board.birth_cell(&(min + Point::new(x, y)));
+                }
+            }
+        }
+        board
+    }
+
+    /// Returns a copy of the board translated so its bounding box starts at the origin.
+    pub fn normalize(&self) -> Board {
+        match self.bounding_box() {
+            Some((min, _)) => self.translate(Point::default() - min),
+            None => Board::default(),
+        }
+    }
+
+    /// Whether `other` is this board moved somewhere else, or the same board. Unlike comparing
+    /// `canonical` boards, rotations and reflections don't count.
+    pub fn same_shape(&self, other: &Board) -> bool {
+        self.population() == other.population() && self.normalize() == other.normalize()
+    }
+
+    /// Returns a copy of the board reflected across the `axis` passing through `about`.
+    pub fn reflect(&self, axis: Axis, about: i64) -> Board {
+        self.board.iter().map(|p| p.reflect(axis, about)).collect()
+    }
+
+    /// Adds the reflection of the board across the `axis` passing through `about`, making it
+    /// symmetric about that axis.
+    pub fn symmetrize(&mut self, axis: Axis, about: i64) {
+        let reflection = self.reflect(axis, about);
+        self.union_with(&reflection);
+    }
+
+    /// Births every cell alive in `other`.
+    pub fn union_with(&mut self, other: &Board) {
+        self.board.extend(other.board.iter().copied());
+    }
+
+    /// Cells that differ going from `self` to `other`, as (added, removed). Unlike the births and
+    /// deaths tracked by `GameOfLife::step`, this works between any two boards.
+    pub fn diff(&self, other: &Board) -> (HashSet<Point>, HashSet<Point>) {
+        (
+            other.board.difference(&self.board).copied().collect(),
+            self.board.difference(&other.board).copied().collect(),
+        )
+    }
+
+    /// Copy of the live cells within the rectangle from `min` to `max`, inclusive.
+    pub fn extract(&self, min: Point, max: Point) -> Board {
+        self.board
+            .iter()
+            .filter(|p| (min.x..=max.x).contains(&p.x) && (min.y..=max.y).contains(&p.y))
+            .copied()
+            .collect()
+    }
+
     pub fn window(
         &self,
         point: Point,
@@ -113,8 +565,9 @@ impl Board {
         height: u16,
     ) -> impl Iterator<Item = (&Point, u16, u16)> + '_ {
         self.board.iter().filter_map(move |p| {
-            let dx = p.x - point.x;
-            let dy = p.y - point.y;
+            // Cells too far away to subtract can't be in the window anyway
+            let dx = p.x.checked_sub(point.x)?;
+            let dy = p.y.checked_sub(point.y)?;
             if dx >= 0 && dx < width.into() && dy >= 0 && dy < height.into() {
                 Some((p, dx as u16, dy as u16))
             } else {
@@ -124,6 +577,48 @@ impl Board {
     }
 }
 
+impl FromIterator<Point> for Board {
+    fn from_iter<T: IntoIterator<Item = Point>>(iter: T) -> Self {
+        Board {
+            board: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl Extend<Point> for Board {
+    fn extend<T: IntoIterator<Item = Point>>(&mut self, iter: T) {
+        self.board.extend(iter);
+    }
+}
+
+impl Board {
+    /// Like `window`, but each screen cell covers a `scale`×`scale` block of world cells starting
+    /// at `origin`. Yields each screen cell whose block contains any live cell, once.
+    pub fn window_scaled(
+        &self,
+        origin: Point,
+        width: u16,
+        height: u16,
+        scale: u16,
+    ) -> impl Iterator<Item = (u16, u16)> + '_ {
+        let scale = scale.max(1) as i64;
+        let lit: HashSet<(u16, u16)> = self
+            .board
+            .iter()
+            .filter_map(|p| {
+                let dx = p.x.checked_sub(origin.x)?.div_euclid(scale);
+                let dy = p.y.checked_sub(origin.y)?.div_euclid(scale);
+                if dx >= 0 && dx < width.into() && dy >= 0 && dy < height.into() {
+                    Some((dx as u16, dy as u16))
+                } else {
+                    None
+                }
+            })
+            .collect();
+        lit.into_iter()
+    }
+}
+
 impl<const N: usize> From<[Point; N]> for Board {
     fn from(value: [Point; N]) -> Self {
         Board {
@@ -132,8 +627,59 @@ impl<const N: usize> From<[Point; N]> for Board {
     }
 }
 
-/// Builds board from string in the +x +y quadrant where '.' represents a dead cell and 'x'
-/// represents an alive one. Any other characters would result in an error.
+impl Board {
+    /// Parses the ASCII grid format described on `FromStr`. When `strict`, only '.' and 'x' are
+    /// accepted and comments aren't allowed.
+    pub fn parse_ascii(s: &str, strict: bool) -> Result<Board, GameError> {
+        let lines: Vec<_> = s
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| strict || !line.starts_with('#'))
+            .collect();
+        let mut board = Board::default();
+        for (y, (line_num, line)) in lines.into_iter().rev().enumerate() {
+            for (x, char) in line.chars().enumerate() {
+                match char {
+                    '.' => {} // Do nothing, dead cell
+                    ' ' if !strict => {}
+                    'x' => board.birth_cell(&(x as i64, y as i64).into()),
+                    c => {
+                        return Err(GameError::new(GameErrorKind::InvalidBoardChar {
+                            c,
+                            s: s.to_owned(),
+                            line: u16::try_from(line_num).unwrap_or(u16::MAX),
+                            col: u16::try_from(x).unwrap_or(u16::MAX),
+                        }))
+                    }
+                }
+            }
+        }
+        Ok(board)
+    }
+
+    /// Parses like `FromStr`, but first rejects grids wider or taller than `max` without allocating
+    /// any cells.
+    pub fn parse_bounded(s: &str, max: usize) -> Result<Board, GameError> {
+        let (width, height) = s
+            .lines()
+            .filter(|line| !line.starts_with('#'))
+            .fold((0, 0), |(width, height), line| {
+                (width.max(line.chars().count()), height + 1)
+            });
+        if width > max || height > max {
+            return Err(GameError::new(GameErrorKind::BoardTooLarge {
+                width,
+                height,
+                max,
+            }));
+        }
+        s.parse()
+    }
+}
+
+/// Builds board from string in the +x +y quadrant where '.' or ' ' represents a dead cell and 'x'
+/// represents an alive one. Lines starting with '#' are comments and are skipped. Any other
+/// characters would result in an error, see `Board::parse_ascii` for a stricter parser.
 /// Lines are along the y-axis and chars are along the x-axis. The board can be naturally written
 /// meaning the first line is the line with the maximum y value.
 ///
@@ -148,22 +694,425 @@ impl FromStr for Board {
     type Err = GameError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Board::parse_ascii(s, false)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::patterns::QUEEN_BEE;
+
+    #[test]
+    fn ascii_round_trip() {
+        let board: Board = QUEEN_BEE.parse().unwrap();
+        assert_eq!(board.to_ascii().parse::<Board>().unwrap(), board);
+        assert_eq!(board.to_ascii(), format!("{}\n", QUEEN_BEE));
+        assert_eq!(Board::default().to_ascii(), "");
+    }
+
+    #[test]
+    fn ascii_positioned_round_trip() {
+        let glider: Board = ".x.\n..x\nxxx".parse().unwrap();
+        for offset in [Point::new(50, 50), Point::new(-7, 3), Point::default()] {
+            let board = glider.translate(offset);
+            let s = board.to_ascii_positioned();
+            assert_eq!(Board::from_ascii_positioned(&s).unwrap(), board);
+            // Still readable as plain ASCII, just not in place
+            assert_eq!(s.parse::<Board>().unwrap(), glider);
+        }
+        assert_eq!(
+            glider.translate(Point::new(50, 50)).to_ascii_positioned(),
+            "#P 50 50\n.x.\n..x\nxxx\n"
+        );
+        assert_eq!(
+            Board::from_ascii_positioned("xxx").unwrap(),
+            "xxx".parse().unwrap()
+        );
+        assert_eq!(Board::default().to_ascii_positioned(), "");
+        assert!(Board::from_ascii_positioned("#P 1\nx").is_err());
+        assert!(Board::from_ascii_positioned("#P 1 2 3\nx").is_err());
+        assert!(Board::from_ascii_positioned("#P a b\nx").is_err());
+
+        let edge = format!("#P {} {}\nx", i64::MAX, i64::MIN);
+        assert_eq!(
+            Board::from_ascii_positioned(&edge).unwrap(),
+            Board::from([Point::new(i64::MAX, i64::MIN)])
+        );
+        let past = format!("#P {} 0\nxx", i64::MAX);
+        assert!(matches!(
+            Board::from_ascii_positioned(&past).unwrap_err().kind(),
+            GameErrorKind::InvalidPosition { .. }
+        ));
+    }
+
+    #[test]
+    fn neighbors_at_extremes() {
+        let board = Board::from([(i64::MAX, i64::MAX).into(), (i64::MAX - 1, i64::MAX).into()]);
+        assert_eq!(board.neighbors(&(i64::MAX, i64::MAX).into()).count(), 3);
+        assert_eq!(board.neighbors(&(i64::MAX, i64::MAX).into()).len(), 3);
+        assert_eq!(board.neighbors(&(0, i64::MIN).into()).len(), 5);
+        let mut interior = board.neighbors(&(0, 0).into());
+        assert_eq!(interior.len(), 8);
+        interior.next();
+        assert_eq!(interior.size_hint(), (7, Some(7)));
+        assert_eq!(board.live_neighbor_count(&(i64::MAX, i64::MAX).into()), 1);
+        assert_eq!(board.neighbors(&(i64::MIN, 0).into()).count(), 5);
+        assert_eq!(board.neighbors(&(0, 0).into()).count(), 8);
+
+        let mut game = crate::game::GameOfLife {
+            board,
+            ..Default::default()
+        };
+        game.step();
+        assert!(game.board.is_empty());
+    }
+
+    #[test]
+    fn moore_includes_center() {
+        let board: Board = "xx\nx.".parse().unwrap();
+        let center = Point::new(0, 1);
+        assert_eq!(board.neighbors(&center).count(), 8);
+        assert_eq!(board.moore(&center).count(), 9);
+        assert_eq!(board.moore(&center).next(), Some(Cell::Alive(center)));
+        let alive = |cells: &mut dyn Iterator<Item = Cell>| {
+            cells.filter(|cell| matches!(cell, Cell::Alive(_))).count()
+        };
+        assert_eq!(alive(&mut board.neighbors(&center)), 2);
+        assert_eq!(alive(&mut board.moore(&center)), 3);
+    }
+
+    #[test]
+    fn diff_blinker() {
+        let vertical: Board = "x\nx\nx\n".parse().unwrap();
+        let mut game = crate::game::GameOfLife {
+            board: vertical.clone(),
+            ..Default::default()
+        };
+        game.step();
+        let (added, removed) = vertical.diff(&game.board);
+        assert_eq!(added, HashSet::from([(-1, 1).into(), (1, 1).into()]));
+        assert_eq!(removed, HashSet::from([(0, 0).into(), (0, 2).into()]));
+        assert_eq!(game.board.diff(&vertical), (removed, added));
+        assert_eq!(vertical.diff(&vertical), Default::default());
+    }
+
+    #[test]
+    fn window_far_from_origin() {
+        let board = Board::from([
+            (i64::MIN, i64::MIN).into(),
+            (i64::MAX, i64::MAX).into(),
+            (i64::MAX - 1, i64::MAX).into(),
+        ]);
+        let corner = Point::new(i64::MAX - 2, i64::MAX - 2);
+        let mut cells: Vec<_> = board.window(corner, 4, 4).map(|(p, ..)| *p).collect();
+        cells.sort();
+        assert_eq!(
+            cells,
+            [(i64::MAX - 1, i64::MAX).into(), (i64::MAX, i64::MAX).into()]
+        );
+        assert_eq!(board.window_scaled(corner, 4, 4, 2).count(), 2);
+        let corner = Point::new(i64::MAX, i64::MIN);
+        assert_eq!(board.window(corner, 4, 4).count(), 0);
+        assert_eq!(board.window_scaled(corner, 4, 4, 3).count(), 0);
+    }
+
+    #[test]
+    fn window_scaled() {
+        let board: Board = "x...\n....\n.x..\n...x\n".parse().unwrap();
+        let mut lit: Vec<_> = board.window_scaled((0, 0).into(), 2, 2, 2).collect();
+        lit.sort();
+        assert_eq!(lit, [(0, 0), (0, 1), (1, 0)]);
+
+        let mut lit: Vec<_> = board.window_scaled((-2, 0).into(), 2, 2, 2).collect();
+        lit.sort();
+        assert_eq!(lit, [(1, 0), (1, 1)]);
+
+        let mut lit: Vec<_> = board.window_scaled((0, 0).into(), 1, 1, 4).collect();
+        lit.sort();
+        assert_eq!(lit, [(0, 0)]);
+    }
+
+    #[test]
+    fn inspect() {
+        let block: Board = "xx\nxx".parse().unwrap();
+        assert_eq!(
+            block.inspect(&(0, 0).into()),
+            CellInfo {
+                pos: (0, 0).into(),
+                alive: true,
+                live_neighbors: 3
+            }
+        );
+        let info = block.inspect(&(2, 1).into());
+        assert!(!info.alive);
+        assert_eq!(info.live_neighbors, 2);
+    }
+
+    #[test]
+    fn collect() {
+        let mut board: Board = (0..10).map(|x| Point::new(x, 0)).collect();
+        assert_eq!(board.population(), 10);
+        board.extend((5..15).map(|x| Point::new(x, 0)));
+        assert_eq!(board.population(), 15);
+        assert_eq!(board.bounding_box(), Some(((0, 0).into(), (14, 0).into())));
+    }
+
+    #[test]
+    fn draw_primitives() {
         let mut board = Board::default();
-        for (y, line) in s.lines().rev().enumerate() {
-            for (x, char) in line.chars().enumerate() {
-                match char {
-                    '.' => {} // Do nothing, dead cell
-                    'x' => board.birth_cell(&(x as i64, y as i64).into()),
-                    c => {
-                        return Err(GameError::new(GameErrorKind::InvalidBoardChar {
-                            c,
-                            s: s.to_owned(),
-                            line: y as u16,
-                        }))
-                    }
-                }
+        board.draw_line((0, 0).into(), (3, 0).into());
+        assert_eq!(board.to_ascii(), "xxxx\n");
+
+        let mut board = Board::default();
+        board.draw_line((3, 1).into(), (0, 0).into());
+        assert_eq!(board.to_ascii(), "..xx\nxx..\n");
+
+        let mut board = Board::default();
+        board.draw_line((0, 0).into(), (-2, -2).into());
+        assert_eq!(board.to_ascii(), "..x\n.x.\nx..\n");
+
+        let mut board = Board::default();
+        board.draw_rect((0, 0).into(), (2, 2).into(), false);
+        assert_eq!(board.to_ascii(), "xxx\nx.x\nxxx\n");
+        board.draw_rect((0, 0).into(), (2, 2).into(), true);
+        assert_eq!(board.population(), 9);
+
+        let mut board = Board::default();
+        board.draw_circle((0, 0).into(), 2);
+        assert_eq!(board.to_ascii(), ".xxx.\nx...x\nx...x\nx...x\n.xxx.\n");
+    }
+
+    #[test]
+    fn invert_region() {
+        let mut board = Board::from([(0i64, 0i64).into(), (5, 5).into()]);
+        board.invert_region((0, 0).into(), (1, 1).into());
+        assert_eq!(board.to_ascii().lines().count(), 6);
+        assert_eq!(board.population(), 4);
+        assert!(!board.contains(&(0, 0).into()));
+        assert!(board.contains(&(5, 5).into()));
+    }
+
+    #[test]
+    fn symmetrize() {
+        let mut board = Board::from([(3i64, 1i64).into()]);
+        board.symmetrize(Axis::Vertical, 0);
+        assert_eq!(board, Board::from([(3i64, 1i64).into(), (-3, 1).into()]));
+        board.symmetrize(Axis::Horizontal, 2);
+        assert_eq!(board.population(), 4);
+        assert!(board.contains(&(-3, 3).into()));
+
+        // Cells on the axis are their own reflection
+        let mut board = Board::from([(0i64, 0i64).into()]);
+        board.symmetrize(Axis::Vertical, 0);
+        assert_eq!(board.population(), 1);
+    }
+
+    #[test]
+    fn comments_and_spaces() {
+        let s = "# Glider\n x\n# heading down-right\n  x  \nxxx";
+        assert_eq!(
+            s.parse::<Board>().unwrap(),
+            ".x.\n..x\nxxx".parse().unwrap()
+        );
+        assert!(Board::parse_ascii(s, true).is_err());
+        assert!(Board::parse_ascii(".x.\n. x", true).is_err());
+        assert!("x?".parse::<Board>().is_err());
+    }
+
+    #[test]
+    fn parse_bounded() {
+        assert_eq!(
+            Board::parse_bounded("x.x\n.x.", 3).unwrap(),
+            "x.x\n.x.".parse().unwrap()
+        );
+        let err = Board::parse_bounded("x...x\n.x.x.", 4).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Board of 5x2 exceeds the maximum dimension of 4"
+        );
+        assert!(Board::parse_bounded("x\n.\n.\nx", 3).is_err());
+    }
+
+    #[test]
+    fn canonical() {
+        let glider: Board = ".x.\n..x\nxxx".parse().unwrap();
+        let canonical = glider.canonical();
+        let mut board = glider.translate((-7, 3).into());
+        for _ in 0..4 {
+            board = board.rotate_cw();
+            assert_eq!(board.canonical(), canonical);
+            assert_eq!(board.flip_horizontal().canonical(), canonical);
+        }
+        assert_ne!(Board::from([(0i64, 0i64).into()]).canonical(), canonical);
+    }
+
+    #[test]
+    fn same_shape() {
+        let glider: Board = ".x.\n..x\nxxx".parse().unwrap();
+        assert!(glider.same_shape(&glider));
+        assert!(glider.same_shape(&glider.translate((-7, 3).into())));
+        assert!(!glider.same_shape(&glider.rotate_cw()));
+        assert!(Board::default().same_shape(&Board::default()));
+        assert!(!glider.same_shape(&Board::default()));
+    }
+
+    #[test]
+    fn random() {
+        let board = Board::random((5, 5).into(), 10, 4, 0.5, &mut Rng::new(1));
+        assert_eq!(
+            board,
+            Board::random((5, 5).into(), 10, 4, 0.5, &mut Rng::new(1))
+        );
+        let (min, max) = board.bounding_box().unwrap();
+        assert!(min.x >= 5 && min.y >= 5 && max.x < 15 && max.y < 9);
+        assert_eq!(
+            Board::random(Point::default(), 3, 3, 1.0, &mut Rng::new(1)).population(),
+            9
+        );
+    }
+
+    #[test]
+    fn extract() {
+        let board: Board = "x..x\n.x..\nx..x".parse().unwrap();
+        let inner = board.extract(Point::new(0, 1), Point::new(2, 2));
+        assert_eq!(inner, Board::from([(0i64, 2i64).into(), (1, 1).into()]));
+        assert_eq!(board.extract(Point::new(-5, -5), Point::new(5, 5)), board);
+        assert!(board.extract(Point::new(1, 0), Point::new(0, 0)).is_empty());
+    }
+
+    #[test]
+    fn svg() {
+        let board = Board::from([Point::new(0, 0), Point::new(2, 1), Point::new(9, 9)]);
+        let svg = board.to_svg(Point::new(0, 0), Point::new(2, 1), 10);
+        assert!(
+            svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"30\" height=\"20\"")
+        );
+        let rects: Vec<_> = svg.lines().filter(|l| l.starts_with("<rect x=")).collect();
+        assert_eq!(
+            rects,
+            [
+                "<rect x=\"0\" y=\"10\" width=\"10\" height=\"10\"/>",
+                "<rect x=\"20\" y=\"0\" width=\"10\" height=\"10\"/>",
+            ]
+        );
+        assert!(svg.ends_with("</svg>\n"));
+
+        let empty = Board::default().to_svg(Point::new(0, 0), Point::new(-1, -1), 10);
+        assert!(empty.contains("width=\"0\" height=\"0\""));
+        assert!(!empty.contains("<rect x="));
+        assert!(empty.ends_with("</svg>\n"));
+
+        let labeled = board.to_svg_labeled(
+            Point::new(0, 0),
+            Point::new(2, 1),
+            10,
+            &[(Point::new(0, 0), "dies <next>"), (Point::new(9, 9), "off")],
+        );
+        let texts: Vec<_> = labeled.lines().filter(|l| l.starts_with("<text")).collect();
+        assert_eq!(
+            texts,
+            ["<text x=\"10\" y=\"20\" font-size=\"10\" fill=\"#c00\">dies &lt;next&gt;</text>"]
+        );
+    }
+
+    #[test]
+    fn png() {
+        let board = Board::from([Point::new(0, 0), Point::new(2, 1)]);
+        let marked = HashSet::from([Point::new(1, 0)]);
+        let png = board
+            .to_png(Point::new(0, 0), Point::new(2, 1), 2, &marked)
+            .unwrap();
+        assert!(png.starts_with(b"\x89PNG"));
+        // IHDR width and height, 3×2 cells at 2 pixels each
+        assert_eq!(&png[16..24], &[0, 0, 0, 6, 0, 0, 0, 4]);
+        let none = HashSet::new();
+        let empty = Board::default()
+            .to_png(Point::new(0, 0), Point::new(-1, -1), 2, &none)
+            .unwrap();
+        assert_eq!(&empty[16..24], &[0, 0, 0, 1, 0, 0, 0, 1]);
+        let far = (Point::new(i64::MIN, 0), Point::new(i64::MAX, 0));
+        assert!(board.to_png(far.0, far.1, 2, &none).is_err());
+    }
+
+    #[test]
+    fn tile() {
+        let cell = Board::from([Point::new(0, 0)]);
+        let tiled = cell.tile(3, 3, 2);
+        let mut expected = Vec::new();
+        for x in [0, 3, 6] {
+            for y in [-6, -3, 0] {
+                expected.push(Point::new(x, y));
             }
         }
-        Ok(board)
+        assert_eq!(tiled.sorted(), expected);
+
+        let block: Board = "xx\nxx".parse().unwrap();
+        assert_eq!(block.tile(1, 2, 1).to_ascii(), "xx.xx\nxx.xx\n");
+        assert!(block.tile(0, 5, 1).is_empty());
+        assert!(Board::default().tile(2, 2, 0).is_empty());
+    }
+
+    #[test]
+    fn clear_region() {
+        let mut board: Board = "x..x\n.x..\nx..x".parse().unwrap();
+        board.clear_region(Point::new(0, 1), Point::new(2, 2));
+        assert_eq!(board.to_ascii(), "...x\n....\nx..x\n");
+    }
+
+    #[test]
+    fn stable_hash() {
+        let points: Vec<Point> = (0..50).map(|i| Point::new(i % 7, i / 7 - 3)).collect();
+        let forward: Board = points.iter().copied().collect();
+        let mut backward = Board::default();
+        for p in points.iter().rev() {
+            backward.birth_cell(p);
+        }
+        assert_eq!(forward.stable_hash(), backward.stable_hash());
+
+        assert_eq!(Board::default().stable_hash(), 0);
+        let glider: Board = ".x.\n..x\nxxx".parse().unwrap();
+        assert_ne!(
+            glider.stable_hash(),
+            glider.translate(Point::new(1, 0)).stable_hash()
+        );
+        assert_ne!(glider.stable_hash(), glider.rotate_cw().stable_hash());
+        // Swapping coordinates gives a different cell
+        assert_ne!(
+            Board::from([(1i64, 2i64).into()]).stable_hash(),
+            Board::from([(2i64, 1i64).into()]).stable_hash()
+        );
+    }
+
+    #[test]
+    fn center_of_mass() {
+        assert_eq!(Board::default().center_of_mass(), None);
+        let plus = Board::from([
+            (5i64, 5i64).into(),
+            (4, 5).into(),
+            (6, 5).into(),
+            (5, 4).into(),
+            (5, 6).into(),
+        ]);
+        assert_eq!(plus.center_of_mass(), Some(Point::new(5, 5)));
+        let block: Board = "xx\nxx".parse().unwrap();
+        assert_eq!(
+            block.translate((-3, -3).into()).center_of_mass(),
+            Some(Point::new(-3, -3))
+        );
+    }
+
+    #[test]
+    fn transforms() {
+        let glider: Board = ".x.\n..x\nxxx".parse().unwrap();
+        assert_eq!(
+            glider.translate((2, -1).into()).normalize(),
+            glider.normalize()
+        );
+        assert_eq!(glider.rotate_cw().normalize().to_ascii(), "x..\nx.x\nxx.\n");
+
+        let mut board = Board::from([(0i64, 0i64).into()]);
+        board.union_with(&Board::from([(0i64, 0i64).into(), (1, 0).into()]));
+        assert_eq!(board.population(), 2);
     }
 }
@@ -0,0 +1,250 @@
+use std::{fmt::Display, path::PathBuf};
+
+use crate::{
+    format::Format,
+    point::{Point, YAxis},
+};
+
+/// Command line options.
+///
+/// ```text
+/// game-of-life-rs [--stdin] [--print] [--rle] [--generations N] [--ascii] [--y-down]
+///                 [--max-fps N] [--info-bottom] [--min-zoom N] [--max-zoom N] [--density FILE]
+/// game-of-life-rs --validate FILE
+/// game-of-life-rs --hash FILE [--generations N]
+/// game-of-life-rs --compare FILE FILE [--generations N]
+/// game-of-life-rs [--stdin] --capture FILE [--generations N] [--label X Y TEXT]...
+/// game-of-life-rs [--record FILE | --replay FILE [--print]] [--listen ADDR]
+/// ```
+#[derive(Debug, PartialEq, Eq)]
+pub struct Args {
+    /// Read the starting pattern from stdin instead of using the default board, which
+    /// `GOL_DEFAULT_PATTERN` can set to a built-in pattern name or a file
+    pub stdin: bool,
+    /// Run headlessly and print the final board instead of starting the TUI
+    pub print: bool,
+    /// Number of generations to run when headless
+    pub generations: u32,
+    /// Format the final board is printed in
+    pub output: Format,
+    /// Draw the board with ASCII characters only, for terminals without Unicode
+    pub ascii: bool,
+    /// Show coordinates with y increasing downwards, like the screen
+    pub y_axis: YAxis,
+    /// Cap on terminal redraws per second, to save bandwidth over slow connections
+    pub max_fps: Option<u32>,
+    /// Show the info panel as a bar below the board, for wide but short terminals
+    pub info_bottom: bool,
+    /// Bounds on world cells per screen cell that the zoom keys stay within
+    pub min_zoom: Option<u16>,
+    pub max_zoom: Option<u16>,
+    /// Check that this pattern file parses and print a summary of it instead of starting the TUI
+    pub validate: Option<PathBuf>,
+    /// Run this pattern file for `generations` and print a hash of the final board instead of
+    /// starting the TUI
+    pub hash: Option<PathBuf>,
+    /// Run both pattern files for `generations` and print whether the final boards are equal,
+    /// the same shape somewhere else, or differ, exiting with an error if they differ
+    pub compare: Option<(PathBuf, PathBuf)>,
+    /// Save a session of everything done to the game to this file on exit
+    pub record: Option<PathBuf>,
+    /// Replay a recorded session, printing the final board if `print` is set
+    pub replay: Option<PathBuf>,
+    /// Count how many generations each cell is alive for and write the counts to this file at
+    /// the end of the run, as a PNG heatmap if it ends in `.png` and CSV otherwise
+    pub density: Option<PathBuf>,
+    /// Run the starting pattern for `generations` and write it with `labels` to this `.svg` or
+    /// `.png` file instead of starting the TUI
+    pub capture: Option<PathBuf>,
+    /// Notes attached to cells in `capture`, each given as `--label X Y TEXT`. Coordinates
+    /// follow `y_axis`.
+    pub labels: Vec<(Point, String)>,
+    /// Accept step, play, pause and load requests over TCP on this address, e.g.
+    /// `127.0.0.1:7000`, or on 127.0.0.1 if it's just a port. Needs the `remote` feature.
+    pub listen: Option<String>,
+}
+
+impl Default for Args {
+    fn default() -> Self {
+        Args {
+            stdin: false,
+            print: false,
+            generations: 0,
+            output: Format::Ascii,
+            ascii: false,
+            y_axis: YAxis::Up,
+            max_fps: None,
+            info_bottom: false,
+            min_zoom: None,
+            max_zoom: None,
+            validate: None,
+            hash: None,
+            compare: None,
+            record: None,
+            replay: None,
+            density: None,
+            capture: None,
+            labels: Vec::new(),
+            listen: None,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ArgsError {
+    Unknown(String),
+    MissingValue(&'static str),
+    InvalidValue { flag: &'static str, value: String },
+}
+
+impl Display for ArgsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ArgsError::Unknown(arg) => f.write_fmt(format_args!("Unknown argument `{}`", arg)),
+            ArgsError::MissingValue(flag) => {
+                f.write_fmt(format_args!("Missing value for `{}`", flag))
+            }
+            ArgsError::InvalidValue { flag, value } => {
+                f.write_fmt(format_args!("Invalid value `{}` for `{}`", value, flag))
+            }
+        }
+    }
+}
+
+impl std::error::Error for ArgsError {}
+
+fn value<T: std::str::FromStr>(
+    flag: &'static str,
+    args: &mut impl Iterator<Item = String>,
+) -> Result<T, ArgsError> {
+    let value = args.next().ok_or(ArgsError::MissingValue(flag))?;
+    value
+        .parse()
+        .map_err(|_| ArgsError::InvalidValue { flag, value })
+}
+
+impl Args {
+    /// Parses arguments, not including the program name.
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, ArgsError> {
+        let mut parsed = Args::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--stdin" => parsed.stdin = true,
+                "--print" => parsed.print = true,
+                "--rle" => parsed.output = Format::Rle,
+                "--ascii" => parsed.ascii = true,
+                "--y-down" => parsed.y_axis = YAxis::Down,
+                "--info-bottom" => parsed.info_bottom = true,
+                "--min-zoom" => parsed.min_zoom = Some(value("--min-zoom", &mut args)?),
+                "--max-zoom" => parsed.max_zoom = Some(value("--max-zoom", &mut args)?),
+                "--generations" => parsed.generations = value("--generations", &mut args)?,
+                "--max-fps" => parsed.max_fps = Some(value("--max-fps", &mut args)?),
+                "--validate" => parsed.validate = Some(value("--validate", &mut args)?),
+                "--hash" => parsed.hash = Some(value("--hash", &mut args)?),
+                "--compare" => {
+                    parsed.compare = Some((
+                        value("--compare", &mut args)?,
+                        value("--compare", &mut args)?,
+                    ))
+                }
+                "--record" => parsed.record = Some(value("--record", &mut args)?),
+                "--replay" => parsed.replay = Some(value("--replay", &mut args)?),
+                "--density" => parsed.density = Some(value("--density", &mut args)?),
+                "--capture" => parsed.capture = Some(value("--capture", &mut args)?),
+                "--label" => {
+                    let at = Point::new(value("--label", &mut args)?, value("--label", &mut args)?);
+                    parsed.labels.push((at, value("--label", &mut args)?));
+                }
+                "--listen" => parsed.listen = Some(value("--listen", &mut args)?),
+                _ => return Err(ArgsError::Unknown(arg)),
+            }
+        }
+        Ok(parsed)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn parse(s: &str) -> Result<Args, ArgsError> {
+        Args::parse(s.split_whitespace().map(str::to_owned))
+    }
+
+    #[test]
+    fn flags() {
+        assert_eq!(parse("").unwrap(), Args::default());
+        assert_eq!(
+            parse("--stdin --generations 100 --print --rle --ascii --y-down").unwrap(),
+            Args {
+                stdin: true,
+                print: true,
+                generations: 100,
+                output: Format::Rle,
+                ascii: true,
+                y_axis: YAxis::Down,
+                max_fps: None,
+                info_bottom: false,
+                min_zoom: None,
+                max_zoom: None,
+                validate: None,
+                hash: None,
+                compare: None,
+                record: None,
+                replay: None,
+                density: None,
+                capture: None,
+                labels: Vec::new(),
+                listen: None,
+            }
+        );
+        assert_eq!(parse("--max-fps 30").unwrap().max_fps, Some(30));
+        assert_eq!(
+            parse("--listen 127.0.0.1:7000").unwrap().listen.as_deref(),
+            Some("127.0.0.1:7000")
+        );
+        assert!(parse("--info-bottom").unwrap().info_bottom);
+        let zoom = parse("--min-zoom 2 --max-zoom 16").unwrap();
+        assert_eq!((zoom.min_zoom, zoom.max_zoom), (Some(2), Some(16)));
+        assert!(parse("--max-fps fast").is_err());
+        assert_eq!(
+            parse("--validate glider.rle").unwrap().validate,
+            Some(PathBuf::from("glider.rle"))
+        );
+        let hash = parse("--hash glider.rle --generations 50").unwrap();
+        assert_eq!(hash.hash, Some(PathBuf::from("glider.rle")));
+        assert_eq!(hash.generations, 50);
+        assert_eq!(
+            parse("--compare a.rle b.rle").unwrap().compare,
+            Some((PathBuf::from("a.rle"), PathBuf::from("b.rle")))
+        );
+        assert_eq!(
+            parse("--compare a.rle"),
+            Err(ArgsError::MissingValue("--compare"))
+        );
+        let capture = parse("--capture glider.svg --label 1 -2 here --label 0 0 origin").unwrap();
+        assert_eq!(capture.capture, Some(PathBuf::from("glider.svg")));
+        assert_eq!(
+            capture.labels,
+            [
+                (Point::new(1, -2), "here".to_owned()),
+                (Point::new(0, 0), "origin".to_owned())
+            ]
+        );
+        assert_eq!(
+            parse("--label 1 2"),
+            Err(ArgsError::MissingValue("--label"))
+        );
+        assert_eq!(
+            parse("--replay bug.session --print").unwrap().replay,
+            Some(PathBuf::from("bug.session"))
+        );
+        assert_eq!(
+            parse("--generations"),
+            Err(ArgsError::MissingValue("--generations"))
+        );
+        assert!(parse("--generations ten").is_err());
+        assert!(parse("--nope").is_err());
+    }
+}
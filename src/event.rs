@@ -1,16 +1,29 @@
 use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap},
+    path::PathBuf,
     sync::{
-        atomic::AtomicBool,
         mpsc::{channel, Iter, Receiver, Sender},
-        Arc, RwLock,
+        Arc, Condvar, Mutex,
     },
     thread::{self, JoinHandle},
     time::{Duration, Instant},
 };
 
+use crate::{
+    action::Action,
+    analysis::DensityMap,
+    board::Board,
+    gallery::Gallery,
+    game::{GameOfLife, StepReport},
+    jump::JumpOutcome,
+    point::Point,
+    program::Command,
+};
+
 use crossterm::event::{
-    poll, read,
-    Event::{Key, Mouse},
+    read,
+    Event::{Key, Mouse, Resize},
     KeyEvent, MouseEvent,
 };
 
@@ -41,6 +54,14 @@ impl Listener {
     pub fn next(&self) -> Result<Event, std::sync::mpsc::RecvError> {
         self.receiver.recv()
     }
+
+    /// Like `next`, giving up after `timeout` without an event.
+    pub fn next_timeout(
+        &self,
+        timeout: Duration,
+    ) -> Result<Event, std::sync::mpsc::RecvTimeoutError> {
+        self.receiver.recv_timeout(timeout)
+    }
 }
 
 pub struct IoProducer {
@@ -53,6 +74,7 @@ impl IoProducer {
             match read() {
                 Ok(Key(e)) => sender.send(e.into()).unwrap(),
                 Ok(Mouse(e)) => sender.send(e.into()).unwrap(),
+                Ok(Resize(width, height)) => sender.send(Event::Resize { width, height }).unwrap(),
                 _ => (),
             };
         });
@@ -60,42 +82,190 @@ impl IoProducer {
     }
 }
 
-pub struct Timer {
+/// Identifies a timer added to a `Scheduler`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TimerId(u64);
+
+#[derive(Debug)]
+struct Timer {
+    period: Duration,
+    event: Event,
+    due: Instant,
+    last_sent: Option<Instant>,
+}
+
+#[derive(Debug, Default)]
+struct SchedulerState {
+    timers: HashMap<TimerId, Timer>,
+    /// When each timer is next due, earliest first. Entries left behind by a reschedule or
+    /// cancel no longer match their timer's `due` and are skipped.
+    queue: BinaryHeap<Reverse<(Instant, TimerId)>>,
+    next_id: u64,
+    shutdown: bool,
+}
+
+/// Sends each timer's event every period, all from one thread. The thread waits on a condition
+/// variable until the earliest timer is due, so adding, cancelling and rescheduling take effect
+/// on the wait already in progress. Dropping the scheduler stops every timer.
+pub struct Scheduler {
     pub thread: JoinHandle<()>,
-    lock: Arc<RwLock<Duration>>,
+    state: Arc<(Mutex<SchedulerState>, Condvar)>,
 }
 
-impl Timer {
-    pub fn spawn(sender: EventSender, period: Duration, event: Event) -> Self {
-        let lock = Arc::new(RwLock::new(period));
+impl Scheduler {
+    pub fn spawn(sender: EventSender) -> Self {
+        let state = Arc::new((Mutex::new(SchedulerState::default()), Condvar::new()));
         let thread = {
-            let lock = lock.clone();
+            let state = state.clone();
             thread::spawn(move || {
-                let mut tick_rate = period;
+                let (lock, changed) = &*state;
+                let mut state = lock.lock().unwrap();
                 loop {
-                    sender.send(event).ok();
-                    if let Ok(new_tick_rate) = lock.try_read() {
-                        tick_rate = *new_tick_rate;
+                    if state.shutdown {
+                        return;
+                    }
+                    let Some(&Reverse((due, id))) = state.queue.peek() else {
+                        state = changed.wait(state).unwrap();
+                        continue;
+                    };
+                    if state.timers.get(&id).map(|timer| timer.due) != Some(due) {
+                        state.queue.pop();
+                        continue;
                     }
-                    thread::sleep(tick_rate);
+                    let now = Instant::now();
+                    if due > now {
+                        state = changed.wait_timeout(state, due - now).unwrap().0;
+                        continue;
+                    }
+                    state.queue.pop();
+                    let timer = state.timers.get_mut(&id).unwrap();
+                    if sender.send(timer.event.clone()).is_err() {
+                        return;
+                    }
+                    timer.last_sent = Some(now);
+                    timer.due = now + timer.period;
+                    let next = timer.due;
+                    state.queue.push(Reverse((next, id)));
                 }
             })
         };
 
-        Self { thread, lock }
+        Self { thread, state }
+    }
+
+    /// Sends `event` right away and then every `period` until cancelled.
+    pub fn add(&self, period: Duration, event: Event) -> TimerId {
+        let (lock, changed) = &*self.state;
+        let mut state = lock.lock().unwrap();
+        let id = TimerId(state.next_id);
+        state.next_id += 1;
+        let due = Instant::now();
+        state.timers.insert(
+            id,
+            Timer {
+                period,
+                event,
+                due,
+                last_sent: None,
+            },
+        );
+        state.queue.push(Reverse((due, id)));
+        changed.notify_all();
+        id
+    }
+
+    /// Changes the time between a timer's events, counted from the last one sent. If that's
+    /// already passed, the next event is sent right away.
+    pub fn reschedule(&self, id: TimerId, period: Duration) {
+        let (lock, changed) = &*self.state;
+        let mut state = lock.lock().unwrap();
+        let Some(timer) = state.timers.get_mut(&id) else {
+            return;
+        };
+        timer.period = period;
+        if let Some(last_sent) = timer.last_sent {
+            timer.due = last_sent + period;
+        }
+        let due = timer.due;
+        state.queue.push(Reverse((due, id)));
+        changed.notify_all();
+    }
+
+    /// Stops sending a timer's events.
+    pub fn cancel(&self, id: TimerId) {
+        let (lock, changed) = &*self.state;
+        lock.lock().unwrap().timers.remove(&id);
+        changed.notify_all();
     }
+}
 
-    pub fn set_period(&self, period: Duration) {
-        *self.lock.write().unwrap() = period;
+impl Drop for Scheduler {
+    fn drop(&mut self) {
+        let (lock, changed) = &*self.state;
+        lock.lock().unwrap().shutdown = true;
+        changed.notify_all();
     }
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Clone)]
 pub enum Event {
     Key(KeyEvent),
     Mouse(MouseEvent),
     Render,
     Tick,
+    /// The terminal's size, sent on startup and whenever it's resized
+    Resize {
+        width: u16,
+        height: u16,
+    },
+    /// A generation computed off the main thread, see `Simulation`
+    Board {
+        generation: u32,
+        board: Arc<Board>,
+    },
+    /// The cells in view of a large pattern that's still loading, see `spawn_load_previewed`
+    BoardPreview {
+        board: Arc<Board>,
+        /// Corners of the rectangle the pattern's header says it fills, which the view was fitted
+        /// to
+        bounds: (Point, Point),
+    },
+    /// A pattern file finished loading in the background
+    BoardLoaded {
+        path: PathBuf,
+        source: Arc<str>,
+        board: Arc<Board>,
+    },
+    LoadFailed {
+        path: PathBuf,
+        error: String,
+    },
+    /// A directory finished being scanned for `:gallery`, see `Gallery::spawn_scan`
+    GalleryScanned {
+        dir: PathBuf,
+        gallery: Result<Gallery, String>,
+    },
+    /// Generations done out of the total of a long jump, see `Jump`
+    Progress {
+        done: u32,
+        total: u32,
+    },
+    Jumped {
+        game: Arc<GameOfLife>,
+        outcome: JumpOutcome,
+        /// The density map handed to the jump, with the generations it stepped counted
+        density: Option<DensityMap>,
+    },
+    /// Reports from the games registered with `Program::game`, in the order they were registered,
+    /// sent after each `Tick` steps them
+    Stepped {
+        reports: Vec<StepReport>,
+    },
+    /// A command for the model to apply as if it had been entered, e.g. from `remote`
+    Action(Action),
+    /// Carried out by the event loop as if the model had run it, for controlling a `Program`
+    /// from outside through `Program::sender`
+    Command(Command),
 }
 
 impl From<KeyEvent> for Event {
@@ -109,3 +279,63 @@ impl From<MouseEvent> for Event {
         Event::Mouse(value)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // Timing is only asserted where the gap is hours against milliseconds, so a loaded machine
+    // can't fail these. Timers send under the state lock, so once `cancel` or `drop` returns
+    // nothing more arrives.
+
+    #[test]
+    fn timer_period_applies_promptly() {
+        let listener = Listener::default();
+        let scheduler = Scheduler::spawn(listener.subscribe());
+        let timer = scheduler.add(Duration::from_secs(3600), Event::Tick);
+        assert!(matches!(listener.next().unwrap(), Event::Tick));
+
+        // Doesn't wait out the old period first
+        scheduler.reschedule(timer, Duration::from_millis(20));
+        for _ in 0..3 {
+            let event = listener.next_timeout(Duration::from_secs(60));
+            assert!(matches!(event, Ok(Event::Tick)));
+        }
+
+        scheduler.cancel(timer);
+        listener.receiver.try_iter().for_each(drop);
+        assert!(listener.next_timeout(Duration::from_millis(50)).is_err());
+    }
+
+    #[test]
+    fn timers_share_a_thread() {
+        let listener = Listener::default();
+        let scheduler = Scheduler::spawn(listener.subscribe());
+        scheduler.add(Duration::from_millis(10), Event::Tick);
+        scheduler.add(Duration::from_secs(3600), Event::Render);
+        scheduler.add(
+            Duration::from_millis(30),
+            Event::Resize {
+                width: 0,
+                height: 0,
+            },
+        );
+
+        // Each sends right away, then the shorter periods keep sending while the long one waits
+        let (mut ticks, mut renders, mut resizes) = (0, 0, 0);
+        while ticks < 10 || resizes < 3 {
+            match listener.next().unwrap() {
+                Event::Tick => ticks += 1,
+                Event::Render => renders += 1,
+                Event::Resize { .. } => resizes += 1,
+                _ => (),
+            }
+        }
+        assert_eq!(renders, 1);
+
+        // Stops every timer
+        drop(scheduler);
+        listener.receiver.try_iter().for_each(drop);
+        assert!(listener.next_timeout(Duration::from_millis(50)).is_err());
+    }
+}
@@ -0,0 +1,185 @@
+use crate::{board::Board, game::GameError, rule::Rule};
+
+pub mod gzip;
+pub mod life;
+pub mod plaintext;
+pub mod png;
+pub mod rle;
+
+/// Largest width or height accepted for ASCII patterns, see `Board::parse_bounded`.
+pub const MAX_ASCII_DIMENSION: usize = 4096;
+
+/// Text formats a pattern can be loaded from.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Format {
+    /// The native `x`/`.` grid accepted by `Board::from_str`
+    Ascii,
+    /// Run Length Encoded, see <https://conwaylife.com/wiki/Run_Length_Encoded>
+    Rle,
+    /// The `O`/`.` grid of `.cells` files, see <https://conwaylife.com/wiki/Plaintext>
+    Plaintext,
+    /// A list of live cell coordinates, see <https://conwaylife.com/wiki/Life_1.06>
+    Life,
+}
+
+impl Format {
+    /// Guesses the format of `s` from its contents. Anything that isn't recognizably another
+    /// format is assumed to be ASCII.
+    pub fn sniff(s: &str) -> Format {
+        if s.trim_start().starts_with(life::HEADER) {
+            return Format::Life;
+        }
+        let header = s
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty() && !line.starts_with('#'));
+        match header {
+            Some(line) if rle::is_header(line) => Format::Rle,
+            Some(line) if line.starts_with('!') || plaintext::is_row(line) => Format::Plaintext,
+            _ => Format::Ascii,
+        }
+    }
+
+    pub fn parse(self, s: &str) -> Result<Board, GameError> {
+        match self {
+            Format::Ascii => Board::parse_bounded(s, MAX_ASCII_DIMENSION),
+            Format::Rle => rle::parse(s),
+            Format::Plaintext => plaintext::parse(s),
+            Format::Life => life::parse(s),
+        }
+    }
+
+    /// The name the pattern `s` gives itself, if its format has a place for one.
+    pub fn name(self, s: &str) -> Option<&str> {
+        match self {
+            Format::Rle => rle::comments(s)
+                .into_iter()
+                .find_map(|line| line.strip_prefix("#N"))
+                .map(str::trim)
+                .filter(|name| !name.is_empty()),
+            Format::Plaintext => plaintext::name(s),
+            Format::Ascii | Format::Life => None,
+        }
+    }
+
+    pub fn write(self, board: &Board) -> String {
+        match self {
+            Format::Ascii => board.to_ascii(),
+            Format::Rle => rle::write(board),
+            Format::Plaintext => plaintext::write(board),
+            Format::Life => life::write(board),
+        }
+    }
+}
+
+/// Parses `s` as whichever format it appears to be in.
+pub fn parse(s: &str) -> Result<Board, GameError> {
+    Format::sniff(s).parse(s)
+}
+
+/// The rule a pattern declares it should be run under, if its format has a place for one.
+pub fn rule(s: &str) -> Result<Option<Rule>, GameError> {
+    match Format::sniff(s) {
+        Format::Rle => rle::header_rule(s),
+        Format::Ascii | Format::Plaintext | Format::Life => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{patterns, random::Rng};
+
+    /// Characters that mean something to one of the parsers, to make malformed inputs likelier
+    /// to get past the first few characters
+    const ALPHABET: &[char] = &[
+        'x', 'o', 'b', '.', '$', '!', '#', '=', ',', ' ', '\n', '-', '0', '1', '9', 'é', '\0',
+    ];
+
+    fn random_text(rng: &mut Rng, len: usize) -> String {
+        let bytes: Vec<u8> = (0..len).map(|_| rng.next_u64() as u8).collect();
+        String::from_utf8_lossy(&bytes).into_owned()
+    }
+
+    /// Applies a few random insertions, deletions and truncations to `s`.
+    fn mutate(rng: &mut Rng, s: &str) -> String {
+        let mut chars: Vec<char> = s.chars().collect();
+        for _ in 0..1 + rng.next_u64() % 4 {
+            let at = rng.next_u64() as usize % (chars.len() + 1);
+            match rng.next_u64() % 4 {
+                0 => chars.insert(at, ALPHABET[rng.next_u64() as usize % ALPHABET.len()]),
+                1 if at < chars.len() => {
+                    chars.remove(at);
+                }
+                2 => chars.truncate(at),
+                _ => {
+                    // Runs and headers with extreme numbers
+                    let n = ["9223372036854775807", "-9223372036854775808", "99999", "0"]
+                        [rng.next_u64() as usize % 4];
+                    chars.splice(at..at, n.chars());
+                }
+            }
+        }
+        chars.into_iter().collect()
+    }
+
+    fn parse_all(s: &str) {
+        for result in [
+            parse(s),
+            rle::parse(s),
+            Board::parse_ascii(s, true),
+            Board::parse_bounded(s, MAX_ASCII_DIMENSION),
+        ] {
+            // Formatting errors has to be panic free too
+            if let Err(e) = result {
+                e.to_string();
+            }
+        }
+    }
+
+    #[test]
+    fn fuzz_parsers() {
+        let mut rng = Rng::new(7);
+        for _ in 0..2000 {
+            let len = rng.next_u64() as usize % 64;
+            parse_all(&random_text(&mut rng, len));
+        }
+        let seeds: Vec<String> = patterns::LIBRARY
+            .iter()
+            .map(|(_, s)| s.to_string())
+            .chain(
+                patterns::LIBRARY
+                    .iter()
+                    .map(|(_, s)| Format::Rle.write(&parse(s).unwrap())),
+            )
+            .collect();
+        for _ in 0..2000 {
+            let seed = &seeds[rng.next_u64() as usize % seeds.len()];
+            parse_all(&mutate(&mut rng, seed));
+        }
+    }
+
+    #[test]
+    fn sniffing() {
+        assert_eq!(Format::sniff("..x\nxxx"), Format::Ascii);
+        assert_eq!(
+            Format::sniff("#N Glider\nx = 3, y = 3\nbo$2bo$3o!"),
+            Format::Rle
+        );
+        assert_eq!(
+            parse("x = 3, y = 3\nbo$2bo$3o!").unwrap(),
+            parse(".x.\n..x\nxxx").unwrap()
+        );
+        assert_eq!(
+            Format::sniff("!Name: Glider\n.O.\n..O\nOOO"),
+            Format::Plaintext
+        );
+        assert_eq!(Format::sniff("..O\nOOO"), Format::Plaintext);
+        assert_eq!(Format::sniff("#Life 1.06\n0 0\n1 0"), Format::Life);
+        assert_eq!(
+            Format::Rle.name("#C A glider\n#N  Glider \nx = 3, y = 3\nbo$2bo$3o!"),
+            Some("Glider")
+        );
+        assert_eq!(Format::Ascii.name("#N Glider\nxxx"), None);
+    }
+}
@@ -0,0 +1,79 @@
+//! Reading gzip-compressed pattern files.
+
+use std::io::Read;
+
+use flate2::read::GzDecoder;
+
+use crate::game::{GameError, GameErrorKind};
+
+const MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Most bytes a stream may inflate to, so a small compressed file can't exhaust memory
+pub const MAX_OUTPUT: usize = 64 << 20;
+
+fn error(detail: &str) -> GameError {
+    GameError::new(GameErrorKind::MalformedGzip {
+        detail: detail.to_owned(),
+    })
+}
+
+pub fn is_gzip(bytes: &[u8]) -> bool {
+    bytes.starts_with(&MAGIC)
+}
+
+/// Decompresses a single-member gzip file, verifying its checksum and length.
+pub fn decompress(bytes: &[u8]) -> Result<Vec<u8>, GameError> {
+    decompress_max(bytes, MAX_OUTPUT)
+}
+
+/// Like `decompress`, failing if the output would be over `max` bytes.
+fn decompress_max(bytes: &[u8], max: usize) -> Result<Vec<u8>, GameError> {
+    let mut out = Vec::new();
+    // Reading a byte past `max` tells a stream that's just too long from one that fits exactly
+    GzDecoder::new(bytes)
+        .take(max as u64 + 1)
+        .read_to_end(&mut out)
+        .map_err(|e| error(&e.to_string()))?;
+    if out.len() > max {
+        return Err(error(&format!("decompresses to more than {} bytes", max)));
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Write;
+
+    use flate2::{write::GzEncoder, Compression};
+
+    use super::*;
+
+    /// `printf 'x = 3, y = 3\nbo$2bo$3o!\n' | gzip -n`
+    const GLIDER_GZ: &[u8] = include_bytes!("../../testdata/glider.rle.gz");
+
+    #[test]
+    fn decompress_rle() {
+        let rle = decompress(GLIDER_GZ).unwrap();
+        assert_eq!(rle, b"x = 3, y = 3\nbo$2bo$3o!\n");
+    }
+
+    #[test]
+    fn output_is_capped() {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::best());
+        encoder.write_all(&[b'o'; 2 << 20]).unwrap();
+        let bomb = encoder.finish().unwrap();
+        assert!(bomb.len() < 16 << 10);
+        assert_eq!(decompress_max(&bomb, 2 << 20).unwrap().len(), 2 << 20);
+        let e = decompress_max(&bomb, 1 << 20).unwrap_err();
+        assert!(e.to_string().contains("more than 1048576 bytes"));
+    }
+
+    #[test]
+    fn corrupt() {
+        let mut bytes = GLIDER_GZ.to_vec();
+        bytes[20] ^= 0xff;
+        assert!(decompress(&bytes).is_err());
+        assert!(decompress(&GLIDER_GZ[..20]).is_err());
+        assert!(decompress(b"x = 3, y = 3").is_err());
+    }
+}
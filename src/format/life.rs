@@ -0,0 +1,77 @@
+use crate::{
+    board::Board,
+    game::{GameError, GameErrorKind},
+    point::Point,
+};
+
+/// First line of every Life 1.06 file
+pub const HEADER: &str = "#Life 1.06";
+
+/// Parses Life 1.06, a list of live cells as `x y` pairs, one per line, after the `#Life 1.06`
+/// header. Its y axis points down, so it's flipped to match the board.
+pub fn parse(s: &str) -> Result<Board, GameError> {
+    let mut board = Board::default();
+    for (num, line) in s.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut coords = line.split_whitespace().map(str::parse::<i64>);
+        let cell = match (coords.next(), coords.next(), coords.next()) {
+            (Some(Ok(x)), Some(Ok(y)), None) => y.checked_neg().map(|y| Point::new(x, y)),
+            _ => None,
+        };
+        match cell {
+            Some(cell) => board.birth_cell(&cell),
+            None => {
+                return Err(GameError::new(GameErrorKind::MalformedLife {
+                    line: num + 1,
+                    text: line.to_owned(),
+                }))
+            }
+        }
+    }
+    Ok(board)
+}
+
+/// Writes `board` as Life 1.06, keeping its absolute position.
+pub fn write(board: &Board) -> String {
+    let mut s = format!("{}\n", HEADER);
+    for p in board.sorted() {
+        // The lowest y flips to one past the highest, which only fits wider
+        s.push_str(&format!("{} {}\n", p.x, -(p.y as i128)));
+    }
+    s
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn glider() {
+        let board = parse("#Life 1.06\n#D A glider\n1 0\n2 1\n0 2\n1 2\n2 2\n").unwrap();
+        assert_eq!(board.normalize(), ".x.\n..x\nxxx".parse().unwrap());
+        assert!(board.contains(&Point::new(1, 0)));
+        assert!(board.contains(&Point::new(0, -2)));
+        assert_eq!(parse(&write(&board)).unwrap(), board);
+
+        let e = parse("#Life 1.06\n0 0\n1 two\n").unwrap_err();
+        assert_eq!(e.to_string(), "Invalid Life 1.06 on line 3: `1 two`");
+
+        let edge = format!("#Life 1.06\n{} {}\n", i64::MIN, i64::MAX);
+        assert!(parse(&edge)
+            .unwrap()
+            .contains(&Point::new(i64::MIN, -i64::MAX)));
+        let past = format!("#Life 1.06\n0 {}\n", i64::MIN);
+        assert!(matches!(
+            parse(&past).unwrap_err().kind(),
+            GameErrorKind::MalformedLife { line: 2, .. }
+        ));
+        let lowest = Board::from([Point::new(0, i64::MIN)]);
+        assert_eq!(
+            write(&lowest),
+            format!("{}\n0 {}\n", HEADER, i64::MAX as i128 + 1)
+        );
+    }
+}
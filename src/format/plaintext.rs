@@ -0,0 +1,66 @@
+use crate::{
+    board::Board,
+    game::{GameError, GameErrorKind},
+};
+
+/// Whether `line` looks like a row of a plaintext grid rather than ASCII, which uses `x`.
+pub fn is_row(line: &str) -> bool {
+    line.contains('O') && line.chars().all(|c| c == 'O' || c == '.')
+}
+
+/// Parses the plaintext (`.cells`) format, where `O` is alive, `.` is dead and lines starting
+/// with `!` are comments. Like ASCII, the first row is the top of the pattern.
+pub fn parse(s: &str) -> Result<Board, GameError> {
+    let rows: Vec<_> = s
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.starts_with('!'))
+        .collect();
+    let mut board = Board::default();
+    for (y, (line_num, line)) in rows.into_iter().rev().enumerate() {
+        for (x, c) in line.trim_end().chars().enumerate() {
+            match c {
+                '.' => {}
+                'O' => board.birth_cell(&(x as i64, y as i64).into()),
+                c => {
+                    return Err(GameError::new(GameErrorKind::InvalidBoardChar {
+                        c,
+                        s: s.to_owned(),
+                        line: u16::try_from(line_num).unwrap_or(u16::MAX),
+                        col: u16::try_from(x).unwrap_or(u16::MAX),
+                    }))
+                }
+            }
+        }
+    }
+    Ok(board)
+}
+
+/// The `!Name:` comment, if there is one.
+pub fn name(s: &str) -> Option<&str> {
+    s.lines()
+        .take_while(|line| line.starts_with('!'))
+        .find_map(|line| line.strip_prefix("!Name:"))
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+}
+
+/// Writes `board` as plaintext from its bounding box.
+pub fn write(board: &Board) -> String {
+    board.to_ascii().replace('x', "O")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn glider() {
+        let s = "!Name: Glider\n!\n.O.\n..O\nOOO\n";
+        let board = parse(s).unwrap();
+        assert_eq!(board, ".x.\n..x\nxxx".parse().unwrap());
+        assert_eq!(name(s), Some("Glider"));
+        assert_eq!(parse(&write(&board)).unwrap(), board);
+        assert!(parse("!\n.x.").is_err());
+    }
+}
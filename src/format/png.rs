@@ -0,0 +1,149 @@
+//! Minimal PNG encoder for grayscale images, storing the pixels uncompressed.
+
+use flate2::Crc;
+
+use crate::{
+    game::{GameError, GameErrorKind},
+    point::Point,
+};
+
+/// Largest width or height, in pixels, of an image drawn from a board
+pub const MAX_DIMENSION: u64 = 8192;
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+
+/// Longest run of data a stored DEFLATE block can hold
+const MAX_STORED: usize = u16::MAX as usize;
+
+fn adler32(bytes: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in bytes {
+        a = (a + byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    b << 16 | a
+}
+
+fn chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend((data.len() as u32).to_be_bytes());
+    let start = out.len();
+    out.extend(kind);
+    out.extend(data);
+    let mut crc = Crc::new();
+    crc.update(&out[start..]);
+    out.extend(crc.sum().to_be_bytes());
+}
+
+/// Wraps `data` in a zlib stream of stored DEFLATE blocks.
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let mut out = vec![0x78, 0x01];
+    let mut blocks = data.chunks(MAX_STORED).peekable();
+    if blocks.peek().is_none() {
+        out.extend([1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        out.push(blocks.peek().is_none() as u8);
+        let len = block.len() as u16;
+        out.extend(len.to_le_bytes());
+        out.extend((!len).to_le_bytes());
+        out.extend(block);
+    }
+    out.extend(adler32(data).to_be_bytes());
+    out
+}
+
+/// The size in pixels of an image of the cells from `min` to `max` inclusive at `scale` pixels
+/// per cell, which is 0×0 if the rectangle is empty. Fails with `BoardTooLarge` if it's wider or
+/// taller than `MAX_DIMENSION`.
+pub fn dimensions(min: Point, max: Point, scale: u32) -> Result<(u32, u32), GameError> {
+    let pixels = |from: i64, to: i64| {
+        let cells = (to as i128 - from as i128 + 1).max(0) as u128;
+        cells.saturating_mul(scale as u128).min(u64::MAX as u128) as u64
+    };
+    let (width, height) = (pixels(min.x, max.x), pixels(min.y, max.y));
+    if width > MAX_DIMENSION || height > MAX_DIMENSION {
+        return Err(GameError::new(GameErrorKind::BoardTooLarge {
+            width: width as usize,
+            height: height as usize,
+            max: MAX_DIMENSION as usize,
+        }));
+    }
+    Ok((width as u32, height as u32))
+}
+
+/// Encodes an 8-bit grayscale image of `width`×`height` pixels given row by row from the top.
+pub fn grayscale(width: u32, height: u32, pixels: &[u8]) -> Vec<u8> {
+    assert_eq!(pixels.len(), width as usize * height as usize);
+    let mut header = Vec::with_capacity(13);
+    header.extend(width.to_be_bytes());
+    header.extend(height.to_be_bytes());
+    // 8 bits per pixel, grayscale, default compression and filtering, not interlaced
+    header.extend([8, 0, 0, 0, 0]);
+
+    // Each row starts with its filter type, which is always none
+    let mut raw = Vec::with_capacity(pixels.len() + height as usize);
+    for row in pixels.chunks(width.max(1) as usize) {
+        raw.push(0);
+        raw.extend(row);
+    }
+
+    let mut out = SIGNATURE.to_vec();
+    chunk(&mut out, b"IHDR", &header);
+    chunk(&mut out, b"IDAT", &zlib_stored(&raw));
+    chunk(&mut out, b"IEND", &[]);
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Read;
+
+    use flate2::read::ZlibDecoder;
+
+    use super::*;
+
+    fn inflate(zlib: &[u8]) -> Vec<u8> {
+        let mut raw = Vec::new();
+        ZlibDecoder::new(zlib).read_to_end(&mut raw).unwrap();
+        raw
+    }
+
+    #[test]
+    fn encode() {
+        let png = grayscale(3, 2, &[0, 128, 255, 255, 128, 0]);
+        assert!(png.starts_with(&SIGNATURE));
+        assert_eq!(&png[12..16], b"IHDR");
+        assert_eq!(&png[16..24], &[0, 0, 0, 3, 0, 0, 0, 2]);
+        assert!(png.ends_with(&[0, 0, 0, 0, b'I', b'E', b'N', b'D', 0xae, 0x42, 0x60, 0x82]));
+
+        let idat_len = u32::from_be_bytes(png[33..37].try_into().unwrap()) as usize;
+        assert_eq!(&png[37..41], b"IDAT");
+        let zlib = &png[41..41 + idat_len];
+        let raw = inflate(zlib);
+        assert_eq!(raw, [0, 0, 128, 255, 0, 255, 128, 0]);
+        assert_eq!(
+            zlib[zlib.len() - 4..],
+            adler32(&raw).to_be_bytes(),
+            "checksum"
+        );
+    }
+
+    #[test]
+    fn checked_dimensions() {
+        let (min, max) = (Point::new(-1, 0), Point::new(1, 9));
+        assert_eq!(dimensions(min, max, 1).unwrap(), (3, 10));
+        assert_eq!(dimensions(min, max, 4).unwrap(), (12, 40));
+        assert_eq!(dimensions(max, min, 4).unwrap(), (0, 0));
+        let far = Point::new(i64::MAX, 0);
+        assert!(dimensions(Point::new(i64::MIN, 0), far, 1).is_err());
+        assert!(dimensions(Point::new(0, 0), Point::new(8191, 0), 1).is_ok());
+        assert!(dimensions(Point::new(0, 0), Point::new(8191, 0), 2).is_err());
+    }
+
+    #[test]
+    fn large_images_span_blocks() {
+        let pixels = vec![7; 300 * 300];
+        let zlib = zlib_stored(&pixels);
+        assert_eq!(inflate(&zlib), pixels);
+    }
+}
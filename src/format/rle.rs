@@ -0,0 +1,513 @@
+use crate::{
+    board::Board,
+    game::{GameError, GameErrorKind},
+    point::Point,
+    rule::Rule,
+};
+
+/// Max characters per line of written RLE, as recommended by the format.
+const LINE_WIDTH: usize = 70;
+
+/// Largest width or height, in cells, of a pattern that will be parsed. Runs reaching further
+/// are rejected rather than allocating a huge board.
+pub const MAX_RLE_DIMENSION: i64 = 1 << 16;
+
+/// Most live cells a parsed pattern may have. Within `MAX_RLE_DIMENSION` a few lines of runs
+/// could otherwise fill billions of cells.
+pub const MAX_RLE_POPULATION: usize = 1 << 24;
+
+fn too_large(width: i64, height: i64) -> GameError {
+    GameError::new(GameErrorKind::BoardTooLarge {
+        width: width as usize,
+        height: height as usize,
+        max: MAX_RLE_DIMENSION as usize,
+    })
+}
+
+/// An error at column `col` of line `line` of the input, both counted from 0, which is `text`.
+fn error(line: usize, col: usize, text: &str, detail: impl Into<String>) -> GameError {
+    GameError::new(GameErrorKind::MalformedRle {
+        line: line + 1,
+        col: col + 1,
+        text: text.to_owned(),
+        detail: detail.into(),
+    })
+}
+
+/// Blames the end of `s` for something missing from it.
+fn missing(s: &str, detail: &str) -> GameError {
+    match s.lines().enumerate().last() {
+        Some((line, text)) => error(line, text.chars().count(), text, detail),
+        None => error(0, 0, "", detail),
+    }
+}
+
+pub fn is_header(line: &str) -> bool {
+    line.strip_prefix('x')
+        .map(|rest| rest.trim_start().starts_with('='))
+        .unwrap_or(false)
+}
+
+/// Parses the `x = m, y = n` header, which is line `num` of the input, into `(width, height)`.
+/// Any other fields are ignored.
+fn parse_header(num: usize, line: &str) -> Result<(i64, i64), GameError> {
+    let mut width = None;
+    let mut height = None;
+    let mut col = 0;
+    for field in line.split(',') {
+        let start = col + field.chars().count() - field.trim_start().chars().count();
+        col += field.chars().count() + 1;
+        let Some((key, value)) = field.split_once('=') else {
+            return Err(error(
+                num,
+                start,
+                line,
+                format!("malformed header field `{}`", field.trim()),
+            ));
+        };
+        let value = value.trim();
+        match key.trim() {
+            "x" => width = value.parse().ok(),
+            "y" => height = value.parse().ok(),
+            _ => {}
+        }
+    }
+    match (width, height) {
+        (Some(width), Some(height)) => Ok((width, height)),
+        _ => Err(error(
+            num,
+            0,
+            line,
+            format!("invalid header `{}`", line.trim()),
+        )),
+    }
+}
+
+/// The rule given by the header's `rule` field, or failing that an older `#r` comment line, if
+/// there is one. Rules from other families of automata, like `WireWorld` or the non-totalistic
+/// `B2a/S12`, are an `UnsupportedRule` error rather than an `InvalidRule` one, so callers can
+/// carry on under another rule.
+pub fn header_rule(s: &str) -> Result<Option<Rule>, GameError> {
+    let mut lines = s.lines().map(str::trim);
+    let mut comment = None;
+    let header = loop {
+        match lines.next() {
+            Some(line) if line.starts_with("#r") => comment = Some(line[2..].trim()),
+            Some(line) if line.is_empty() || line.starts_with('#') => {}
+            Some(line) => break line,
+            None => return Err(missing(s, "missing header")),
+        }
+    };
+    let field = header
+        .split(',')
+        .filter_map(|field| field.split_once('='))
+        .find(|(key, _)| key.trim() == "rule")
+        .map(|(_, rule)| rule.trim());
+    let Some(rule) = field.or(comment) else {
+        return Ok(None);
+    };
+    rule.parse().map(Some).map_err(|e| {
+        // Anything beyond B/S digits and a state count is notation this can't represent
+        let exotic = rule.contains(':')
+            || rule
+                .chars()
+                .any(|c| c.is_alphabetic() && !"BSCG".contains(c.to_ascii_uppercase()));
+        match exotic {
+            true => GameError::new(GameErrorKind::UnsupportedRule { s: rule.to_owned() }),
+            false => e,
+        }
+    })
+}
+
+/// Corners `(min, max)` of the rectangle the header says the pattern fills, placed as `parse`
+/// places it, or `None` if it's empty. Only the header is read.
+pub fn declared_bounds(s: &str) -> Result<Option<(Point, Point)>, GameError> {
+    let (num, header) = s
+        .lines()
+        .enumerate()
+        .find(|(_, line)| {
+            let line = line.trim();
+            !line.is_empty() && !line.starts_with('#')
+        })
+        .ok_or_else(|| missing(s, "missing header"))?;
+    let (width, height) = parse_header(num, header)?;
+    if width > MAX_RLE_DIMENSION || height > MAX_RLE_DIMENSION {
+        return Err(too_large(width, height));
+    }
+    if width <= 0 || height <= 0 {
+        return Ok(None);
+    }
+    Ok(Some((Point::default(), Point::new(width - 1, height - 1))))
+}
+
+/// Parses an RLE pattern. Like the ASCII format, the pattern is placed in the +x +y quadrant with
+/// the first row at the maximum y.
+pub fn parse(s: &str) -> Result<Board, GameError> {
+    parse_within(
+        s,
+        Point::new(i64::MIN, i64::MIN),
+        Point::new(i64::MAX, i64::MAX),
+    )
+}
+
+/// Like `parse`, but only keeps the cells within the rectangle from `min` to `max` inclusive.
+/// Runs are clipped rather than expanded, so a view into a pattern with millions of cells costs
+/// little more than reading its text.
+pub fn parse_within(s: &str, min: Point, max: Point) -> Result<Board, GameError> {
+    parse_capped(s, min, max, MAX_RLE_POPULATION)
+}
+
+/// Like `parse_within`, failing once more than `cap` cells would be kept.
+fn parse_capped(s: &str, min: Point, max: Point, cap: usize) -> Result<Board, GameError> {
+    let mut lines = s.lines().enumerate().filter(|(_, line)| {
+        let line = line.trim();
+        !line.is_empty() && !line.starts_with('#')
+    });
+    let (header_num, header) = lines.next().ok_or_else(|| missing(s, "missing header"))?;
+    let (width, height) = parse_header(header_num, header)?;
+    if width < 0 || height < 0 {
+        return Err(error(
+            header_num,
+            0,
+            header,
+            format!("invalid header `{}`", header.trim()),
+        ));
+    }
+    if width > MAX_RLE_DIMENSION || height > MAX_RLE_DIMENSION {
+        return Err(too_large(width, height));
+    }
+
+    // Under a Generations rule, states past `A` are decaying cells, which a board can't hold
+    let decaying = matches!(header_rule(s), Ok(Some(rule)) if rule.states() > 2);
+    let mut board = Board::default();
+    // Rows are counted down from the top, which is at y = height - 1
+    let (mut x, mut row) = (0i64, 0i64);
+    // Moves `n` cells along, keeping within `MAX_RLE_DIMENSION`
+    let forward = |from: i64, n: i64| match from.checked_add(n) {
+        Some(to) if to <= MAX_RLE_DIMENSION => Ok(to),
+        _ => Err(too_large(MAX_RLE_DIMENSION + 1, 0)),
+    };
+    let mut count: Option<i64> = None;
+    for (num, line) in lines {
+        for (col, c) in line.chars().enumerate() {
+            match c {
+                '0'..='9' => {
+                    let digit = c.to_digit(10).unwrap() as i64;
+                    count = Some(
+                        count
+                            .unwrap_or(0)
+                            .checked_mul(10)
+                            .and_then(|n| n.checked_add(digit))
+                            .ok_or_else(|| error(num, col, line, "run count overflow"))?,
+                    );
+                }
+                'b' | '.' => x = forward(x, count.take().unwrap_or(1))?,
+                'B'..='Z' if decaying => x = forward(x, count.take().unwrap_or(1))?,
+                'o' | 'A'..='Z' => {
+                    let end = forward(x, count.take().unwrap_or(1))?;
+                    let y = height - 1 - row;
+                    if (min.y..=max.y).contains(&y) {
+                        let cells = x.max(min.x)..end.min(max.x.saturating_add(1));
+                        let added = (cells.end - cells.start).max(0) as usize;
+                        if board.population() + added > cap {
+                            return Err(GameError::new(GameErrorKind::TooManyCells { max: cap }));
+                        }
+                        for x in cells {
+                            board.birth_cell(&Point::new(x, y));
+                        }
+                    }
+                    x = end;
+                }
+                '$' => {
+                    row = forward(row, count.take().unwrap_or(1))
+                        .map_err(|_| too_large(0, MAX_RLE_DIMENSION + 1))?;
+                    x = 0;
+                }
+                '!' => return Ok(board),
+                c if c.is_whitespace() => {}
+                '-' => return Err(error(num, col, line, "run counts can't be negative")),
+                c => {
+                    return Err(error(
+                        num,
+                        col,
+                        line,
+                        format!("unexpected character `{}`", c),
+                    ))
+                }
+            }
+        }
+    }
+    Err(missing(s, "missing terminating `!`"))
+}
+
+/// The `#N` name, `#O` author and `#C` comment lines before the header, in order.
+pub fn comments(s: &str) -> Vec<&str> {
+    s.lines()
+        .map(str::trim)
+        .take_while(|line| !is_header(line))
+        .filter(|line| {
+            ["#N", "#O", "#C", "#c"]
+                .iter()
+                .any(|tag| line.starts_with(tag))
+        })
+        .collect()
+}
+
+/// Like `write`, with `comments` written before the header. Each should be a whole line
+/// including its tag, e.g. `#N Glider`.
+pub fn write_with_comments(board: &Board, comments: &[impl AsRef<str>]) -> String {
+    let mut s = String::new();
+    for comment in comments {
+        s.push_str(comment.as_ref());
+        s.push('\n');
+    }
+    s.push_str(&write(board));
+    s
+}
+
+/// Writes `board` as RLE. The pattern is written from its bounding box, so absolute position is
+/// not preserved.
+pub fn write(board: &Board) -> String {
+    let Some((min, max)) = board.bounding_box() else {
+        return "x = 0, y = 0\n!\n".to_owned();
+    };
+
+    let mut tokens = Vec::new();
+    let push_run = |tokens: &mut Vec<String>, n: i64, tag: char| match n {
+        0 => {}
+        1 => tokens.push(tag.to_string()),
+        n => tokens.push(format!("{}{}", n, tag)),
+    };
+    let mut pending_rows = 0;
+    for y in (min.y..=max.y).rev() {
+        let mut run = (0, 'b');
+        let mut row = Vec::new();
+        for x in min.x..=max.x {
+            let tag = if board.contains(&Point::new(x, y)) {
+                'o'
+            } else {
+                'b'
+            };
+            if tag == run.1 {
+                run.0 += 1;
+            } else {
+                push_run(&mut row, run.0, run.1);
+                run = (1, tag);
+            }
+        }
+        // Trailing dead cells are implied
+        if run.1 == 'o' {
+            push_run(&mut row, run.0, run.1);
+        }
+        if row.is_empty() {
+            pending_rows += 1;
+            continue;
+        }
+        if !tokens.is_empty() {
+            push_run(&mut tokens, pending_rows + 1, '$');
+        }
+        pending_rows = 0;
+        tokens.extend(row);
+    }
+    tokens.push("!".to_owned());
+
+    let mut s = format!("x = {}, y = {}\n", max.x - min.x + 1, max.y - min.y + 1);
+    let mut line_len = 0;
+    for token in tokens {
+        if line_len + token.len() > LINE_WIDTH {
+            s.push('\n');
+            line_len = 0;
+        }
+        line_len += token.len();
+        s.push_str(&token);
+    }
+    s.push('\n');
+    s
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let glider = parse("#N Glider\nx = 3, y = 3\nbo$2bo$3o!").unwrap();
+        assert_eq!(glider, ".x.\n..x\nxxx".parse().unwrap());
+        assert_eq!(write(&glider), "x = 3, y = 3\nbo$2bo$3o!\n");
+
+        let gap: Board = "x\n.\n.\nx".parse().unwrap();
+        assert_eq!(write(&gap), "x = 1, y = 4\no3$o!\n");
+        assert_eq!(parse(&write(&gap)).unwrap(), gap);
+    }
+
+    #[test]
+    fn comment_lines() {
+        let s = "#N Glider\n#O Richard K. Guy\n#r 23/3\n#C Moves c/4\nx = 3, y = 3\nbo$2bo$3o!";
+        assert_eq!(
+            comments(s),
+            ["#N Glider", "#O Richard K. Guy", "#C Moves c/4"]
+        );
+        let glider = parse(s).unwrap();
+        let written = write_with_comments(&glider, &comments(s));
+        assert_eq!(
+            written,
+            "#N Glider\n#O Richard K. Guy\n#C Moves c/4\nx = 3, y = 3\nbo$2bo$3o!\n"
+        );
+        assert_eq!(parse(&written).unwrap(), glider);
+        assert!(comments("x = 1, y = 1\no!").is_empty());
+    }
+
+    #[test]
+    fn rule_field() {
+        let rule = |s| header_rule(s).unwrap().map(|rule| rule.to_string());
+        assert_eq!(rule("x = 3, y = 3\nbo$2bo$3o!"), None);
+        assert_eq!(
+            rule("#N Glider\nx = 3, y = 3, rule = B36/S23\nbo$2bo$3o!"),
+            Some("B36/S23".to_owned())
+        );
+        assert_eq!(
+            rule("x = 1, y = 1, rule = 23/3\no!"),
+            Some("B3/S23".to_owned())
+        );
+        assert!(header_rule("x = 1, y = 1, rule = B9\no!").is_err());
+        assert_eq!(
+            rule("#r 23/36\nx = 1, y = 1\no!"),
+            Some("B36/S23".to_owned())
+        );
+        for exotic in ["WireWorld", "B2a/S12", "B3/S23:T30,20"] {
+            let s = format!("x = 1, y = 1, rule = {}\no!", exotic);
+            assert!(matches!(
+                header_rule(&s).unwrap_err().kind(),
+                GameErrorKind::UnsupportedRule { .. }
+            ));
+        }
+    }
+
+    #[test]
+    fn generations_rule() {
+        let brain = "#N Brian's Brain oscillator\nx = 4, y = 2, rule = B2/S/G3\nAB$2.BA!\n";
+        let rule = header_rule(brain).unwrap().unwrap();
+        assert_eq!(rule, Rule::preset("Brian's Brain").unwrap());
+        assert_eq!(rule.states(), 3);
+        assert_eq!(
+            parse(brain).unwrap(),
+            Board::from([Point::new(0, 1), Point::new(3, 0)])
+        );
+        assert_eq!(
+            header_rule("x = 1, y = 1, rule = /2/C3\no!").unwrap(),
+            Some(rule)
+        );
+        assert_eq!(
+            header_rule("x = 1, y = 1, rule = B2/S345/4\no!")
+                .unwrap()
+                .map(|rule| rule.states()),
+            Some(4)
+        );
+    }
+
+    #[test]
+    fn error_positions() {
+        let position = |s: &str| match parse(s).unwrap_err().kind() {
+            GameErrorKind::MalformedRle {
+                line, col, detail, ..
+            } => (*line, *col, detail.clone()),
+            kind => panic!("{:?}", kind),
+        };
+        assert_eq!(
+            position("#N Glider\nx = 3, y = 3\nbo$\n  2bz$3o!"),
+            (4, 5, "unexpected character `z`".to_owned())
+        );
+        assert_eq!(
+            position("x = 3, y = 3\nbo$-2bo$3o!"),
+            (2, 4, "run counts can't be negative".to_owned())
+        );
+        assert_eq!(
+            position("x = 3, y = 3\nbo$2bo$\n3o"),
+            (3, 3, "missing terminating `!`".to_owned())
+        );
+        assert_eq!(
+            position("x = 3, y = 3, size\no!"),
+            (1, 15, "malformed header field `size`".to_owned())
+        );
+        assert_eq!(
+            position("x = 1, y = 1\n99999999999999999999o!").2,
+            "run count overflow"
+        );
+        assert_eq!(
+            parse("x = 3, y = 3\nbo$2bz$3o!").unwrap_err().to_string(),
+            "Invalid RLE on line 2, column 6: unexpected character `z`\nbo$2bz$3o!\n     ^\n"
+        );
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(parse("bo$2bo$3o!").is_err());
+        assert!(parse("x = 3, y = 3\nbo$2bo$3o").is_err());
+        assert!(parse("x = 3, y = 3\nbo$2bz$3o!").is_err());
+    }
+
+    #[test]
+    fn parse_within_culls() {
+        // A 2000×2000 block with every other row empty
+        let mut s = String::from("x = 2000, y = 2000\n");
+        for _ in 0..1000 {
+            s.push_str("2000o2$\n");
+        }
+        s.push('!');
+        let (min, max) = (Point::new(995, 990), Point::new(1004, 999));
+        let view = parse_within(&s, min, max).unwrap();
+        assert_eq!(view.population(), 50);
+        assert!(view
+            .iter()
+            .all(|p| (min.x..=max.x).contains(&p.x) && (min.y..=max.y).contains(&p.y)));
+        // Rows are counted down from y = 1999, so the filled ones are at odd y
+        assert!(view.iter().all(|p| p.y % 2 == 1));
+
+        assert!(parse_within(&s, Point::new(3000, 0), Point::new(4000, 10))
+            .unwrap()
+            .is_empty());
+        assert!(parse_within("x = 3, y = 3\nbo$2bz$3o!", min, max).is_err());
+
+        assert_eq!(
+            declared_bounds(&s).unwrap(),
+            Some((Point::new(0, 0), Point::new(1999, 1999)))
+        );
+        assert_eq!(declared_bounds("#N Empty\nx = 0, y = 0\n!").unwrap(), None);
+        assert!(declared_bounds("bo$2bo$3o!").is_err());
+    }
+
+    /// Inputs found by `format::test::fuzz_parsers` that used to overflow or hang
+    #[test]
+    fn huge_runs() {
+        assert!(parse("x = 3, y = 3\n9223372036854775807o!").is_err());
+        assert!(parse("x = 3, y = 3\n9223372036854775807b9223372036854775807b!").is_err());
+        assert!(parse("x = 3, y = 3\n9223372036854775807$2$o!").is_err());
+        assert!(parse("x = 3, y = -9223372036854775808\no!").is_err());
+        assert!(parse("x = 99999, y = 1\no!").is_err());
+        assert_eq!(
+            parse("x = 3, y = 1\n65535bo!").unwrap(),
+            Board::from([(65535i64, 0i64).into()])
+        );
+    }
+
+    #[test]
+    fn population_cap() {
+        let (min, max) = (
+            Point::new(i64::MIN, i64::MIN),
+            Point::new(i64::MAX, i64::MAX),
+        );
+        let s = format!("x = 65536, y = 65536\n{}!", "65536o$".repeat(65536));
+        let e = parse_capped(&s, min, max, 100_000).unwrap_err();
+        assert_eq!(e.to_string(), "Pattern has more than 100000 live cells");
+        assert_eq!(
+            parse_capped("x = 4, y = 2\n4o$4o!", min, max, 8)
+                .unwrap()
+                .population(),
+            8
+        );
+        assert!(parse_capped("x = 4, y = 2\n4o$4o!", min, max, 7).is_err());
+        // Culled cells don't count
+        let view = parse_capped(&s, Point::new(0, 0), Point::new(9, 9), 100_000).unwrap();
+        assert_eq!(view.population(), 100);
+    }
+}
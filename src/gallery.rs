@@ -0,0 +1,163 @@
+use std::{
+    io,
+    path::{Path, PathBuf},
+    thread::{self, JoinHandle},
+};
+
+use crate::{
+    event::{Event, EventSender},
+    format, loader,
+};
+
+/// Extensions of the files a gallery lists. Gzipped copies, e.g. `glider.rle.gz`, count too.
+pub const EXTENSIONS: &[&str] = &["rle", "cells", "life"];
+
+/// A pattern file found by `Gallery::scan`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GalleryEntry {
+    pub path: PathBuf,
+    /// The name the pattern gives itself, or else its file name
+    pub name: String,
+}
+
+/// The patterns in a directory, for browsing a collection and loading them one at a time.
+#[derive(Debug, Default, Clone)]
+pub struct Gallery {
+    pub dir: PathBuf,
+    /// Sorted by path
+    pub entries: Vec<GalleryEntry>,
+    /// Files with a pattern extension that couldn't be read or parsed, and why
+    pub skipped: Vec<(PathBuf, String)>,
+    /// Index into `entries` of the highlighted pattern
+    pub selected: usize,
+}
+
+/// Whether `path` ends in one of `EXTENSIONS`, possibly followed by `.gz`.
+fn is_pattern(path: &Path) -> bool {
+    let path = match path.extension().and_then(|e| e.to_str()) {
+        Some("gz") => Path::new(path.file_stem().unwrap_or_default()),
+        _ => path,
+    };
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| EXTENSIONS.contains(&e))
+}
+
+impl Gallery {
+    /// Lists the pattern files directly inside `dir`. Each is parsed up front so invalid ones
+    /// can be skipped, which only fails if `dir` itself can't be read.
+    pub fn scan(dir: &Path) -> io::Result<Gallery> {
+        let mut paths: Vec<_> = std::fs::read_dir(dir)?
+            .filter_map(|entry| Some(entry.ok()?.path()))
+            .filter(|path| path.is_file() && is_pattern(path))
+            .collect();
+        paths.sort();
+        let mut gallery = Gallery {
+            dir: dir.to_owned(),
+            ..Default::default()
+        };
+        for path in paths {
+            match loader::load(&path, format::parse) {
+                Ok((source, _)) => {
+                    let name = match format::Format::sniff(&source).name(&source) {
+                        Some(name) => name.to_owned(),
+                        None => path
+                            .file_name()
+                            .unwrap_or_default()
+                            .to_string_lossy()
+                            .into_owned(),
+                    };
+                    gallery.entries.push(GalleryEntry { path, name });
+                }
+                Err(e) => gallery.skipped.push((path, e)),
+            }
+        }
+        Ok(gallery)
+    }
+
+    /// Scans `dir` on another thread, since every file in it is read and parsed, and sends the
+    /// result as an `Event::GalleryScanned`.
+    pub fn spawn_scan(dir: PathBuf, sender: EventSender) -> JoinHandle<()> {
+        thread::spawn(move || {
+            let gallery = Gallery::scan(&dir).map_err(|e| e.to_string());
+            sender.send(Event::GalleryScanned { dir, gallery }).ok();
+        })
+    }
+
+    pub fn selected(&self) -> Option<&GalleryEntry> {
+        self.entries.get(self.selected)
+    }
+
+    pub fn select_previous(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn select_next(&mut self) {
+        self.selected = (self.selected + 1).min(self.entries.len().saturating_sub(1));
+    }
+
+    /// What was found, for the status line, e.g. `12 patterns in ./patterns, skipped 2
+    /// unreadable or invalid files`. A lone skipped file is named along with why.
+    pub fn summary(&self) -> String {
+        let found = format!("{} patterns in {}", self.entries.len(), self.dir.display());
+        match self.skipped.first() {
+            None => found,
+            Some((path, reason)) if self.skipped.len() == 1 => format!(
+                "{}, skipped {}: {}",
+                found,
+                path.display(),
+                reason.lines().next().unwrap_or_default()
+            ),
+            Some(_) => format!(
+                "{}, skipped {} unreadable or invalid files",
+                found,
+                self.skipped.len()
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn lists_valid_patterns() {
+        let dir = std::env::temp_dir().join(format!("gallery-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("nested.rle")).unwrap();
+        for (file, contents) in [
+            ("glider.rle", "#N Glider\nx = 3, y = 3\nbo$2bo$3o!\n"),
+            ("blinker.cells", "!Name: Blinker\nOOO\n"),
+            ("block.life", "#Life 1.06\n0 0\n1 0\n0 1\n1 1\n"),
+            ("unnamed.rle", "x = 1, y = 1\no!\n"),
+            ("broken.rle", "x = 3, y = 3\nbo$2bo$3o\n"),
+            ("broken.cells", "!Name: Oops\n.x.\n"),
+            ("notes.txt", "xxx"),
+        ] {
+            std::fs::write(dir.join(file), contents).unwrap();
+        }
+
+        let mut gallery = Gallery::scan(&dir).unwrap();
+        let names: Vec<_> = gallery.entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["Blinker", "block.life", "Glider", "unnamed.rle"]);
+        assert_eq!(gallery.entries[2].path, dir.join("glider.rle"));
+        let skipped: Vec<_> = gallery.skipped.iter().map(|(path, _)| path).collect();
+        assert_eq!(
+            skipped,
+            [&dir.join("broken.cells"), &dir.join("broken.rle")]
+        );
+        assert!(gallery
+            .summary()
+            .ends_with("skipped 2 unreadable or invalid files"));
+
+        gallery.select_previous();
+        assert_eq!(gallery.selected, 0);
+        for _ in 0..10 {
+            gallery.select_next();
+        }
+        assert_eq!(gallery.selected().unwrap().name, "unnamed.rle");
+
+        assert!(Gallery::scan(&dir.join("missing")).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,8 +1,16 @@
-use std::{collections::HashSet, fmt::Display, str::FromStr};
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fmt::Display,
+    str::FromStr,
+};
 
 use crate::{
+    analysis::{self, Drift, Escape, MAX_CLASSIFY_PERIOD},
+    board::POSITION_PREFIX,
     board::{Board, Cell},
     point::Point,
+    random::Rng,
+    rule::{Rule, StochasticRule},
 };
 
 #[derive(Debug)]
@@ -14,38 +22,267 @@ impl GameError {
     pub fn new(kind: GameErrorKind) -> Self {
         GameError { kind }
     }
+
+    pub fn kind(&self) -> &GameErrorKind {
+        &self.kind
+    }
 }
 
 #[derive(Debug)]
 pub enum GameErrorKind {
-    InvalidBoardChar { c: char, line: u16, s: String },
+    InvalidBoardChar {
+        c: char,
+        line: u16,
+        col: u16,
+        s: String,
+    },
+    BoardTooLarge {
+        width: usize,
+        height: usize,
+        max: usize,
+    },
+    /// Counting lines and columns from 1, with `text` the line they're on
+    MalformedRle {
+        line: usize,
+        col: usize,
+        text: String,
+        detail: String,
+    },
+    MalformedGzip {
+        detail: String,
+    },
+    /// A pattern with more live cells than a parser allows
+    TooManyCells {
+        max: usize,
+    },
+    /// Counting lines from 1, with `text` the offending line
+    MalformedLife {
+        line: usize,
+        text: String,
+    },
+    InvalidRule {
+        s: String,
+    },
+    /// A rule outside the outer-totalistic and Generations families, e.g. `WireWorld`
+    UnsupportedRule {
+        s: String,
+    },
+    InvalidPosition {
+        header: String,
+    },
+    PopulationCap {
+        generation: u32,
+        population: usize,
+    },
+    NoHistory {
+        generation: u32,
+    },
 }
 
 impl std::error::Error for GameError {}
 
+/// Writes each line of `s`, with a `^` under column `col` of line `line`.
+fn write_with_caret(
+    f: &mut std::fmt::Formatter<'_>,
+    s: &str,
+    line: usize,
+    col: usize,
+) -> std::fmt::Result {
+    for (i, text) in s.lines().enumerate() {
+        f.write_fmt(format_args!("{}\n", text))?;
+        if i == line {
+            f.write_fmt(format_args!("{:>1$}\n", "^", col + 1))?;
+        }
+    }
+    Ok(())
+}
+
 impl Display for GameError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match &self.kind {
-            GameErrorKind::InvalidBoardChar { c, line, s } => f.write_fmt(format_args!(
-                "Invalid char `{}` found on line {} when parsing\n{}\n into Board",
-                c, line, s
+            GameErrorKind::InvalidBoardChar { c, line, col, s } => {
+                f.write_fmt(format_args!(
+                    "Invalid char `{}` found on line {}, column {} when parsing\n",
+                    c, line, col
+                ))?;
+                write_with_caret(f, s, *line as usize, *col as usize)?;
+                f.write_str(" into Board")
+            }
+            GameErrorKind::BoardTooLarge { width, height, max } => f.write_fmt(format_args!(
+                "Board of {}x{} exceeds the maximum dimension of {}",
+                width, height, max
+            )),
+            GameErrorKind::MalformedRle {
+                line,
+                col,
+                text,
+                detail,
+            } => {
+                f.write_fmt(format_args!(
+                    "Invalid RLE on line {}, column {}: {}\n",
+                    line, col, detail
+                ))?;
+                write_with_caret(f, text, 0, col - 1)
+            }
+            GameErrorKind::TooManyCells { max } => {
+                f.write_fmt(format_args!("Pattern has more than {} live cells", max))
+            }
+            GameErrorKind::MalformedGzip { detail } => {
+                f.write_fmt(format_args!("Invalid gzip data: {}", detail))
+            }
+            GameErrorKind::MalformedLife { line, text } => f.write_fmt(format_args!(
+                "Invalid Life 1.06 on line {}: `{}`",
+                line, text
             )),
+            GameErrorKind::InvalidRule { s } => {
+                f.write_fmt(format_args!("Invalid rule `{}`, expected e.g. B3/S23", s))
+            }
+            GameErrorKind::UnsupportedRule { s } => {
+                f.write_fmt(format_args!("Unsupported rule `{}`", s))
+            }
+            GameErrorKind::InvalidPosition { header } => f.write_fmt(format_args!(
+                "Invalid position `{}`, expected e.g. {} 50 -20",
+                header, POSITION_PREFIX
+            )),
+            GameErrorKind::PopulationCap {
+                generation,
+                population,
+            } => f.write_fmt(format_args!(
+                "Stopped at generation {} with population {}",
+                generation, population
+            )),
+            GameErrorKind::NoHistory { generation } => {
+                f.write_fmt(format_args!("No history back to generation {}", generation))
+            }
         }
     }
 }
 
 // Contains board and any game parameters
-// Game of Life Rules:
+// Game of Life Rules (the default `Rule`, B3/S23):
 // 1. Any live cell with fewer than two live neighbours dies (referred to as underpopulation)
 // 2. Any live cell with more than three live neighbours dies (referred to as overpopulation)
 // 3. Any live cell with two or three live neighbours lives, unchanged, to the next generation
 // 4. Any dead cell with exactly three live neighbours comes to life
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone)]
 pub struct GameOfLife {
     pub board: Board,
     pub killed_cells: HashSet<Point>,
     pub birthed_cells: HashSet<Point>,
     pub generation: u32,
+    pub rule: Rule,
+    /// How each previous generation changed into the next, most recent last, up to
+    /// `HISTORY_LIMIT` generations. Past boards are rebuilt by reverting these from `board`.
+    pub history: VecDeque<Diff>,
+    /// Statistics for each generation since the first step, oldest first, up to the last
+    /// `STATS_LIMIT`
+    pub stats: VecDeque<GenerationStats>,
+    /// When set, `step` follows this instead of `rule`
+    pub stochastic: Option<Stochastic>,
+    /// Cells decaying under a Generations rule, by state from 2 up to `rule.states() - 1`. These
+    /// aren't kept in history, so stepping back forgets them.
+    pub dying: HashMap<Point, u8>,
+}
+
+/// How a cell fared over the generations kept in history, see `GameOfLife::cell_history`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct CellHistory {
+    /// Generations kept, including the current one
+    pub generations: usize,
+    /// How many of those the cell was alive in
+    pub alive: usize,
+    /// Generations it's been alive for in a row up to now, or 0 if it's dead
+    pub age: usize,
+}
+
+/// The cells a step birthed and killed, which is all it takes to undo it.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Diff {
+    pub birthed: HashSet<Point>,
+    pub killed: HashSet<Point>,
+}
+
+impl Diff {
+    /// Undoes the step on `board`, killing what it birthed and birthing what it killed.
+    pub fn revert(&self, board: &mut Board) {
+        for pos in &self.birthed {
+            board.kill_cell(pos);
+        }
+        for pos in &self.killed {
+            board.birth_cell(pos);
+        }
+    }
+}
+
+/// A stochastic rule and the seeded generator its outcomes are drawn from.
+#[derive(Debug, Clone)]
+pub struct Stochastic {
+    pub rule: StochasticRule,
+    pub rng: Rng,
+}
+
+/// Population and changes for a single generation.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct GenerationStats {
+    pub generation: u32,
+    pub population: usize,
+    pub births: usize,
+    pub deaths: usize,
+}
+
+/// What changed in a single step, returned by `GameOfLife::step` and `GameOfLife::advance`.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct StepReport {
+    /// Generation the game is at after the step
+    pub generation: u32,
+    pub births: usize,
+    pub deaths: usize,
+}
+
+pub const HISTORY_LIMIT: usize = 1000;
+
+/// Generations of `GameOfLife::stats` kept, far more than history since each is a few numbers
+pub const STATS_LIMIT: usize = 100_000;
+
+/// How a board ended up after `GameOfLife::run_until_stable`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Stability {
+    /// Every cell died
+    Extinct,
+    /// The board repeats in place, with period 1 meaning a still life
+    Periodic { period: u32 },
+    /// The board repeats its shape but keeps moving, so it never settles in place
+    Drifting(Drift),
+    /// The board repeats in place apart from spaceships it has shot off
+    Escaping(Escape),
+    /// Nothing was detected within the step limit
+    Unstable,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct StableResult {
+    pub stability: Stability,
+    /// Generations stepped before stopping
+    pub steps: u32,
+}
+
+impl StableResult {
+    /// Whether the board settled in place, i.e. died out or became periodic.
+    pub fn stabilized(&self) -> bool {
+        matches!(
+            self.stability,
+            Stability::Extinct | Stability::Periodic { .. } | Stability::Escaping(_)
+        )
+    }
+
+    pub fn period(&self) -> Option<u32> {
+        match self.stability {
+            Stability::Periodic { period } => Some(period),
+            Stability::Drifting(drift) => Some(drift.period),
+            Stability::Escaping(escape) => Some(escape.period),
+            Stability::Extinct | Stability::Unstable => None,
+        }
+    }
 }
 
 impl<const N: usize> From<[Point; N]> for GameOfLife {
@@ -64,47 +301,466 @@ impl GameOfLife {
         Ok(())
     }
 
-    pub fn step(&mut self) {
+    pub fn step_n(&mut self, n: u32) {
+        for _ in 0..n {
+            self.step();
+        }
+    }
+
+    /// Starts over from generation 0 with `board`, forgetting history.
+    pub fn restart(&mut self, board: Board) {
+        self.board = board;
+        self.generation = 0;
+        self.history.clear();
+        self.stats.clear();
         self.killed_cells.clear();
         self.birthed_cells.clear();
+        self.dying.clear();
+    }
+
+    /// Empties the board and starts over from generation 0, forgetting everything tracked about
+    /// the old one.
+    pub fn clear(&mut self) {
+        self.restart(Board::default());
+    }
+
+    /// The next generation with only the cells from `min` to `max` stepped, as if the board were
+    /// empty around them, and everything outside left as it is. For checking bounded or chunked
+    /// stepping against `step`; only the two-state `rule` is followed.
+    pub fn step_region(&self, min: Point, max: Point) -> Board {
+        let within = |p: &Point| (min.x..=max.x).contains(&p.x) && (min.y..=max.y).contains(&p.y);
+        let (birthed, killed) = GameOfLife::transition(&self.board.extract(min, max), &self.rule);
+        let mut next = self.board.clone();
+        for pos in &killed {
+            next.kill_cell(pos);
+        }
+        for pos in birthed.iter().filter(|p| within(p)) {
+            next.birth_cell(pos);
+        }
+        next
+    }
+
+    /// Where `step_region` and a full step disagree within the region, as the cells alive after
+    /// only the full step and those alive after only the region step. Both are empty when nothing
+    /// outside the region reaches in.
+    pub fn region_discrepancies(&self, min: Point, max: Point) -> (HashSet<Point>, HashSet<Point>) {
+        let (birthed, killed) = GameOfLife::transition(&self.board, &self.rule);
+        let mut full = self.board.clone();
+        for pos in &killed {
+            full.kill_cell(pos);
+        }
+        for pos in &birthed {
+            full.birth_cell(pos);
+        }
+        self.step_region(min, max)
+            .extract(min, max)
+            .diff(&full.extract(min, max))
+    }
+
+    /// The board `generations` steps ago, or `None` if history doesn't go back that far.
+    pub fn past(&self, generations: usize) -> Option<Board> {
+        let start = self.history.len().checked_sub(generations)?;
+        let mut board = self.board.clone();
+        for diff in self.history.range(start..).rev() {
+            diff.revert(&mut board);
+        }
+        Some(board)
+    }
+
+    /// Cells alive in the last `length` generations but not now, by how many generations ago
+    /// they were last alive. Shorter where history doesn't go back that far.
+    pub fn trail(&self, length: u8) -> HashMap<Point, u8> {
+        let mut trail = HashMap::new();
+        let mut board = self.board.clone();
+        for (age, diff) in (1..=length).zip(self.history.iter().rev()) {
+            diff.revert(&mut board);
+            for p in board.iter().filter(|p| !self.board.contains(p)) {
+                trail.entry(*p).or_insert(age);
+            }
+        }
+        trail
+    }
+
+    /// How often `pos` was alive in the current generation and those kept in history, and how
+    /// long it's been alive for. Ages are cut short where history runs out.
+    pub fn cell_history(&self, pos: &Point) -> CellHistory {
+        let mut alive = self.board.contains(pos);
+        let mut history = CellHistory {
+            generations: 1,
+            alive: alive as usize,
+            age: alive as usize,
+        };
+        let mut streak = alive;
+        for diff in self.history.iter().rev() {
+            if diff.birthed.contains(pos) {
+                alive = false;
+            } else if diff.killed.contains(pos) {
+                alive = true;
+            }
+            streak &= alive;
+            history.generations += 1;
+            history.alive += alive as usize;
+            history.age += streak as usize;
+        }
+        history
+    }
+
+    /// Folds an edit made directly to `board`, which was `before`, into the last step's diff so
+    /// stepping back still restores the previous generation as it was.
+    pub fn edited(&mut self, before: &Board) {
+        let Some(last) = self.history.back_mut() else {
+            return;
+        };
+        let (added, removed) = before.diff(&self.board);
+        for pos in added {
+            if !last.killed.remove(&pos) {
+                last.birthed.insert(pos);
+            }
+        }
+        for pos in removed {
+            if !last.birthed.remove(&pos) {
+                last.killed.insert(pos);
+            }
+        }
+    }
+
+    /// Restores the previous generation from history. Returns false if there is none.
+    pub fn step_back(&mut self) -> bool {
+        match self.history.pop_back() {
+            Some(diff) => {
+                diff.revert(&mut self.board);
+                self.generation -= 1;
+                if self.stats.len() > 1 {
+                    self.stats.pop_back();
+                }
+                self.killed_cells.clear();
+                self.birthed_cells.clear();
+                self.dying.clear();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Steps forward, or rewinds through history, to `generation`. Stepping forward stops early
+    /// if the population exceeds `population_cap`.
+    pub fn jump_to(&mut self, generation: u32, population_cap: usize) -> Result<(), GameError> {
+        self.jump_to_with(generation, population_cap, |_| ())
+    }
+
+    /// Like `jump_to`, calling `each` with the board after every generation stepped forward,
+    /// e.g. to keep counting a `DensityMap`.
+    pub fn jump_to_with(
+        &mut self,
+        generation: u32,
+        population_cap: usize,
+        mut each: impl FnMut(&Board),
+    ) -> Result<(), GameError> {
+        if generation < self.generation {
+            if ((self.generation - generation) as usize) > self.history.len() {
+                return Err(GameError::new(GameErrorKind::NoHistory { generation }));
+            }
+            while self.generation > generation {
+                self.step_back();
+            }
+            return Ok(());
+        }
+        while self.generation < generation {
+            if self.board.population() > population_cap {
+                return Err(GameError::new(GameErrorKind::PopulationCap {
+                    generation: self.generation,
+                    population: self.board.population(),
+                }));
+            }
+            self.step();
+            each(&self.board);
+        }
+        Ok(())
+    }
+
+    /// Steps until the board dies out, repeats, drifts with a stable shape, or repeats apart from
+    /// escaping spaceships, up to `max_steps` generations. Periods up to
+    /// `analysis::MAX_CLASSIFY_PERIOD` are detected.
+    pub fn run_until_stable(&mut self, max_steps: u32) -> StableResult {
+        let mut steps = 0;
+        loop {
+            let stability = if self.board.is_empty() {
+                Stability::Extinct
+            } else if let Some(period) = analysis::detect_period(self, MAX_CLASSIFY_PERIOD) {
+                Stability::Periodic { period }
+            } else if let Some(drift) = analysis::detect_drift(self, MAX_CLASSIFY_PERIOD) {
+                Stability::Drifting(drift)
+            } else if let Some(escape) = analysis::detect_escape(self, MAX_CLASSIFY_PERIOD) {
+                Stability::Escaping(escape)
+            } else if steps == max_steps {
+                Stability::Unstable
+            } else {
+                self.step();
+                steps += 1;
+                continue;
+            };
+            return StableResult { stability, steps };
+        }
+    }
+
+    /// Steps until `pred` holds, checking before each step, up to `max_steps` generations. Returns
+    /// the number of steps taken if `pred` was met.
+    pub fn run_until(&mut self, pred: impl Fn(&GameOfLife) -> bool, max_steps: u32) -> Option<u32> {
+        for steps in 0..=max_steps {
+            if pred(self) {
+                return Some(steps);
+            }
+            if steps < max_steps {
+                self.step();
+            }
+        }
+        None
+    }
+
+    /// Formats `stats` as CSV with a header row.
+    pub fn population_csv(&self) -> String {
+        let mut csv = String::from("generation,population,births,deaths\n");
+        for stats in &self.stats {
+            csv.push_str(&format!(
+                "{},{},{},{}\n",
+                stats.generation, stats.population, stats.births, stats.deaths
+            ));
+        }
+        csv
+    }
 
-        for pos in self.board.iter() {
+    /// Returns the cells that will be `(birthed, killed)` going from `board` to its next
+    /// generation under `rule`.
+    pub fn transition(board: &Board, rule: &Rule) -> (HashSet<Point>, HashSet<Point>) {
+        let mut birthed_cells = HashSet::new();
+        let mut killed_cells = HashSet::new();
+        for pos in board.iter() {
             let mut num_alive = 0;
-            for cell in self.board.neighbors(pos) {
+            for cell in board.neighbors(pos) {
                 match cell {
                     Cell::Dead(pos) => {
                         // Rule 4
-                        if let 3 = self
-                            .board
-                            .neighbors(&pos)
-                            .filter(|c| matches!(c, Cell::Alive(_)))
-                            .count()
-                        {
-                            self.birthed_cells.insert(pos);
+                        if rule.births(board.live_neighbor_count(&pos)) {
+                            birthed_cells.insert(pos);
                         }
                     }
                     Cell::Alive(_) => num_alive += 1,
                 }
             }
-            match num_alive {
-                // Rule 1 & 2
-                0 | 1 | 4.. => {
-                    self.killed_cells.insert(*pos);
+            // Rule 1, 2 & 3
+            if !rule.survives(num_alive) {
+                killed_cells.insert(*pos);
+            }
+        }
+        (birthed_cells, killed_cells)
+    }
+
+    /// Like `transition`, but looks up each cell's fate in `table` from `Rule::lookup_table`.
+    /// Only live cells and their neighbors are visited, row by row, sliding a 3×3 window along
+    /// runs of adjacent cells so each costs three lookups instead of nine.
+    pub fn transition_lut(board: &Board, table: &[bool; 512]) -> (HashSet<Point>, HashSet<Point>) {
+        let mut birthed_cells = HashSet::new();
+        let mut killed_cells = HashSet::new();
+        let mut cells: Vec<Point> = board
+            .iter()
+            .flat_map(|pos| {
+                board.neighbors(pos).map(|cell| match cell {
+                    Cell::Dead(pos) | Cell::Alive(pos) => pos,
+                })
+            })
+            .chain(board.iter().copied())
+            .collect();
+        cells.sort_by_key(|pos| (pos.y, pos.x));
+        cells.dedup();
+        // Cells off the edge of the `i64` range are dead
+        let alive = |x: Option<i64>, y: Option<i64>| match (x, y) {
+            (Some(x), Some(y)) => board.contains(&Point::new(x, y)) as usize,
+            _ => 0,
+        };
+        // Column at `x` as bits, top to bottom
+        let column = |x: Option<i64>, y: i64| {
+            alive(x, y.checked_add(1)) | alive(x, Some(y)) << 1 | alive(x, y.checked_sub(1)) << 2
+        };
+        let mut window = 0;
+        let mut previous: Option<Point> = None;
+        for pos in cells {
+            let (x, y) = (pos.x, pos.y);
+            window = match previous {
+                Some(p) if p.y == y && p.x.checked_add(1) == Some(x) => {
+                    (window >> 3) | column(x.checked_add(1), y) << 6
+                }
+                _ => {
+                    column(x.checked_sub(1), y)
+                        | column(Some(x), y) << 3
+                        | column(x.checked_add(1), y) << 6
                 }
-                // Rule 3
-                _ => {}
             };
+            previous = Some(pos);
+            match (window & (1 << 4) != 0, table[window]) {
+                (false, true) => {
+                    birthed_cells.insert(pos);
+                }
+                (true, false) => {
+                    killed_cells.insert(pos);
+                }
+                _ => (),
+            }
         }
+        (birthed_cells, killed_cells)
+    }
 
-        for pos in &self.killed_cells {
-            self.board.kill_cell(pos);
+    /// Like `transition`, but each cell is born or survives with the probability given by `rule`.
+    /// Cells are visited in sorted order so the outcome only depends on the state of `rng`.
+    pub fn transition_stochastic(
+        board: &Board,
+        rule: &StochasticRule,
+        rng: &mut Rng,
+    ) -> (HashSet<Point>, HashSet<Point>) {
+        let mut dead: Vec<Point> = board
+            .iter()
+            .flat_map(|pos| board.neighbors(pos))
+            .filter_map(|cell| match cell {
+                Cell::Dead(pos) => Some(pos),
+                Cell::Alive(_) => None,
+            })
+            .collect();
+        dead.sort();
+        dead.dedup();
+        let birthed_cells = dead
+            .into_iter()
+            .filter(|pos| rng.chance(rule.birth[board.live_neighbor_count(pos) as usize]))
+            .collect();
+        let mut alive: Vec<Point> = board.iter().copied().collect();
+        alive.sort();
+        let killed_cells = alive
+            .into_iter()
+            .filter(|pos| !rng.chance(rule.survive[board.live_neighbor_count(pos) as usize]))
+            .collect();
+        (birthed_cells, killed_cells)
+    }
+
+    /// Computes the next generation of `board` under `rule` without any bookkeeping. Generations
+    /// rules need the decaying cells, which a board doesn't hold, so they aren't supported.
+    pub fn next_board(board: &Board, rule: &Rule) -> Board {
+        debug_assert!(
+            rule.states() <= 2,
+            "next_board can't follow the decay of {}",
+            rule
+        );
+        let (birthed_cells, killed_cells) = GameOfLife::transition(board, rule);
+        let mut next = board.clone();
+        for pos in &killed_cells {
+            next.kill_cell(pos);
         }
+        for pos in &birthed_cells {
+            next.birth_cell(pos);
+        }
+        next
+    }
+
+    pub fn step(&mut self) -> StepReport {
+        let (mut birthed_cells, killed_cells) = match &mut self.stochastic {
+            Some(Stochastic { rule, rng }) => {
+                GameOfLife::transition_stochastic(&self.board, rule, rng)
+            }
+            None => GameOfLife::transition(&self.board, &self.rule),
+        };
+        self.decay(&mut birthed_cells, &killed_cells);
+        self.apply(birthed_cells, killed_cells)
+    }
 
-        for pos in &self.birthed_cells {
+    /// Like `step`, but computes the next generation with `transition_lut`. Stochastic and
+    /// Generations rules don't fit in a lookup table, so those fall back to `step`.
+    pub fn step_lut(&mut self) -> StepReport {
+        if self.stochastic.is_some() || self.rule.states() > 2 {
+            return self.step();
+        }
+        let (birthed_cells, killed_cells) =
+            GameOfLife::transition_lut(&self.board, &self.rule.lookup_table());
+        // Left over from a Generations rule, as `step` would clear them in `decay`
+        self.dying.clear();
+        self.apply(birthed_cells, killed_cells)
+    }
+
+    fn apply(&mut self, birthed_cells: HashSet<Point>, killed_cells: HashSet<Point>) -> StepReport {
+        for pos in &killed_cells {
+            self.board.kill_cell(pos);
+        }
+        for pos in &birthed_cells {
             self.board.birth_cell(pos);
         }
+        self.birthed_cells = birthed_cells;
+        self.killed_cells = killed_cells;
+        self.record_history();
+        self.finish_step()
+    }
+
+    /// Moves to the next generation using a `next` board computed elsewhere, e.g. by
+    /// `GameOfLife::next_board` on another thread. That can't draw from the game's generator or
+    /// age its decaying cells, so stochastic and Generations rules must `step` instead.
+    pub fn advance(&mut self, next: Board) -> StepReport {
+        debug_assert!(
+            self.stochastic.is_none() && self.rule.states() <= 2,
+            "advance can't follow stochastic or Generations rules"
+        );
+        (self.birthed_cells, self.killed_cells) = self.board.diff(&next);
+        self.board = next;
+        self.record_history();
+        self.finish_step()
+    }
+
+    /// Ages decaying cells under a Generations rule, where cells dying this step start decaying
+    /// and decaying cells can't be born into.
+    fn decay(&mut self, birthed_cells: &mut HashSet<Point>, killed_cells: &HashSet<Point>) {
+        let states = self.rule.states();
+        if states <= 2 {
+            self.dying.clear();
+            return;
+        }
+        birthed_cells.retain(|pos| !self.dying.contains_key(pos));
+        self.dying.retain(|_, state| {
+            *state += 1;
+            *state < states
+        });
+        self.dying.extend(killed_cells.iter().map(|&pos| (pos, 2)));
+    }
+
+    /// Records the step just applied to `board`.
+    fn record_history(&mut self) {
+        if self.stats.is_empty() {
+            self.stats.push_back(GenerationStats {
+                generation: self.generation,
+                population: self.board.population() + self.killed_cells.len()
+                    - self.birthed_cells.len(),
+                ..Default::default()
+            });
+        }
+        if self.history.len() == HISTORY_LIMIT {
+            self.history.pop_front();
+        }
+        self.history.push_back(Diff {
+            birthed: self.birthed_cells.clone(),
+            killed: self.killed_cells.clone(),
+        });
+    }
 
+    fn finish_step(&mut self) -> StepReport {
         self.generation += 1;
+        let report = StepReport {
+            generation: self.generation,
+            births: self.birthed_cells.len(),
+            deaths: self.killed_cells.len(),
+        };
+        if self.stats.len() == STATS_LIMIT {
+            self.stats.pop_front();
+        }
+        self.stats.push_back(GenerationStats {
+            generation: report.generation,
+            population: self.board.population(),
+            births: report.births,
+            deaths: report.deaths,
+        });
+        report
     }
 }
 
@@ -122,6 +778,43 @@ impl FromStr for GameOfLife {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::random::Rng;
+
+    #[test]
+    fn step_region() {
+        // A blinker well inside the region steps the same either way
+        let mut game: GameOfLife = ".....\n.xxx.\n.....".parse().unwrap();
+        game.board.birth_cell(&Point::new(20, 20));
+        let (min, max) = (Point::new(-2, -2), Point::new(6, 4));
+        let (missing, extra) = game.region_discrepancies(min, max);
+        assert!(missing.is_empty() && extra.is_empty());
+        let next = game.step_region(min, max);
+        assert!(next.contains(&Point::new(20, 20)), "outside is frozen");
+        assert!(next.contains(&Point::new(2, 2)) && next.contains(&Point::new(2, 0)));
+
+        // Cutting through it, the cells left inside starve
+        let (missing, extra) = game.region_discrepancies(Point::new(2, -2), Point::new(6, 4));
+        assert_eq!(
+            missing,
+            [Point::new(2, 0), Point::new(2, 1), Point::new(2, 2)].into()
+        );
+        assert!(extra.is_empty());
+    }
+
+    #[test]
+    fn clear() {
+        let mut game: GameOfLife = "xxx".parse().unwrap();
+        game.step_n(3);
+        assert!(!game.killed_cells.is_empty());
+        game.clear();
+        assert!(game.board.is_empty());
+        assert_eq!(game.generation, 0);
+        assert!(game.killed_cells.is_empty());
+        assert!(game.birthed_cells.is_empty());
+        assert!(game.history.is_empty());
+        assert!(game.stats.is_empty());
+        assert!(game.dying.is_empty());
+    }
 
     #[test]
     fn lonely_cell() {
@@ -155,6 +848,25 @@ mod test {
         assert_eq!(game.board, before);
     }
 
+    #[test]
+    fn step_report() {
+        let mut game: GameOfLife = "...\nxxx\n...".parse().unwrap();
+        let expected = StepReport {
+            generation: 1,
+            births: 2,
+            deaths: 2,
+        };
+        assert_eq!(game.step(), expected);
+
+        // A glider keeps its population, with births and deaths in step
+        let mut game: GameOfLife = ".x.\n..x\nxxx".parse().unwrap();
+        let next = GameOfLife::next_board(&game.board, &game.rule);
+        let report = game.advance(next);
+        assert_eq!(report.generation, 1);
+        assert_eq!(report.births, report.deaths);
+        assert_eq!(report.births, game.birthed_cells.len());
+    }
+
     #[test]
     fn sufficated_cell() {
         let mut game = GameOfLife::from([
@@ -178,6 +890,483 @@ mod test {
 
     #[test]
     fn oscillators() {
-        todo!()
+        // Blinker
+        let mut game: GameOfLife = "...\nxxx\n...".parse().unwrap();
+        let before = game.board.clone();
+        game.step();
+        assert_eq!(
+            game.board,
+            Board::from([(1i64, 0i64).into(), (1, 1).into(), (1, 2).into()])
+        );
+        game.step();
+        assert_eq!(game.board, before);
+    }
+
+    #[test]
+    fn invalid_char_caret() {
+        let err = "x..\n.?x\n...".parse::<Board>().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid char `?` found on line 1, column 1 when parsing\nx..\n.?x\n ^\n...\n into Board"
+        );
+
+        // Positions too far in to count stop at the limit rather than wrapping around
+        let wide = format!("{}?", ".".repeat(u16::MAX as usize + 1));
+        let err = wide.parse::<Board>().unwrap_err();
+        assert!(matches!(
+            err.kind(),
+            GameErrorKind::InvalidBoardChar { col: u16::MAX, .. }
+        ));
+    }
+
+    #[test]
+    fn population_csv() {
+        let mut game = GameOfLife::default();
+        assert_eq!(
+            game.population_csv(),
+            "generation,population,births,deaths\n"
+        );
+
+        game.restart(".x.\n..x\nxxx".parse().unwrap());
+        game.step_n(3);
+        game.step_back();
+        let csv = game.population_csv();
+        let mut lines = csv.lines();
+        assert_eq!(lines.next(), Some("generation,population,births,deaths"));
+        assert_eq!(lines.next(), Some("0,5,0,0"));
+        assert_eq!(lines.next(), Some("1,5,2,2"));
+        assert_eq!(lines.next(), Some("2,5,2,2"));
+        assert_eq!(lines.next(), None);
+
+        // Only the latest generations are kept
+        game.restart("xx".parse().unwrap());
+        game.step_n(STATS_LIMIT as u32 + 10);
+        assert_eq!(game.stats.len(), STATS_LIMIT);
+        assert_eq!(game.stats[0].generation, 11);
+    }
+
+    /// Property test cases generated per test
+    const CASES: usize = 300;
+
+    /// A small random soup at a random position, stepped under a random two-state preset rule.
+    fn random_game(rng: &mut Rng) -> GameOfLife {
+        let rules: Vec<Rule> = crate::rule::PRESETS
+            .iter()
+            .map(|(_, rule)| rule.parse().unwrap())
+            .filter(|rule: &Rule| rule.states() == 2)
+            .collect();
+        let min = Point::new(
+            (rng.next_u64() % 2000) as i64 - 1000,
+            (rng.next_u64() % 2000) as i64 - 1000,
+        );
+        let width = 1 + (rng.next_u64() % 12) as u16;
+        let height = 1 + (rng.next_u64() % 12) as u16;
+        let density = rng.next_f64();
+        let rule = rules[rng.next_u64() as usize % rules.len()];
+        GameOfLife {
+            board: Board::random(min, width, height, density, rng),
+            rule,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn step_translation_invariant() {
+        let mut rng = Rng::new(3);
+        for _ in 0..CASES {
+            let mut game = random_game(&mut rng);
+            let offset = Point::new(
+                (rng.next_u64() % (1 << 40)) as i64 - (1 << 39),
+                (rng.next_u64() % (1 << 40)) as i64 - (1 << 39),
+            );
+            let translated = game.board.translate(offset);
+            let next = GameOfLife::next_board(&translated, &game.rule);
+            game.step();
+            assert_eq!(
+                next,
+                game.board.translate(offset),
+                "{} moved by {:?} under {}",
+                translated.to_ascii(),
+                offset,
+                game.rule
+            );
+        }
+    }
+
+    /// Obviously correct reference for `step`: checks every cell in the bounding box grown by one,
+    /// counting neighbors directly.
+    fn step_naive(board: &Board, rule: &Rule) -> Board {
+        let Some((min, max)) = board.bounding_box() else {
+            return Board::default();
+        };
+        let mut next = Board::default();
+        for y in min.y - 1..=max.y + 1 {
+            for x in min.x - 1..=max.x + 1 {
+                let mut neighbors = 0;
+                for dy in -1..=1 {
+                    for dx in -1..=1 {
+                        if (dx, dy) != (0, 0) && board.contains(&Point::new(x + dx, y + dy)) {
+                            neighbors += 1;
+                        }
+                    }
+                }
+                let alive = board.contains(&Point::new(x, y));
+                if (alive && rule.survives(neighbors)) || (!alive && rule.births(neighbors)) {
+                    next.birth_cell(&Point::new(x, y));
+                }
+            }
+        }
+        next
+    }
+
+    #[test]
+    fn step_matches_naive() {
+        let mut rng = Rng::new(4);
+        for _ in 0..CASES {
+            let mut game = random_game(&mut rng);
+            for _ in 0..5 {
+                let expected = step_naive(&game.board, &game.rule);
+                let before = game.board.clone();
+                game.step();
+                assert_eq!(
+                    game.board,
+                    expected,
+                    "{}stepped under {}",
+                    before.to_ascii(),
+                    game.rule
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn step_lut_matches_step() {
+        let mut rng = Rng::new(6);
+        for _ in 0..CASES {
+            let mut game = random_game(&mut rng);
+            let mut lut = game.clone();
+            for _ in 0..5 {
+                let before = game.board.clone();
+                assert_eq!(lut.step_lut(), game.step());
+                assert_eq!(
+                    lut.board,
+                    game.board,
+                    "{}stepped under {}",
+                    before.to_ascii(),
+                    game.rule
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn lut_extreme_coordinates() {
+        let table = Rule::default().lookup_table();
+        // Far apart cells don't make it scan the space between them
+        let board = Board::from([
+            Point::new(0, 0),
+            Point::new(1_000_000_000, 0),
+            Point::new(i64::MAX, i64::MIN),
+            Point::new(i64::MIN, i64::MAX),
+        ]);
+        assert_eq!(
+            GameOfLife::transition_lut(&board, &table),
+            GameOfLife::transition(&board, &Rule::default())
+        );
+        // A blinker against the corner of the coordinate range
+        let corner = Point::new(i64::MAX, i64::MAX);
+        let board = Board::from([corner, corner - Point::new(1, 0), corner - Point::new(2, 0)]);
+        assert_eq!(
+            GameOfLife::transition_lut(&board, &table),
+            GameOfLife::transition(&board, &Rule::default())
+        );
+    }
+
+    #[test]
+    fn stochastic_extremes() {
+        let mut rng = Rng::new(5);
+        for _ in 0..20 {
+            let mut game = random_game(&mut rng);
+            let mut stochastic = game.clone();
+            stochastic.stochastic = Some(Stochastic {
+                rule: game.rule.into(),
+                rng: Rng::new(rng.next_u64()),
+            });
+            for _ in 0..5 {
+                assert_eq!(stochastic.step(), game.step());
+                assert_eq!(stochastic.board, game.board);
+            }
+
+            let mut dying = game.clone();
+            dying.stochastic = Some(Stochastic {
+                rule: StochasticRule {
+                    birth: [0.0; 9],
+                    survive: [0.0; 9],
+                },
+                rng: Rng::new(0),
+            });
+            let population = dying.board.population();
+            assert_eq!(dying.step().deaths, population);
+            assert!(dying.board.is_empty());
+        }
+    }
+
+    #[test]
+    fn stochastic_reproducible() {
+        let rule = StochasticRule {
+            birth: [0.0, 0.0, 0.0, 0.9, 0.1, 0.0, 0.0, 0.0, 0.0],
+            survive: [0.0, 0.0, 0.95, 0.95, 0.0, 0.0, 0.0, 0.0, 0.0],
+        };
+        let run = |seed| {
+            let mut game: GameOfLife = crate::patterns::QUEEN_BEE.parse().unwrap();
+            game.stochastic = Some(Stochastic {
+                rule,
+                rng: Rng::new(seed),
+            });
+            game.step_n(5);
+            game.board
+        };
+        assert_eq!(run(1), run(1));
+        assert_ne!(run(1), run(2));
+    }
+
+    #[test]
+    fn brians_brain() {
+        let mut game: GameOfLife = "xx".parse().unwrap();
+        game.rule = Rule::preset("Brian's Brain").unwrap();
+
+        // Live cells always start dying, and cells with two live neighbors are born
+        game.step();
+        let pair = |y| [Point::new(0, y), Point::new(1, y)];
+        assert_eq!(
+            game.board,
+            Board::from_iter(pair(1).into_iter().chain(pair(-1)))
+        );
+        assert_eq!(game.dying, pair(0).into_iter().map(|p| (p, 2)).collect());
+
+        // The dying pair can't be reborn, so the two pairs move apart
+        game.step();
+        assert!(game.dying.values().all(|&state| state == 2));
+        assert_eq!(game.dying.len(), 4);
+        for p in pair(0) {
+            assert!(!game.board.contains(&p));
+            assert!(!game.dying.contains_key(&p));
+        }
+        assert!(pair(2)
+            .iter()
+            .chain(&pair(-2))
+            .all(|p| game.board.contains(p)));
+    }
+
+    #[test]
+    fn step_lut_after_generations() {
+        let mut game: GameOfLife = "xx".parse().unwrap();
+        game.rule = Rule::preset("Brian's Brain").unwrap();
+        game.step_lut();
+        assert_eq!(game.dying.len(), 2);
+
+        game.rule = Rule::CONWAY;
+        let mut serial = game.clone();
+        game.step_lut();
+        serial.step();
+        assert!(game.dying.is_empty());
+        assert_eq!(game.board, serial.board);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "advance can't follow")]
+    fn advance_needs_two_states() {
+        let mut game: GameOfLife = "xx".parse().unwrap();
+        game.rule = Rule::preset("Brian's Brain").unwrap();
+        let next = GameOfLife::next_board(&game.board, &Rule::CONWAY);
+        game.advance(next);
+    }
+
+    #[test]
+    fn blinker_cell_history() {
+        let mut game: GameOfLife = "xxx".parse().unwrap();
+        game.step_n(4);
+        assert_eq!(
+            game.cell_history(&Point::new(0, 0)),
+            CellHistory {
+                generations: 5,
+                alive: 3,
+                age: 1
+            }
+        );
+        assert_eq!(game.cell_history(&Point::new(1, 0)).age, 5);
+        assert_eq!(game.cell_history(&Point::new(1, 1)).alive, 2);
+        assert_eq!(game.cell_history(&Point::new(1, 1)).age, 0);
+        assert_eq!(game.cell_history(&Point::new(10, 10)).alive, 0);
+    }
+
+    #[test]
+    fn glider_trail() {
+        let mut game: GameOfLife = ".x.\n..x\nxxx".parse().unwrap();
+        assert!(game.trail(3).is_empty());
+        for _ in 0..8 {
+            game.step();
+        }
+        let trail = game.trail(3);
+        assert!(!trail.is_empty());
+        for (p, &age) in &trail {
+            assert!(!game.board.contains(p));
+            assert!(game.past(age as usize).unwrap().contains(p));
+            assert!((1..age).all(|newer| !game.past(newer as usize).unwrap().contains(p)));
+        }
+        assert_eq!(trail.values().max(), Some(&3));
+
+        let mut block: GameOfLife = "xx\nxx".parse().unwrap();
+        block.step_n(3);
+        assert!(block.trail(3).is_empty());
+    }
+
+    #[test]
+    fn rewind_through_diffs() {
+        // R-pentomino churns through plenty of births and deaths
+        let mut game: GameOfLife = ".xx\nxx.\n.x.".parse().unwrap();
+        let original = game.board.clone();
+        let mut boards = vec![original.clone()];
+        for _ in 0..200 {
+            game.step();
+            boards.push(game.board.clone());
+        }
+        assert_eq!(game.past(200).unwrap(), original);
+        assert_eq!(game.past(201), None);
+        for generations in [0, 1, 57, 199] {
+            assert_eq!(game.past(generations).unwrap(), boards[200 - generations]);
+        }
+        while game.step_back() {
+            assert_eq!(game.board, boards[game.generation as usize]);
+        }
+        assert_eq!(game.generation, 0);
+        assert_eq!(game.board, original);
+    }
+
+    #[test]
+    fn edits_fold_into_history() {
+        let mut game: GameOfLife = "...\nxxx\n...".parse().unwrap();
+        let original = game.board.clone();
+        game.step();
+        let before = game.board.clone();
+        // Kill a cell that was just born, and birth one that was just killed and one far away
+        game.board.kill_cell(&Point::new(1, 2));
+        game.board.birth_cell(&Point::new(0, 1));
+        game.board.birth_cell(&Point::new(9, 9));
+        game.edited(&before);
+        assert_eq!(game.past(1).unwrap(), original);
+        assert!(game.step_back());
+        assert_eq!(game.board, original);
+    }
+
+    #[test]
+    fn advance_matches_step() {
+        let mut stepped: GameOfLife = ".x.\n..x\nxxx".parse().unwrap();
+        let mut advanced: GameOfLife = ".x.\n..x\nxxx".parse().unwrap();
+        for _ in 0..4 {
+            stepped.step();
+            advanced.advance(GameOfLife::next_board(&advanced.board, &advanced.rule));
+            assert_eq!(advanced.board, stepped.board);
+            assert_eq!(advanced.birthed_cells, stepped.birthed_cells);
+            assert_eq!(advanced.killed_cells, stepped.killed_cells);
+        }
+        assert_eq!(advanced.generation, 4);
+        assert_eq!(advanced.history, stepped.history);
+        assert_eq!(advanced.stats, stepped.stats);
+    }
+
+    #[test]
+    fn run_until_population() {
+        // R-pentomino grows past 20 cells at generation 16
+        let mut game: GameOfLife = ".xx\nxx.\n.x.".parse().unwrap();
+        let mut reference = game.clone();
+        let steps = game.run_until(|game| game.board.population() > 20, 100);
+        assert_eq!(steps, Some(16));
+        assert_eq!(game.generation, 16);
+        reference.step_n(15);
+        assert!(reference.board.population() <= 20);
+
+        // Already true, so nothing is stepped
+        assert_eq!(
+            game.run_until(|game| game.board.population() > 20, 100),
+            Some(0)
+        );
+        assert_eq!(game.generation, 16);
+
+        let mut game: GameOfLife = "xxx".parse().unwrap();
+        assert_eq!(game.run_until(|game| game.board.population() > 3, 50), None);
+        assert_eq!(game.generation, 50);
+    }
+
+    #[test]
+    fn run_until_stable() {
+        let mut game: GameOfLife = "xxx".parse().unwrap();
+        let result = game.run_until_stable(100);
+        assert!(result.stabilized());
+        assert_eq!(result.period(), Some(2));
+        assert_eq!(result.steps, 2);
+
+        let mut game: GameOfLife = "x.x".parse().unwrap();
+        let result = game.run_until_stable(100);
+        assert_eq!(result.stability, Stability::Extinct);
+        assert_eq!(result.steps, 1);
+
+        let mut game: GameOfLife = ".x.\n..x\nxxx".parse().unwrap();
+        let result = game.run_until_stable(100);
+        assert!(!result.stabilized());
+        assert!(matches!(result.stability, Stability::Drifting(_)));
+        assert_eq!(result.period(), Some(4));
+        assert_eq!(result.steps, 8);
+
+        // R-pentomino takes over a thousand generations
+        let mut game: GameOfLife = ".xx\nxx.\n.x.".parse().unwrap();
+        let result = game.run_until_stable(10);
+        assert_eq!(result.stability, Stability::Unstable);
+        assert_eq!(result.steps, 10);
+        assert_eq!(game.generation, 10);
+    }
+
+    #[test]
+    #[ignore = "runs thousands of generations, try with --release"]
+    fn acorn_stabilizes() {
+        let mut game = GameOfLife {
+            board: crate::patterns::get("Acorn").unwrap(),
+            ..Default::default()
+        };
+        let result = game.run_until_stable(6000);
+        assert!(result.stabilized());
+        assert_eq!(
+            result.stability,
+            Stability::Escaping(Escape {
+                period: 4,
+                spaceships: 13
+            })
+        );
+        // Settles at generation 5206, which is confirmed a period later
+        assert_eq!(result.steps, 5210);
+        assert_eq!(game.board.population(), 633);
+    }
+
+    #[test]
+    fn jump_to() {
+        let mut game: GameOfLife = "xxx".parse().unwrap();
+        let horizontal = game.board.clone();
+        game.jump_to(10, usize::MAX).unwrap();
+        assert_eq!(game.generation, 10);
+        assert_eq!(game.board, horizontal);
+
+        game.jump_to(3, usize::MAX).unwrap();
+        assert_eq!(game.generation, 3);
+        assert_eq!(game.board.to_ascii(), "x\nx\nx\n");
+
+        game.restart(horizontal);
+        assert!(game.jump_to(5, 2).is_err());
+        assert_eq!(game.generation, 0);
+        game.jump_to(2, 3).unwrap();
+        assert!(game.jump_to(0, 3).is_ok());
+        assert!(game.jump_to(1, 3).is_ok());
+        game.history.clear();
+        assert!(game.jump_to(0, 3).is_err());
     }
 }
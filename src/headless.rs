@@ -0,0 +1,359 @@
+use std::{
+    collections::HashSet,
+    fmt::Display,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    analysis::DensityMap,
+    board::Board,
+    format::{self, Format},
+    game::{GameError, GameErrorKind, GameOfLife},
+    loader,
+    point::Point,
+    rule::Rule,
+};
+
+/// Runs `input` (in any supported format) for `generations` and returns the final board written
+/// in the `output` format.
+pub fn run(input: &str, generations: u32, output: Format) -> Result<String, GameError> {
+    run_counting(input, generations, output, None)
+}
+
+/// Like `run`, also counting in `density`, if given, how many generations each cell is alive
+/// for.
+pub fn run_counting(
+    input: &str,
+    generations: u32,
+    output: Format,
+    mut density: Option<&mut DensityMap>,
+) -> Result<String, GameError> {
+    let mut game = GameOfLife {
+        board: format::parse(input)?,
+        ..Default::default()
+    };
+    for _ in 0..generations {
+        game.step();
+        if let Some(density) = density.as_deref_mut() {
+            density.record(&game.board);
+        }
+    }
+    Ok(output.write(&game.board))
+}
+
+/// Pixels per cell in image exports
+pub const IMAGE_CELL_SIZE: u32 = 10;
+
+/// Draws `board` from `min` to `max` with `labels` as an SVG if `path` ends in `.svg` or a PNG if
+/// it ends in `.png`, or returns `None` for anything else. PNGs mark labeled cells in gray as
+/// they can't hold text, and fail if too big.
+pub fn image(
+    path: &Path,
+    board: &Board,
+    (min, max): (Point, Point),
+    labels: &[(Point, String)],
+) -> Result<Option<Vec<u8>>, GameError> {
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("svg") => {
+            let labels: Vec<_> = labels.iter().map(|(at, s)| (*at, s.as_str())).collect();
+            Ok(Some(
+                board
+                    .to_svg_labeled(min, max, IMAGE_CELL_SIZE, &labels)
+                    .into_bytes(),
+            ))
+        }
+        Some("png") => {
+            let marked: HashSet<_> = labels.iter().map(|(at, _)| *at).collect();
+            Ok(Some(board.to_png(min, max, IMAGE_CELL_SIZE, &marked)?))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Runs `input` for exactly `generations` under the rule it declares and writes the final board
+/// with `labels` to `path` as an image, see `image`. The image covers the board and labels with
+/// a cell of margin, for making annotated diagrams in one go.
+pub fn capture(
+    input: &str,
+    generations: u32,
+    labels: &[(Point, String)],
+    path: &Path,
+) -> Result<(), String> {
+    let mut game = GameOfLife {
+        board: format::parse(input).map_err(|e| e.to_string())?,
+        rule: declared_rule(input)?.unwrap_or_default(),
+        ..Default::default()
+    };
+    game.step_n(generations);
+    // Labels run right of their cells, about a cell per character
+    let mut extent: Board = labels
+        .iter()
+        .flat_map(|(at, text)| [*at, *at + Point::new(text.chars().count() as i64, 0)])
+        .collect();
+    extent.union_with(&game.board);
+    let (min, max) = extent
+        .bounding_box()
+        .unwrap_or((Point::default(), Point::default()));
+    let bounds = (min - Point::new(1, 1), max + Point::new(1, 1));
+    let contents = image(path, &game.board, bounds, labels)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| {
+            format!(
+                "Can't capture to {}, expected a .svg or .png file",
+                path.display()
+            )
+        })?;
+    std::fs::write(path, contents).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// Writes `density` to `path` as a PNG heatmap if it ends in `.png` and CSV otherwise.
+pub fn write_density(path: &Path, density: &DensityMap) -> std::io::Result<()> {
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("png") => {
+            let png = density.to_png().map_err(|e| {
+                std::io::Error::new(std::io::ErrorKind::InvalidInput, e.to_string())
+            })?;
+            std::fs::write(path, png)
+        }
+        _ => std::fs::write(path, density.to_csv()),
+    }
+}
+
+/// The rule `source` declares, if any. A rule this can't run is warned about on stderr and
+/// treated as none rather than failing.
+fn declared_rule(source: &str) -> Result<Option<Rule>, String> {
+    match format::rule(source) {
+        Err(e) if matches!(e.kind(), GameErrorKind::UnsupportedRule { .. }) => {
+            eprintln!("Warning: {}, using {} instead", e, Rule::default());
+            Ok(None)
+        }
+        rule => rule.map_err(|e| e.to_string()),
+    }
+}
+
+/// Runs the pattern file at `path` for `generations` under the rule it declares, or Conway's if
+/// none, and returns the `stable_hash` of the final board in hex. The same file and generations
+/// give the same hash on any machine, for checking that they simulate alike.
+pub fn hash(path: &PathBuf, generations: u32) -> Result<String, String> {
+    let (source, board) = loader::load(path, format::parse)?;
+    let rule = declared_rule(&source)?;
+    let mut game = GameOfLife {
+        board,
+        rule: rule.unwrap_or_default(),
+        ..Default::default()
+    };
+    game.step_n(generations);
+    Ok(format!("{:016x}\n", game.board.stable_hash()))
+}
+
+/// How the final boards of two patterns compare, see `compare`.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Comparison {
+    Equal,
+    /// The same cells moved by `offset` from the first board to the second
+    SameShape {
+        offset: Point,
+    },
+    /// Boards that differ in this many cells
+    Differ {
+        cells: usize,
+    },
+}
+
+impl Display for Comparison {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Comparison::Equal => f.write_str("equal"),
+            Comparison::SameShape { offset } => f.write_fmt(format_args!(
+                "same shape, offset by ({}, {})",
+                offset.x, offset.y
+            )),
+            Comparison::Differ { cells } => f.write_fmt(format_args!("differ in {} cells", cells)),
+        }
+    }
+}
+
+/// Runs the pattern files at `a` and `b` for `generations`, each under the rule it declares,
+/// and compares where they end up.
+pub fn compare(a: &PathBuf, b: &PathBuf, generations: u32) -> Result<Comparison, String> {
+    let run = |path| -> Result<Board, String> {
+        let (source, board) = loader::load(path, format::parse)?;
+        let mut game = GameOfLife {
+            board,
+            rule: declared_rule(&source)?.unwrap_or_default(),
+            ..Default::default()
+        };
+        game.step_n(generations);
+        Ok(game.board)
+    };
+    let (a, b) = (run(a)?, run(b)?);
+    if a == b {
+        return Ok(Comparison::Equal);
+    }
+    if a.same_shape(&b) {
+        let (Some((from, _)), Some((to, _))) = (a.bounding_box(), b.bounding_box()) else {
+            unreachable!("only empty boards are equal to each other");
+        };
+        return Ok(Comparison::SameShape { offset: to - from });
+    }
+    let (added, removed) = a.diff(&b);
+    Ok(Comparison::Differ {
+        cells: added.len() + removed.len(),
+    })
+}
+
+/// Checks that the pattern file at `path` parses, returning a summary of it: population,
+/// bounding box, and the rule it declares, if any.
+pub fn validate(path: &PathBuf) -> Result<String, String> {
+    let (source, board) = loader::load(path, format::parse)?;
+    let rule = declared_rule(&source)?;
+    let mut report = format!("{}\npopulation = {}\n", path.display(), board.population());
+    match board.bounding_box() {
+        Some((min, max)) => report.push_str(&format!(
+            "bounding box = ({}, {}) to ({}, {}), {}x{}\n",
+            min.x,
+            min.y,
+            max.x,
+            max.y,
+            max.x - min.x + 1,
+            max.y - min.y + 1
+        )),
+        None => report.push_str("bounding box = empty\n"),
+    }
+    match rule {
+        Some(rule) => report.push_str(&format!("rule = {}\n", rule)),
+        None => report.push_str("rule = unspecified\n"),
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn validate_files() {
+        let dir = std::env::temp_dir().join("game-of-life-rs-validate");
+        std::fs::create_dir_all(&dir).unwrap();
+        let good = dir.join("glider.rle");
+        std::fs::write(&good, "x = 3, y = 3, rule = B3/S23\nbo$2bo$3o!\n").unwrap();
+        assert_eq!(
+            validate(&good).unwrap(),
+            format!(
+                "{}\npopulation = 5\nbounding box = (0, 0) to (2, 2), 3x3\nrule = B3/S23\n",
+                good.display()
+            )
+        );
+
+        let plain = dir.join("blinker.txt");
+        std::fs::write(&plain, "xxx").unwrap();
+        assert!(validate(&plain).unwrap().ends_with("rule = unspecified\n"));
+
+        let malformed = dir.join("malformed.rle");
+        std::fs::write(&malformed, "x = 3, y = 3\nbo$2bz$3o!\n").unwrap();
+        assert!(validate(&malformed).is_err());
+        let bad_rule = dir.join("bad-rule.rle");
+        std::fs::write(&bad_rule, "x = 3, y = 3, rule = B9/S\nbo$2bo$3o!\n").unwrap();
+        assert!(validate(&bad_rule).is_err());
+        let exotic = dir.join("wireworld.rle");
+        std::fs::write(&exotic, "x = 3, y = 1, rule = WireWorld\n3A!\n").unwrap();
+        assert!(validate(&exotic).unwrap().ends_with("rule = unspecified\n"));
+        assert!(validate(&dir.join("missing.rle")).is_err());
+    }
+
+    #[test]
+    fn annotated_capture() {
+        let dir = std::env::temp_dir().join("game-of-life-rs-capture");
+        std::fs::create_dir_all(&dir).unwrap();
+        let glider = "#N Glider\nx = 3, y = 3\nbo$2bo$3o!\n";
+        let labels = [(Point::new(3, 0), "moved".to_owned())];
+
+        // A glider moves a cell right and down every 4 generations
+        let svg = dir.join("glider.svg");
+        capture(glider, 4, &labels, &svg).unwrap();
+        let svg = std::fs::read_to_string(svg).unwrap();
+        assert!(svg.contains("width=\"100\" height=\"50\""));
+        assert_eq!(svg.lines().filter(|l| l.starts_with("<rect x=")).count(), 5);
+        assert!(svg.contains("<text x=\"40\" y=\"30\" font-size=\"10\" fill=\"#c00\">moved</text>"));
+
+        let png = dir.join("glider.png");
+        capture(glider, 4, &labels, &png).unwrap();
+        assert!(std::fs::read(&png).unwrap().starts_with(b"\x89PNG"));
+
+        let e = capture(glider, 4, &labels, &dir.join("glider.rle")).unwrap_err();
+        assert!(e.ends_with("expected a .svg or .png file"));
+    }
+
+    #[test]
+    fn hash_is_reproducible() {
+        let dir = std::env::temp_dir().join("game-of-life-rs-hash");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("r-pentomino.rle");
+        std::fs::write(&path, "x = 3, y = 3, rule = B3/S23\nb2o$2o$bo!\n").unwrap();
+
+        let first = hash(&path, 100).unwrap();
+        assert_eq!(first.len(), 17);
+        assert_eq!(hash(&path, 100).unwrap(), first);
+        assert_ne!(hash(&path, 101).unwrap(), first);
+
+        let mut game = GameOfLife {
+            board: format::parse(".xx\nxx.\n.x.").unwrap(),
+            ..Default::default()
+        };
+        game.step_n(100);
+        assert_eq!(first, format!("{:016x}\n", game.board.stable_hash()));
+        assert!(hash(&dir.join("missing.rle"), 1).is_err());
+    }
+
+    #[test]
+    fn compare_files() {
+        let dir = std::env::temp_dir().join("game-of-life-rs-compare");
+        std::fs::create_dir_all(&dir).unwrap();
+        let glider = dir.join("glider.rle");
+        std::fs::write(&glider, "x = 3, y = 3\nbo$2bo$3o!\n").unwrap();
+        let shifted = dir.join("shifted.txt");
+        std::fs::write(&shifted, "...x.\n....x\n..xxx").unwrap();
+        let blinker = dir.join("blinker.txt");
+        std::fs::write(&blinker, "xxx").unwrap();
+
+        assert_eq!(compare(&glider, &glider, 8).unwrap(), Comparison::Equal);
+        let Comparison::SameShape { offset } = compare(&glider, &shifted, 8).unwrap() else {
+            panic!("expected the same shape");
+        };
+        assert_eq!(offset, Point::new(2, 0));
+        assert_eq!(
+            compare(&glider, &blinker, 0).unwrap(),
+            Comparison::Differ { cells: 2 }
+        );
+        assert!(compare(&glider, &dir.join("missing.rle"), 1).is_err());
+    }
+
+    #[test]
+    fn density_files() {
+        let mut density = DensityMap::default();
+        let end = run_counting("xxx", 10, Format::Ascii, Some(&mut density)).unwrap();
+        assert_eq!(end, run("xxx", 10, Format::Ascii).unwrap());
+        assert_eq!(density.count(&crate::point::Point::new(0, 0)), 5);
+
+        let dir = std::env::temp_dir().join("game-of-life-rs-density");
+        std::fs::create_dir_all(&dir).unwrap();
+        let csv = dir.join("blinker.csv");
+        write_density(&csv, &density).unwrap();
+        assert!(std::fs::read_to_string(&csv)
+            .unwrap()
+            .starts_with("x,y,count\n"));
+        let png = dir.join("blinker.png");
+        write_density(&png, &density).unwrap();
+        assert!(std::fs::read(&png).unwrap().starts_with(b"\x89PNG"));
+    }
+
+    #[test]
+    fn blinker() {
+        assert_eq!(run("xxx", 1, Format::Ascii).unwrap(), "x\nx\nx\n");
+        assert_eq!(run("xxx", 2, Format::Ascii).unwrap(), "xxx\n");
+        assert_eq!(
+            run("x = 3, y = 1\n3o!", 1, Format::Rle).unwrap(),
+            "x = 1, y = 3\no$o$o!\n"
+        );
+    }
+}
@@ -0,0 +1,174 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+};
+
+use crate::{
+    analysis::DensityMap,
+    event::{Event, EventSender},
+    game::{GameError, GameOfLife},
+};
+
+/// Generations stepped between progress reports and checks for cancellation
+pub const JUMP_CHUNK: u32 = 100;
+
+/// How a jump ended.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JumpOutcome {
+    Reached,
+    Cancelled,
+    /// Stopped early, e.g. by the population cap
+    Failed(String),
+}
+
+/// Steps `game` forward to `target` in chunks of `JUMP_CHUNK` generations, calling `progress`
+/// with the generations done and total after each chunk. Stops at the end of the current chunk
+/// once `cancel` is set. Every generation stepped is counted in `density`, if given.
+pub fn run_jump(
+    game: &mut GameOfLife,
+    target: u32,
+    population_cap: usize,
+    cancel: &AtomicBool,
+    mut density: Option<&mut DensityMap>,
+    mut progress: impl FnMut(u32, u32),
+) -> Result<JumpOutcome, GameError> {
+    let start = game.generation;
+    let total = target.saturating_sub(start);
+    while game.generation < target {
+        if cancel.load(Ordering::Relaxed) {
+            return Ok(JumpOutcome::Cancelled);
+        }
+        let next = game.generation.saturating_add(JUMP_CHUNK).min(target);
+        game.jump_to_with(next, population_cap, |board| {
+            if let Some(density) = density.as_deref_mut() {
+                density.record(board);
+            }
+        })?;
+        progress(game.generation - start, total);
+    }
+    Ok(JumpOutcome::Reached)
+}
+
+/// A long jump forward running on a worker thread. Progress is reported with `Event::Progress`
+/// and the stepped game is handed back in an `Event::Jumped`, even if cancelled part way, along
+/// with `density` if one was given to keep counting.
+pub struct Jump {
+    pub thread: JoinHandle<()>,
+    pub target: u32,
+    /// Generations done and total as of the last progress report
+    pub progress: (u32, u32),
+    cancel: Arc<AtomicBool>,
+}
+
+impl Jump {
+    pub fn spawn(
+        mut game: GameOfLife,
+        target: u32,
+        population_cap: usize,
+        mut density: Option<DensityMap>,
+        sender: EventSender,
+    ) -> Self {
+        let cancel = Arc::new(AtomicBool::new(false));
+        let progress = (0, target.saturating_sub(game.generation));
+        let thread = {
+            let cancel = cancel.clone();
+            thread::spawn(move || {
+                let result = run_jump(
+                    &mut game,
+                    target,
+                    population_cap,
+                    &cancel,
+                    density.as_mut(),
+                    |done, total| {
+                        sender.send(Event::Progress { done, total }).ok();
+                    },
+                );
+                let outcome = result.unwrap_or_else(|e| JumpOutcome::Failed(e.to_string()));
+                sender
+                    .send(Event::Jumped {
+                        game: Arc::new(game),
+                        outcome,
+                        density,
+                    })
+                    .ok();
+            })
+        };
+        Self {
+            thread,
+            target,
+            progress,
+            cancel,
+        }
+    }
+
+    /// Asks the worker to stop after its current chunk.
+    pub fn cancel(&self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+impl std::fmt::Debug for Jump {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Jump")
+            .field("target", &self.target)
+            .field("progress", &self.progress)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::event::Listener;
+
+    #[test]
+    fn cancelled_jump_stops_early() {
+        let mut game: GameOfLife = ".x.\n..x\nxxx".parse().unwrap();
+        let cancel = AtomicBool::new(false);
+        let mut reports = vec![];
+        let outcome = run_jump(&mut game, 1000, usize::MAX, &cancel, None, |done, total| {
+            reports.push((done, total));
+            if done >= 2 * JUMP_CHUNK {
+                cancel.store(true, Ordering::Relaxed);
+            }
+        });
+        assert_eq!(outcome.unwrap(), JumpOutcome::Cancelled);
+        assert_eq!(game.generation, 2 * JUMP_CHUNK);
+        assert_eq!(reports, [(JUMP_CHUNK, 1000), (2 * JUMP_CHUNK, 1000)]);
+    }
+
+    #[test]
+    fn jump_on_worker() {
+        let listener = Listener::default();
+        let game: GameOfLife = "xxx".parse().unwrap();
+        Jump::spawn(
+            game,
+            250,
+            usize::MAX,
+            Some(DensityMap::default()),
+            listener.subscribe(),
+        );
+        let mut progress = vec![];
+        loop {
+            match listener.next().unwrap() {
+                Event::Progress { done, .. } => progress.push(done),
+                Event::Jumped {
+                    game,
+                    outcome,
+                    density,
+                } => {
+                    assert_eq!(outcome, JumpOutcome::Reached);
+                    assert_eq!(density.unwrap().generations, 250);
+                    assert_eq!(game.generation, 250);
+                    assert_eq!(game.board.to_ascii(), "xxx\n");
+                    break;
+                }
+                e => panic!("unexpected event {:?}", e),
+            }
+        }
+        assert_eq!(progress, [100, 200, 250]);
+    }
+}
@@ -0,0 +1,33 @@
+//! Conway's Game of Life engine: an unbounded board, rules, pattern formats and analysis. The
+//! terminal frontend is the `game-of-life-rs` binary built on top of this crate.
+//!
+//! ```
+//! use game_of_life_rs::prelude::*;
+//!
+//! let mut game: GameOfLife = "...\nxxx\n...".parse()?;
+//! let report = game.step();
+//! assert_eq!((report.births, report.deaths), (2, 2));
+//! assert_eq!(game.board.to_ascii(), "x\nx\nx\n");
+//! # Ok::<(), GameError>(())
+//! ```
+
+pub mod analysis;
+pub mod board;
+pub mod format;
+pub mod game;
+pub mod patterns;
+pub mod point;
+pub mod random;
+pub mod rule;
+pub mod soup;
+
+/// The types needed to load and run a pattern.
+pub mod prelude {
+    pub use crate::{
+        board::Board,
+        format::Format,
+        game::{GameError, GameOfLife, StepReport},
+        point::Point,
+        rule::Rule,
+    };
+}
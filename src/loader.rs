@@ -0,0 +1,211 @@
+use std::{
+    path::PathBuf,
+    sync::Arc,
+    thread::{self, JoinHandle},
+};
+
+use crate::{
+    board::Board,
+    event::{Event, EventSender},
+    format::{self, gzip, rle, Format},
+    game::GameError,
+    point::Point,
+};
+
+pub type ParseFn = fn(&str) -> Result<Board, GameError>;
+
+/// RLE files at least this long get an `Event::BoardPreview` of the cells in view before the
+/// whole board is built
+pub const PREVIEW_MIN_BYTES: usize = 1 << 16;
+
+/// Reads the pattern text at `path`. Gzipped files are detected by their magic bytes and
+/// decompressed transparently.
+fn read(path: &PathBuf) -> Result<String, String> {
+    let mut bytes =
+        std::fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    if gzip::is_gzip(&bytes) {
+        bytes = gzip::decompress(&bytes).map_err(|e| e.to_string())?;
+    }
+    String::from_utf8(bytes).map_err(|_| format!("{} is not valid UTF-8 text", path.display()))
+}
+
+/// Reads and parses the pattern at `path`, returning its source text too.
+pub fn load(path: &PathBuf, parse: ParseFn) -> Result<(String, Board), String> {
+    let source = read(path)?;
+    let board = parse(&source).map_err(|e| e.to_string())?;
+    Ok((source, board))
+}
+
+/// Loads the pattern at `path` on a worker thread so large files don't freeze the UI. The result
+/// is delivered as an `Event::BoardLoaded` or `Event::LoadFailed`.
+pub fn spawn_load(path: PathBuf, sender: EventSender) -> JoinHandle<()> {
+    spawn_load_with(path, sender, format::parse)
+}
+
+pub fn spawn_load_with(path: PathBuf, sender: EventSender, parse: ParseFn) -> JoinHandle<()> {
+    spawn(path, None, sender, parse)
+}
+
+/// The corners `(min, max)` of the view to preview a pattern in, given the corners of the
+/// rectangle its header says it fills.
+pub type ViewFn = Box<dyn FnOnce(Point, Point) -> (Point, Point) + Send>;
+
+/// Like `spawn_load`, but large RLE files first send an `Event::BoardPreview` with just the cells
+/// in the view `fit` picks for the pattern's declared size, which is quick to show while the full
+/// board is built.
+pub fn spawn_load_previewed(path: PathBuf, fit: ViewFn, sender: EventSender) -> JoinHandle<()> {
+    spawn(path, Some(fit), sender, format::parse)
+}
+
+fn spawn(
+    path: PathBuf,
+    fit: Option<ViewFn>,
+    sender: EventSender,
+    parse: ParseFn,
+) -> JoinHandle<()> {
+    thread::spawn(move || {
+        let source = match read(&path) {
+            Ok(source) => source,
+            Err(error) => {
+                sender.send(Event::LoadFailed { path, error }).ok();
+                return;
+            }
+        };
+        if let Some(fit) = fit
+            .filter(|_| source.len() >= PREVIEW_MIN_BYTES && Format::sniff(&source) == Format::Rle)
+        {
+            // A malformed file fails below with a better error
+            if let Ok(Some(bounds)) = rle::declared_bounds(&source) {
+                let (min, max) = fit(bounds.0, bounds.1);
+                if let Ok(board) = rle::parse_within(&source, min, max) {
+                    let board = Arc::new(board);
+                    sender.send(Event::BoardPreview { board, bounds }).ok();
+                }
+            }
+        }
+        let event = match parse(&source) {
+            Ok(board) => Event::BoardLoaded {
+                path,
+                source: source.into(),
+                board: Arc::new(board),
+            },
+            Err(error) => Event::LoadFailed {
+                path,
+                error: error.to_string(),
+            },
+        };
+        sender.send(event).ok();
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::{Duration, Instant};
+
+    use super::*;
+    use crate::event::Listener;
+
+    /// `printf 'x = 3, y = 3\nbo$2bo$3o!\n' | gzip -n`, shared with the `gzip` tests
+    const GLIDER_GZ: &[u8] = include_bytes!("../testdata/glider.rle.gz");
+
+    /// A directory of its own for the test `name`, so tests running at once don't collide.
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("loader-{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn slow_parse(s: &str) -> Result<Board, GameError> {
+        thread::sleep(Duration::from_millis(200));
+        format::parse(s)
+    }
+
+    #[test]
+    fn loads_in_background() {
+        let dir = temp_dir("background");
+        let path = dir.join("blinker.txt");
+        std::fs::write(&path, "xxx").unwrap();
+
+        let listener = Listener::default();
+        let start = Instant::now();
+        spawn_load_with(path.clone(), listener.subscribe(), slow_parse);
+        assert!(start.elapsed() < Duration::from_millis(200));
+        match listener.next().unwrap() {
+            Event::BoardLoaded {
+                path: loaded,
+                source,
+                board,
+            } => {
+                assert_eq!(loaded, path);
+                assert_eq!(&*source, "xxx");
+                assert_eq!(board.population(), 3);
+            }
+            e => panic!("unexpected event {:?}", e),
+        }
+
+        spawn_load(dir.join("missing.rle"), listener.subscribe());
+        assert!(matches!(listener.next().unwrap(), Event::LoadFailed { .. }));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn previews_large_rle() {
+        let dir = temp_dir("preview");
+        let path = dir.join("large.rle");
+        // Comments pad out a sparse pattern with a live cell at each end of every row
+        let mut s = String::new();
+        while s.len() < PREVIEW_MIN_BYTES {
+            s.push_str("#C Padding to make the file large\n");
+        }
+        s.push_str("x = 1000, y = 1000\n");
+        s.push_str(&"o998bo$\n".repeat(1000));
+        s.push('!');
+        std::fs::write(&path, &s).unwrap();
+
+        let listener = Listener::default();
+        let view = (Point::new(0, 990), Point::new(9, 999));
+        let fit = move |min, max| {
+            assert_eq!((min, max), (Point::new(0, 0), Point::new(999, 999)));
+            view
+        };
+        spawn_load_previewed(path.clone(), Box::new(fit), listener.subscribe());
+        match listener.next().unwrap() {
+            Event::BoardPreview { board, bounds } => {
+                assert_eq!(board.population(), 10);
+                assert_eq!(board.bounding_box(), Some((view.0, Point::new(0, 999))));
+                assert_eq!(bounds, (Point::new(0, 0), Point::new(999, 999)));
+            }
+            e => panic!("unexpected event {:?}", e),
+        }
+        assert!(matches!(
+            listener.next().unwrap(),
+            Event::BoardLoaded { board, .. } if board.population() == 2000
+        ));
+
+        // Small files don't bother
+        let small = dir.join("small.rle");
+        std::fs::write(&small, "x = 3, y = 1\n3o!").unwrap();
+        spawn_load_previewed(small, Box::new(fit), listener.subscribe());
+        assert!(matches!(
+            listener.next().unwrap(),
+            Event::BoardLoaded { .. }
+        ));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn loads_gzipped() {
+        let dir = temp_dir("gzipped");
+        let plain = dir.join("glider.rle");
+        let gzipped = dir.join("glider.rle.gz");
+        std::fs::write(&plain, "x = 3, y = 3\nbo$2bo$3o!\n").unwrap();
+        std::fs::write(&gzipped, GLIDER_GZ).unwrap();
+
+        let (source, board) = load(&gzipped, format::parse).unwrap();
+        assert_eq!(load(&plain, format::parse).unwrap(), (source, board));
+
+        std::fs::write(&gzipped, &GLIDER_GZ[..20]).unwrap();
+        assert!(load(&gzipped, format::parse).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,28 +1,51 @@
 #![allow(dead_code)]
 
-mod board;
+mod action;
+mod cli;
 mod event;
-mod game;
+mod gallery;
+mod headless;
+mod jump;
+mod loader;
 mod model;
-mod point;
 mod program;
+mod remote;
+mod session;
+mod simulation;
 mod terminal;
+mod theme;
 mod widgets;
 
+use game_of_life_rs::{analysis, board, format, game, patterns, point, rule};
+
+use action::{Action, PopulationTarget};
 use board::Board;
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
 use event::Event;
-use game::{GameError, GameOfLife};
+use gallery::Gallery;
+use game::{Diff, GameError, GameOfLife};
+use jump::{Jump, JumpOutcome};
 use model::Model;
-use point::Point;
+use point::{Axis, Point, YAxis};
 use program::{Command, Context, Program};
-use std::time::Duration;
+use session::{Entry, Replay, Session};
+use simulation::Simulation;
+use std::{
+    borrow::Cow,
+    collections::{HashMap, VecDeque},
+    ffi::OsString,
+    io::Read,
+    path::Path,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 use tui::{
     layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
     text::Text,
-    widgets::Paragraph,
+    widgets::{Block, Borders, Clear, Gauge, List, ListItem, ListState, Paragraph},
 };
-use widgets::BoardWidget;
+use widgets::{BoardWidget, Ruler, Slider, Viewport};
 
 // TODO:
 // 1. Game controls w/ toggle-able UI
@@ -37,7 +60,68 @@ use widgets::BoardWidget;
 //      - birth/death of cells
 // 7. Add origin axis
 
-const QUEEN_BEE_BOARD: &str = "xx..\nx.x.\n...x\nx..x\n...x\nx.x.\nxx..";
+/// Longest period checked when looking for steady-state drift
+const MAX_DRIFT_PERIOD: u32 = 30;
+
+/// Slowest tick rate the adaptive mode will back off to
+const MAX_ADAPTIVE_TICK: Duration = Duration::from_secs(2);
+
+/// Adjusts the tick rate based on how long the last step took. A step that overran `tick` slows
+/// the tick to comfortably fit it, while a cheap step speeds it back up towards `target`.
+fn adapt_tick_rate(tick: Duration, step: Duration, target: Duration) -> Duration {
+    if step > tick {
+        (step * 3 / 2).min(MAX_ADAPTIVE_TICK)
+    } else if step * 2 < tick && tick > target {
+        (tick * 3 / 4).max(target).max(step * 3 / 2)
+    } else {
+        tick
+    }
+}
+
+/// Fastest tick rate on the tick rate slider
+const MIN_TICK: Duration = Duration::from_millis(10);
+
+/// Position of `tick` on the tick rate slider, which runs from `MIN_TICK` to `MAX_ADAPTIVE_TICK`
+/// on a log scale so fast rates get as much room as slow ones.
+fn tick_to_ratio(tick: Duration) -> f64 {
+    let range = (MAX_ADAPTIVE_TICK.as_secs_f64() / MIN_TICK.as_secs_f64()).ln();
+    ((tick.as_secs_f64() / MIN_TICK.as_secs_f64()).ln() / range).clamp(0.0, 1.0)
+}
+
+/// Tick rate at `ratio` along the tick rate slider, the inverse of `tick_to_ratio`.
+fn ratio_to_tick(ratio: f64) -> Duration {
+    let range = MAX_ADAPTIVE_TICK.as_secs_f64() / MIN_TICK.as_secs_f64();
+    MIN_TICK.mul_f64(range.powf(ratio.clamp(0.0, 1.0)))
+}
+
+/// Rounds `point` to the nearest point that's a multiple of `size` away from `anchor` on each
+/// axis.
+fn snap_to_grid(point: Point, anchor: Point, size: Point) -> Point {
+    let snap = |v: i64, a: i64, s: i64| a + (v - a + s / 2).div_euclid(s) * s;
+    Point::new(
+        snap(point.x, anchor.x, size.x),
+        snap(point.y, anchor.y, size.y),
+    )
+}
+
+/// Editor undo steps kept, see `App::edit`
+const EDIT_HISTORY_LIMIT: usize = 100;
+
+/// Jumps stop early once the population exceeds this, to avoid hanging on explosive patterns
+const JUMP_POPULATION_CAP: usize = 100_000;
+
+/// Most generations a time-lapse steps per tick, since each tick's steps run on the UI thread
+const MAX_TIME_LAPSE: u32 = 1000;
+
+/// Most cells `:tile` will build a stamp from
+const TILE_POPULATION_CAP: u64 = 100_000;
+
+/// Furthest out the zoom keys go unless `--max-zoom` says otherwise, as world cells per screen
+/// cell. Zoomed further out, drawing has to cover enormous regions of the world.
+const DEFAULT_MAX_ZOOM: u16 = 64;
+
+/// Generations a trail lingers behind moving cells
+const TRAIL_LENGTH: u8 = 4;
 
 #[derive(Debug, Default)]
 enum AppState {
@@ -48,13 +132,25 @@ enum AppState {
 }
 
 impl AppState {
-    fn toggle(&mut self) {
-        match self {
-            AppState::Stopped => *self = AppState::Running,
-            AppState::Paused => *self = AppState::Running,
-            AppState::Running => *self = AppState::Paused,
+    /// Starts running, returning whether that starts a fresh run from `Stopped` rather than
+    /// resuming a paused one.
+    fn play(&mut self) -> bool {
+        let fresh = matches!(self, AppState::Stopped);
+        *self = AppState::Running;
+        fresh
+    }
+
+    /// Pauses if running.
+    fn pause(&mut self) {
+        if matches!(self, AppState::Running) {
+            *self = AppState::Paused;
         }
     }
+
+    /// Ends the run, going back to editing.
+    fn stop(&mut self) {
+        *self = AppState::Stopped;
+    }
 }
 
 #[derive(Debug, Default)]
@@ -72,8 +168,126 @@ struct App {
     state: AppState,
     view: AppView,
     mouse: (u16, u16),
+    /// Button held down and where it was pressed, to match with its release
+    pressed: Option<(MouseButton, u16, u16)>,
     board_area: Rect,
+    /// Where the tick rate slider was last laid out, for dragging it with the mouse
+    tick_slider: Rect,
     initial_board: Board,
+    /// Library pattern waiting to be stamped at the mouse cursor
+    pending_stamp: Option<Board>,
+    /// Index into `patterns::LIBRARY` of the last selected pattern
+    library_index: usize,
+    /// Name of the library pattern the pending stamp was picked from
+    stamp_source: Option<&'static str>,
+    /// Name of the library pattern last stamped onto the board, whose metadata is kept when
+    /// exporting
+    stamped_from: Option<&'static str>,
+    /// Keep the board's center of mass centered each generation
+    follow: bool,
+    /// Text typed into the command input, if it's open
+    input: Option<String>,
+    /// Feedback from the last command
+    status: String,
+    /// Steady-state drift of the board as of the last generation
+    drift: Option<analysis::Drift>,
+    /// Slow down the tick rate when steps can't keep up with `game_tick`
+    adaptive: bool,
+    /// Tick rate currently in effect, which differs from `game_tick` when adaptive
+    effective_tick: Duration,
+    /// Index into the game's history being previewed, where `history.len()` is the current board
+    scrub: Option<usize>,
+    /// The board at `scrub` as `(index, generation, board)`, so it's rebuilt from history when
+    /// the index or the game moves rather than on every frame
+    scrubbed: Option<(usize, u32, Board)>,
+    /// Worker stepping the game, spawned once there's an event loop to report back to
+    simulation: Option<Simulation>,
+    /// Step currently running on `simulation`
+    pending_step: Option<PendingStep>,
+    /// World cells per screen cell along each axis
+    zoom: u16,
+    /// Bounds the zoom keys keep `zoom` within
+    min_zoom: u16,
+    max_zoom: u16,
+    /// Index into `rule::PRESETS` highlighted in the rule menu, if it's open
+    rule_menu: Option<usize>,
+    /// Pattern files being browsed, from `:gallery`
+    gallery: Option<Gallery>,
+    /// Text of the originally loaded pattern, re-parsed to start over from scratch
+    source: String,
+    /// Highlight the cells that changed since the previous generation
+    show_diff: bool,
+    /// From `:region`, cells alive next generation only when stepping the full board and only
+    /// when stepping the region alone, highlighted until the next step
+    discrepancies: Option<(Board, Board)>,
+    /// Cell clicked while paused, whose history is shown in the info panel
+    queried: Option<Point>,
+    /// Shade the background of every other cell to help count them
+    checkerboard: bool,
+    /// Draw a fading trail of where cells were alive over the last `TRAIL_LENGTH` generations
+    show_trail: bool,
+    /// Overlay the generation and population on a corner of the board
+    hud: bool,
+    /// Show `initial_board` in place of the current one to compare them, without touching the
+    /// game
+    show_initial: bool,
+    /// Keyboard cursor for editing without a mouse, shown once an arrow key is pressed
+    cursor: Option<Point>,
+    /// Index into `theme::THEMES` of the theme the board is drawn with
+    theme: usize,
+    /// Glyphs live and dead cells are drawn with
+    cell_glyphs: &'static theme::CellGlyphs,
+    /// Draw two world rows per terminal row with half-block glyphs
+    half_blocks: bool,
+    /// Long jump running in the background, during which the board can't be edited
+    jump: Option<Jump>,
+    /// Whether `jump` was started by `:freeze`, which says so once it's reached
+    freezing: bool,
+    /// Draw the board with ASCII characters only
+    ascii: bool,
+    /// Which way y increases in coordinate readouts
+    y_axis: YAxis,
+    /// Snap stamps to a grid the size of the pattern, so repeated stamps tile
+    snap: bool,
+    /// Where the first stamp since turning on snapping landed, which the grid is aligned to
+    snap_anchor: Option<Point>,
+    /// Pause once the population crosses this, set by `:until`
+    pause_when: Option<PopulationTarget>,
+    /// Show coordinate rulers along the top and left of the board
+    rulers: bool,
+    /// Show the info panel as a bar below the board instead of a column left of it
+    info_bottom: bool,
+    /// Allow drawing while running or paused. Edits land between steps, and a step already in
+    /// flight on the worker is discarded so the next generation includes them.
+    live_edit: bool,
+    /// Generations stepped per tick, more than 1 for a time-lapse
+    generations_per_tick: u32,
+    /// Notes attached to world cells, drawn beside them when in view
+    labels: Vec<(Point, String)>,
+    /// Whether the terminal reports the mouse to us. Turned off so text can be selected and
+    /// copied, and back on by the next key press.
+    mouse_capture: bool,
+    /// Cells in view of a large pattern that's still loading, shown in place of the board
+    load_preview: Option<Board>,
+    /// Boards from before each edit, most recent last, separate from the game's history
+    edit_history: VecDeque<Board>,
+    /// Boards from before each undo, most recent last
+    redo_stack: Vec<Board>,
+    /// Everything done to the game since starting, when recording a session
+    recording: Option<Session>,
+    /// Generations each cell has been alive for, when `--density` asks for them
+    density: Option<analysis::DensityMap>,
+    /// Session being played back as the game reaches each entry's generation
+    replay: Option<Replay>,
+}
+
+/// What a step on the simulation worker was requested from, so that results for a game that has
+/// since been reset or edited can be ignored.
+#[derive(Debug)]
+struct PendingStep {
+    started: Instant,
+    board: Arc<Board>,
+    rule: rule::Rule,
 }
 
 impl App {
@@ -85,15 +299,873 @@ impl App {
             state: Default::default(),
             view: Default::default(),
             board_area: Default::default(),
+            tick_slider: Default::default(),
             mouse: Default::default(),
+            pressed: None,
             initial_board: Default::default(),
+            pending_stamp: Default::default(),
+            library_index: Default::default(),
+            stamp_source: None,
+            stamped_from: None,
+            follow: false,
+            input: None,
+            status: Default::default(),
+            drift: None,
+            adaptive: false,
+            effective_tick: game_tick,
+            scrub: None,
+            scrubbed: None,
+            simulation: None,
+            pending_step: None,
+            zoom: 1,
+            min_zoom: 1,
+            max_zoom: DEFAULT_MAX_ZOOM,
+            rule_menu: None,
+            gallery: None,
+            source: String::new(),
+            show_diff: false,
+            discrepancies: None,
+            queried: None,
+            checkerboard: false,
+            show_trail: false,
+            hud: false,
+            show_initial: false,
+            cursor: None,
+            theme: 0,
+            cell_glyphs: &theme::BLOCKS,
+            half_blocks: false,
+            jump: None,
+            freezing: false,
+            snap: false,
+            snap_anchor: None,
+            pause_when: None,
+            rulers: false,
+            info_bottom: false,
+            live_edit: false,
+            generations_per_tick: 1,
+            labels: Vec::new(),
+            mouse_capture: true,
+            load_preview: None,
+            edit_history: VecDeque::new(),
+            redo_stack: Vec::new(),
+            recording: None,
+            density: None,
+            replay: None,
+            ascii: false,
+            y_axis: YAxis::Up,
         }
     }
 
     fn board(mut self, s: &str) -> Result<Self, GameError> {
-        self.game.board_from_str(s)?;
+        self.game.board = format::parse(s)?;
+        if let Ok(Some(rule)) = format::rule(s) {
+            self.game.rule = rule;
+        }
+        self.source = s.to_owned();
+        Ok(self)
+    }
+
+    /// Limits how far the zoom keys go in and out, where unset bounds keep their defaults.
+    fn zoom_limits(mut self, min: Option<u16>, max: Option<u16>) -> Result<Self, String> {
+        let min = min.unwrap_or(self.min_zoom);
+        let max = max.unwrap_or(self.max_zoom);
+        if min == 0 || min > max {
+            return Err(format!(
+                "Invalid zoom limits {} to {}, expected 1 <= min <= max",
+                min, max
+            ));
+        }
+        self.min_zoom = min;
+        self.max_zoom = max;
+        self.zoom = self.zoom.clamp(min, max);
         Ok(self)
     }
+
+    /// Replaces the board with a newly loaded pattern, which `reload` will return to, switching
+    /// to the rule the pattern declares, or Conway's if it doesn't declare one or declares one
+    /// that isn't supported. Returns a note on the rule if it changed or wasn't supported.
+    fn loaded(&mut self, source: String, board: Board) -> Option<String> {
+        let (rule, note) = match format::rule(&source) {
+            Ok(Some(rule)) => (rule, Some(format!("rule set to {}", rule))),
+            Ok(None) => (rule::Rule::CONWAY, None),
+            Err(e) => (
+                rule::Rule::CONWAY,
+                Some(format!(
+                    "{}, running under {} instead",
+                    e,
+                    rule::Rule::CONWAY
+                )),
+            ),
+        };
+        let note = match note {
+            None if rule != self.game.rule => Some(format!("rule set to {}", rule)),
+            note => note,
+        };
+        if rule != self.game.rule {
+            self.game.rule = rule;
+            self.record(Entry::Action(Action::Rule(rule)));
+        }
+        self.source = source;
+        self.stamped_from = None;
+        self.state.stop();
+        self.record(Entry::Restart(board.clone()));
+        self.initial_board = board.clone();
+        self.game.restart(board);
+        self.drift = None;
+        self.edit_history.clear();
+        self.redo_stack.clear();
+        self.fit_view();
+        note
+    }
+
+    /// Centers the view on the board and zooms to show all of it, within the zoom limits.
+    fn fit_view(&mut self) {
+        if let Some((min, max)) = self.game.board.bounding_box() {
+            self.fit_view_to(min, max);
+        }
+    }
+
+    /// Centers the view on the rectangle from `min` to `max` and zooms to show all of it.
+    fn fit_view_to(&mut self, min: Point, max: Point) {
+        let viewport = self.fitter()(min, max);
+        self.origin = viewport.origin;
+        self.zoom = viewport.zoom;
+    }
+
+    /// The viewport `fit_view_to` would pick for a rectangle, usable off this thread.
+    fn fitter(&self) -> impl Fn(Point, Point) -> Viewport + Send + 'static {
+        let (viewport, area) = (self.viewport(), self.board_area);
+        let zooms = (self.min_zoom, self.max_zoom);
+        move |min, max| {
+            let mut viewport = viewport;
+            viewport.fit(min, max, area.width, area.height);
+            viewport.zoom = viewport.zoom.clamp(zooms.0, zooms.1);
+            viewport
+        }
+    }
+
+    /// Like `loaded`, reporting the file and any rule change in the status line.
+    fn loaded_file(&mut self, path: &Path, source: String, board: Board) {
+        self.status = match self.loaded(source, board) {
+            Some(note) => format!("Loaded {}, {}", path.display(), note),
+            None => format!("Loaded {}", path.display()),
+        };
+    }
+
+    /// Whether the board can be drawn on, which is only while stopped unless live editing.
+    fn can_edit(&self) -> bool {
+        matches!(self.state, AppState::Stopped) || self.live_edit
+    }
+
+    /// Applies a drawing action to the board, remembering the board from before so it can be
+    /// undone if anything changed.
+    fn edit(&mut self, edit: impl FnOnce(&mut Board)) {
+        let before = self.game.board.clone();
+        edit(&mut self.game.board);
+        if self.game.board != before {
+            self.record_edit(&before);
+            self.push_undo(before);
+        }
+    }
+
+    fn push_undo(&mut self, before: Board) {
+        if self.edit_history.len() == EDIT_HISTORY_LIMIT {
+            self.edit_history.pop_front();
+        }
+        self.edit_history.push_back(before);
+        self.redo_stack.clear();
+    }
+
+    /// Empties the board and starts over from generation 0. The old board can still be brought
+    /// back with undo.
+    fn clear(&mut self) {
+        if self.game.board.is_empty() && self.game.generation == 0 {
+            return;
+        }
+        let before = std::mem::take(&mut self.game.board);
+        self.game.clear();
+        self.stamped_from = None;
+        self.record(Entry::Restart(Board::default()));
+        self.drift = None;
+        if !before.is_empty() {
+            self.push_undo(before);
+        }
+    }
+
+    /// Pairs button presses with releases so a click acts once, on release, however many events
+    /// the terminal sends for it. Returns whether `mouse` completes a click: a release over the
+    /// cell its press was on, or with no press seen since some terminals only report releases.
+    /// Releasing elsewhere, like after dragging the tick slider, isn't a click.
+    fn track_press(&mut self, mouse: MouseEvent) -> bool {
+        match mouse.kind {
+            MouseEventKind::Down(button) => {
+                self.pressed = Some((button, mouse.column, mouse.row));
+                false
+            }
+            MouseEventKind::Up(button) => match self.pressed.take() {
+                Some(pressed) => pressed == (button, mouse.column, mouse.row),
+                None => true,
+            },
+            _ => false,
+        }
+    }
+
+    fn undo(&mut self) {
+        if let Some(board) = self.edit_history.pop_back() {
+            let before = std::mem::replace(&mut self.game.board, board);
+            self.record_edit(&before);
+            self.redo_stack.push(before);
+        }
+    }
+
+    fn redo(&mut self) {
+        if let Some(board) = self.redo_stack.pop() {
+            let before = std::mem::replace(&mut self.game.board, board);
+            self.record_edit(&before);
+            self.edit_history.push_back(before);
+        }
+    }
+
+    /// Adds `entry` to the session at the current generation, if recording.
+    fn record(&mut self, entry: Entry) {
+        if let Some(session) = &mut self.recording {
+            session.entries.push((self.game.generation, entry));
+        }
+    }
+
+    /// Notes that the board was edited from `before`, both in the game's history and the session.
+    fn record_edit(&mut self, before: &Board) {
+        self.game.edited(before);
+        let (birthed, killed) = before.diff(&self.game.board);
+        self.record(Entry::Edit(Diff { birthed, killed }));
+    }
+
+    /// Starts playing back `session` from its first generation.
+    fn replaying(mut self, session: &Session) -> Self {
+        let (game, replay) = Replay::start(session);
+        self.initial_board = game.board.clone();
+        self.game = game;
+        self.replay = Some(replay);
+        self.status = "Replaying session".to_owned();
+        self.advance_replay();
+        self
+    }
+
+    /// Applies the replay's entries that are due, pausing once it's over.
+    fn advance_replay(&mut self) {
+        let Some(replay) = &mut self.replay else {
+            return;
+        };
+        if let Err(e) = replay.apply_due(&mut self.game) {
+            self.status = format!("Replay failed: {}", e);
+            self.replay = None;
+        } else if replay.next_stop(&self.game).is_none() {
+            self.status = "Replay finished".to_owned();
+            self.replay = None;
+            self.state.pause();
+        }
+    }
+
+    /// Discards all edits and runs by re-parsing the originally loaded pattern.
+    fn reload(&mut self) -> Result<(), GameError> {
+        let board = format::parse(&self.source)?;
+        self.state.stop();
+        self.record(Entry::Restart(board.clone()));
+        self.initial_board = board.clone();
+        self.game.restart(board);
+        self.drift = None;
+        self.edit_history.clear();
+        self.redo_stack.clear();
+        Ok(())
+    }
+
+    /// Maps a terminal position to the world point shown there, if it's within the board. With
+    /// half blocks this is the upper of the two world cells in the terminal cell.
+    fn screen_to_world(&self, column: u16, row: u16) -> Option<Point> {
+        let (rel_x, rel_y) = contains(self.board_area, column, row)?;
+        Some(self.viewport().screen_to_world(
+            self.board_area.width,
+            self.board_area.height,
+            rel_x,
+            rel_y,
+        ))
+    }
+
+    /// Live cells within the part of the world shown in the board area.
+    fn visible_board(&self) -> Board {
+        let (min, max) = self
+            .viewport()
+            .bounds(self.board_area.width, self.board_area.height);
+        self.game.board.extract(min, max)
+    }
+
+    /// The cells in view as RLE. When they came from a library pattern, its name, author and
+    /// comments are kept, noting that it was edited if it no longer matches.
+    fn view_rle(&self) -> String {
+        let board = self.visible_board();
+        let Some(name) = self.stamped_from else {
+            return format::rle::write(&board);
+        };
+        let source = patterns::source(name).expect("stamps come from the library");
+        let mut comments: Vec<_> = format::rle::comments(source)
+            .into_iter()
+            .map(str::to_owned)
+            .collect();
+        if patterns::get(name).map(|b| b.normalize()) != Some(board.normalize()) {
+            comments.push(format!("#C Edited from {} in the pattern library", name));
+        }
+        format::rle::write_with_comments(&board, &comments)
+    }
+
+    /// Labels in view as the screen area each is drawn in, starting just right of its cell and
+    /// clipped to the board.
+    fn visible_labels(&self) -> Vec<(Rect, &str)> {
+        let area = self.board_area;
+        self.labels
+            .iter()
+            .filter_map(|(at, text)| {
+                let (x, y) = self
+                    .viewport()
+                    .world_to_screen(area.width, area.height, *at)?;
+                let width = (area.width - x - 1)
+                    .min(u16::try_from(text.chars().count()).unwrap_or(u16::MAX));
+                (width > 0).then(|| {
+                    (
+                        Rect::new(area.x + x + 1, area.y + y, width, 1),
+                        text.as_str(),
+                    )
+                })
+            })
+            .collect()
+    }
+
+    /// Clears just the cells in view, leaving anything off screen.
+    fn clear_view(&mut self) {
+        let (min, max) = self
+            .viewport()
+            .bounds(self.board_area.width, self.board_area.height);
+        self.edit(|board| board.clear_region(min, max));
+        self.game
+            .dying
+            .retain(|p, _| !((min.x..=max.x).contains(&p.x) && (min.y..=max.y).contains(&p.y)));
+    }
+
+    fn viewport(&self) -> Viewport {
+        Viewport {
+            origin: self.origin,
+            zoom: self.zoom,
+            half_blocks: self.half_blocks,
+        }
+    }
+
+    /// The pending stamp placed at the world position under the mouse, snapped to the grid if
+    /// snapping.
+    fn stamp_preview(&self) -> Option<Board> {
+        let stamp = self.pending_stamp.as_ref()?;
+        let mut cursor = self.screen_to_world(self.mouse.0, self.mouse.1)?;
+        if let (true, Some(anchor), Some((min, max))) =
+            (self.snap, self.snap_anchor, stamp.bounding_box())
+        {
+            cursor = snap_to_grid(cursor, anchor, max - min + Point::new(1, 1));
+        }
+        Some(stamp.translate(cursor))
+    }
+
+    fn apply(&mut self, cx: &mut Context, action: Action) {
+        match action {
+            Action::Gen(generation) => match cx.subscribe() {
+                Some(sender) if generation > self.game.generation => {
+                    self.hold();
+                    self.jump = Some(Jump::spawn(
+                        self.game.clone(),
+                        generation,
+                        JUMP_POPULATION_CAP,
+                        self.density.clone(),
+                        sender,
+                    ));
+                }
+                _ => {
+                    self.hold();
+                    let density = &mut self.density;
+                    let jumped = self
+                        .game
+                        .jump_to_with(generation, JUMP_POPULATION_CAP, |board| {
+                            if let Some(density) = density {
+                                density.record(board);
+                            }
+                        });
+                    self.status = match jumped {
+                        Ok(()) => format!("Jumped to generation {}", generation),
+                        Err(e) => e.to_string(),
+                    };
+                }
+            },
+            Action::ExportCsv(path) => {
+                self.status = match std::fs::write(&path, self.game.population_csv()) {
+                    Ok(()) => format!("Wrote {}", path.display()),
+                    Err(e) => format!("Failed to write {}: {}", path.display(), e),
+                };
+            }
+            Action::ExportView(path) => {
+                let (min, max) = self
+                    .viewport()
+                    .bounds(self.board_area.width, self.board_area.height);
+                let contents =
+                    match headless::image(&path, &self.game.board, (min, max), &self.labels) {
+                        Ok(Some(image)) => image,
+                        Ok(None) => self.view_rle().into_bytes(),
+                        Err(e) => {
+                            self.status = format!("Failed to write {}: {}", path.display(), e);
+                            return;
+                        }
+                    };
+                self.status = match std::fs::write(&path, contents) {
+                    Ok(()) => format!("Wrote {}", path.display()),
+                    Err(e) => format!("Failed to write {}: {}", path.display(), e),
+                };
+            }
+            Action::Gallery(dir) => match cx.subscribe() {
+                Some(sender) => {
+                    self.status = format!("Scanning {}…", dir.display());
+                    Gallery::spawn_scan(dir, sender);
+                }
+                None => {
+                    let gallery = Gallery::scan(&dir).map_err(|e| e.to_string());
+                    self.scanned(&dir, gallery);
+                }
+            },
+            Action::Rule(rule) => {
+                self.game.rule = rule;
+                self.record(Entry::Action(Action::Rule(rule)));
+                self.status = format!("Rule set to {}", rule);
+            }
+            Action::Tile { rows, columns, gap } => match &self.pending_stamp {
+                Some(stamp)
+                    if stamp.population() as u64 * rows as u64 * columns as u64
+                        > TILE_POPULATION_CAP =>
+                {
+                    self.status = format!("Tiling {}×{} would make too many cells", rows, columns);
+                }
+                Some(stamp) => {
+                    self.pending_stamp = Some(stamp.tile(rows, columns, gap));
+                    self.status = format!("Tiled pattern {}×{}, click to stamp", rows, columns);
+                }
+                None => self.status = "Pick a pattern to tile first".to_owned(),
+            },
+            Action::Label { at, text } => {
+                let at = self.y_axis.display(at);
+                self.labels.retain(|(p, _)| *p != at);
+                self.status = if text.is_empty() {
+                    "Label removed".to_owned()
+                } else {
+                    self.labels.push((at, text));
+                    "Label added".to_owned()
+                };
+            }
+            Action::Region { from, to } => {
+                let (from, to) = (self.y_axis.display(from), self.y_axis.display(to));
+                let min = Point::new(from.x.min(to.x), from.y.min(to.y));
+                let max = Point::new(from.x.max(to.x), from.y.max(to.y));
+                let (missing, extra) = self.game.region_discrepancies(min, max);
+                self.status = match missing.len() + extra.len() {
+                    0 => "Region steps the same as the full board".to_owned(),
+                    n => format!("{} cells differ stepping the region alone", n),
+                };
+                self.discrepancies =
+                    Some((missing.into_iter().collect(), extra.into_iter().collect()));
+            }
+            Action::Step => {
+                self.state.pause();
+                self.step_now(cx);
+            }
+            Action::Freeze(generations) => {
+                let target = self.game.generation.saturating_add(generations);
+                self.apply(cx, Action::Gen(target));
+                if self.jump.is_some() {
+                    self.freezing = true;
+                } else if self.game.generation == target {
+                    self.status = self.frozen_status();
+                }
+            }
+            Action::Play => self.play(),
+            Action::Pause => {
+                self.state.pause();
+            }
+            Action::TimeLapse(0) => self.status = "Step at least 1 generation per tick".to_owned(),
+            Action::TimeLapse(generations) if generations > MAX_TIME_LAPSE => {
+                self.status = format!("Step at most {} generations per tick", MAX_TIME_LAPSE)
+            }
+            Action::TimeLapse(generations) => {
+                self.generations_per_tick = generations;
+                self.status = match generations {
+                    1 => "Time-lapse off".to_owned(),
+                    n => format!("Time-lapse at {} generations per tick", n),
+                };
+            }
+            Action::Cells(glyphs) => {
+                self.cell_glyphs = glyphs;
+                self.status = format!("Drawing cells with {}", glyphs.name);
+            }
+            Action::Until(target) => {
+                if target.reached(&self.game) {
+                    self.status = format!("Already at {}", target);
+                } else {
+                    self.pause_when = Some(target);
+                    self.play();
+                    self.status = format!("Running until {}", target);
+                }
+            }
+            Action::Load(path) => match cx.subscribe() {
+                Some(sender) => {
+                    self.status = format!("Loading {}…", path.display());
+                    // Previews the part of the pattern the view will be fitted to once it's loaded
+                    let (fit, area) = (self.fitter(), self.board_area);
+                    let view = move |min, max| fit(min, max).bounds(area.width, area.height);
+                    loader::spawn_load_previewed(path, Box::new(view), sender);
+                }
+                None => match loader::load(&path, format::parse) {
+                    Ok((source, board)) => self.loaded_file(&path, source, board),
+                    Err(e) => self.status = e,
+                },
+            },
+        }
+    }
+
+    /// Handles keys while the command input is open.
+    fn update_input(&mut self, cx: &mut Context, key: KeyEvent) {
+        let Some(input) = &mut self.input else {
+            return;
+        };
+        match key.code {
+            KeyCode::Char(c) => input.push(c),
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Esc => self.input = None,
+            KeyCode::Enter => {
+                let input = self.input.take().unwrap_or_default();
+                match input.parse() {
+                    Ok(action) => self.apply(cx, action),
+                    Err(e) => self.status = format!("{}", e),
+                }
+            }
+            _ => (),
+        }
+    }
+
+    /// Advances the game a generation, on the simulation worker if possible. Without an event
+    /// loop to deliver the result (e.g. in tests) the step happens immediately, as it does for
+    /// Generations rules since the worker only passes boards and not decaying cells.
+    fn request_step(&mut self, cx: &mut Context) {
+        if self.pending_step.is_some() {
+            // Still waiting on the last one
+            return;
+        }
+        if self.game.rule.states() > 2 || self.generations_per_tick > 1 {
+            self.step_now(cx);
+            return;
+        }
+        if self.simulation.is_none() {
+            self.simulation = cx.subscribe().map(Simulation::spawn);
+        }
+        match &self.simulation {
+            Some(simulation) => {
+                let board = Arc::new(self.game.board.clone());
+                simulation.request_step(self.game.generation, board.clone(), self.game.rule);
+                self.pending_step = Some(PendingStep {
+                    started: Instant::now(),
+                    board,
+                    rule: self.game.rule,
+                });
+            }
+            None => self.step_now(cx),
+        }
+    }
+
+    fn frozen_status(&self) -> String {
+        format!(
+            "Frozen at generation {}, add labels with :label then :export an .svg or .png",
+            self.game.generation
+        )
+    }
+
+    /// Steps `generations_per_tick` generations on this thread. A time-lapse stops early at the
+    /// `until` target or the next replay entry, so neither is skipped over, and pauses if the
+    /// population exceeds `JUMP_POPULATION_CAP`.
+    fn step_now(&mut self, cx: &mut Context) {
+        let start = Instant::now();
+        for _ in 0..self.generations_per_tick {
+            self.game.step();
+            self.record_density();
+            if self.generations_per_tick > 1 && self.game.board.population() > JUMP_POPULATION_CAP {
+                self.state.pause();
+                self.status = format!(
+                    "Time-lapse paused at generation {} with population {}",
+                    self.game.generation,
+                    self.game.board.population()
+                );
+                break;
+            }
+            let replay_due = self
+                .replay
+                .as_ref()
+                .and_then(|replay| replay.next_stop(&self.game))
+                == Some(self.game.generation);
+            if replay_due
+                || self
+                    .pause_when
+                    .is_some_and(|target| target.reached(&self.game))
+            {
+                break;
+            }
+        }
+        self.after_step(cx, start.elapsed());
+    }
+
+    fn set_mouse_capture(&mut self, cx: &mut Context, on: bool) {
+        self.mouse_capture = on;
+        cx.run(Command::SetMouseCapture(on));
+        self.status = if on {
+            "Mouse capture on".to_owned()
+        } else {
+            "Mouse capture off, select text to copy it then press any key".to_owned()
+        };
+    }
+
+    /// Changes the tick rate the game runs at, which adaptive mode then adjusts from.
+    fn set_tick(&mut self, cx: &mut Context, tick: Duration) {
+        self.game_tick = tick;
+        self.effective_tick = tick;
+        cx.run(Command::SetTickRate(tick));
+    }
+
+    /// Counts the generation just stepped to in the density map, if one is being accumulated.
+    fn record_density(&mut self) {
+        if let Some(density) = &mut self.density {
+            density.record(&self.game.board);
+        }
+    }
+
+    fn after_step(&mut self, cx: &mut Context, elapsed: Duration) {
+        self.discrepancies = None;
+        if self.adaptive {
+            let tick = adapt_tick_rate(self.effective_tick, elapsed, self.game_tick);
+            if tick != self.effective_tick {
+                self.effective_tick = tick;
+                cx.run(Command::SetTickRate(tick));
+            }
+        }
+        self.advance_replay();
+        self.drift = analysis::detect_drift(&self.game, MAX_DRIFT_PERIOD);
+        if let Some(target) = self.pause_when.filter(|target| target.reached(&self.game)) {
+            self.pause_when = None;
+            self.state.pause();
+            self.status = format!("Paused at generation {}, {}", self.game.generation, target);
+        }
+        if self.follow {
+            if let Some(center) = self.game.board.center_of_mass() {
+                self.origin = center;
+            }
+        }
+    }
+
+    /// Handles keys while scrubbing through history.
+    fn update_scrub(&mut self, key: KeyEvent) {
+        let Some(index) = self.scrub else {
+            return;
+        };
+        let len = self.game.history.len();
+        match key.code {
+            KeyCode::Left => self.scrub_to(Some(index.saturating_sub(1))),
+            KeyCode::Right => self.scrub_to(Some((index + 1).min(len))),
+            KeyCode::Esc => self.scrub_to(None),
+            KeyCode::Enter => {
+                let generation = self.game.generation - (len - index) as u32;
+                if let Err(e) = self.game.jump_to(generation, JUMP_POPULATION_CAP) {
+                    self.status = e.to_string();
+                }
+                self.scrub_to(None);
+            }
+            _ => (),
+        }
+    }
+
+    /// Moves the scrub position, rebuilding the board shown there if it's a past one.
+    fn scrub_to(&mut self, scrub: Option<usize>) {
+        self.scrub = scrub;
+        self.scrubbed = match scrub {
+            Some(i) if i < self.game.history.len() => self
+                .game
+                .past(self.game.history.len() - i)
+                .map(|board| (i, self.game.generation, board)),
+            _ => None,
+        };
+    }
+
+    /// Handles keys while a jump is running, which can only be cancelled.
+    fn update_jump(&mut self, cx: &mut Context, key: KeyEvent) {
+        let Some(jump) = &self.jump else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc => jump.cancel(),
+            KeyCode::Char('q') => cx.run(Command::Exit),
+            _ => (),
+        }
+    }
+
+    /// Handles keys while the rule preset menu is open.
+    fn update_rule_menu(&mut self, key: KeyEvent) {
+        let Some(index) = self.rule_menu else {
+            return;
+        };
+        match key.code {
+            KeyCode::Up => self.rule_menu = Some(index.saturating_sub(1)),
+            KeyCode::Down => self.rule_menu = Some((index + 1).min(rule::PRESETS.len() - 1)),
+            KeyCode::Esc => self.rule_menu = None,
+            KeyCode::Enter => {
+                let (name, rule) = rule::PRESETS[index];
+                self.game.rule = rule.parse().expect("presets are valid");
+                self.record(Entry::Action(Action::Rule(self.game.rule)));
+                self.status = format!("Rule set to {} ({})", name, rule);
+                self.rule_menu = None;
+            }
+            _ => (),
+        }
+    }
+
+    /// Opens a scanned gallery, unless it's empty or couldn't be read.
+    fn scanned(&mut self, dir: &Path, gallery: Result<Gallery, String>) {
+        match gallery {
+            Ok(gallery) if gallery.entries.is_empty() => {
+                self.status = format!("No patterns found: {}", gallery.summary());
+            }
+            Ok(gallery) => {
+                self.status = format!("{}, enter to load", gallery.summary());
+                self.gallery = Some(gallery);
+            }
+            Err(e) => self.status = format!("Failed to read {}: {}", dir.display(), e),
+        }
+    }
+
+    /// Handles keys while browsing a gallery: arrows move through it and enter loads the
+    /// highlighted pattern.
+    fn update_gallery(&mut self, cx: &mut Context, key: KeyEvent) {
+        let Some(gallery) = &mut self.gallery else {
+            return;
+        };
+        match key.code {
+            KeyCode::Up => gallery.select_previous(),
+            KeyCode::Down => gallery.select_next(),
+            KeyCode::Esc => self.gallery = None,
+            KeyCode::Enter => {
+                if let Some(entry) = gallery.selected() {
+                    let path = entry.path.clone();
+                    self.gallery = None;
+                    self.apply(cx, Action::Load(path));
+                }
+            }
+            _ => (),
+        }
+    }
+
+    /// The board being displayed, which is the initial board while comparing against it or a
+    /// past generation while scrubbing.
+    fn displayed_board(&self) -> Cow<'_, Board> {
+        if self.show_initial {
+            return Cow::Borrowed(&self.initial_board);
+        }
+        match self.scrub {
+            Some(i) if i < self.game.history.len() => match &self.scrubbed {
+                Some((index, generation, board))
+                    if (*index, *generation) == (i, self.game.generation) =>
+                {
+                    Cow::Borrowed(board)
+                }
+                _ => Cow::Owned(
+                    self.game
+                        .past(self.game.history.len() - i)
+                        .unwrap_or_default(),
+                ),
+            },
+            _ => Cow::Borrowed(&self.game.board),
+        }
+    }
+
+    /// The generation of `displayed_board`.
+    fn displayed_generation(&self) -> u32 {
+        if self.show_initial {
+            return 0;
+        }
+        match self.scrub {
+            Some(i) if i < self.game.history.len() => {
+                self.game.generation - (self.game.history.len() - i) as u32
+            }
+            _ => self.game.generation,
+        }
+    }
+
+    /// Cells added and removed going from the previous generation to the displayed one.
+    fn displayed_diff(&self) -> Option<(Board, Board)> {
+        if self.show_initial {
+            return None;
+        }
+        let index = self.scrub.unwrap_or(self.game.history.len());
+        let diff = self.game.history.get(index.checked_sub(1)?)?;
+        Some((
+            diff.birthed.iter().copied().collect(),
+            diff.killed.iter().copied().collect(),
+        ))
+    }
+
+    /// How often the queried cell was alive over the generations in history, for the info panel.
+    fn query_report(&self) -> Option<String> {
+        let pos = self.queried?;
+        let history = self.game.cell_history(&pos);
+        let shown = self.y_axis.display(pos);
+        Some(match history.alive {
+            0 => format!("cell = ({}, {})\nnever alive in history", shown.x, shown.y),
+            alive => format!(
+                "cell = ({}, {})\nalive in {} of {} gens\nage = {}",
+                shown.x, shown.y, alive, history.generations, history.age
+            ),
+        })
+    }
+
+    /// Where cells were alive recently, when trails are on and the current generation is shown.
+    fn displayed_trail(&self) -> Option<HashMap<Point, u8>> {
+        (self.show_trail && self.scrub.is_none() && !self.show_initial)
+            .then(|| self.game.trail(TRAIL_LENGTH))
+    }
+
+    /// Runs the game, remembering the board it starts from when starting afresh so `r` can
+    /// return to it.
+    fn play(&mut self) {
+        if self.state.play() {
+            self.initial_board = self.game.board.clone();
+        }
+    }
+
+    /// Pauses, first remembering the board to restart from if stopped, for commands that
+    /// advance the game themselves.
+    fn hold(&mut self) {
+        self.play();
+        self.state.pause();
+    }
+
+    /// Moves the keyboard cursor, starting it at the center of the view if it's hidden.
+    fn move_cursor(&mut self, dx: i64, dy: i64) {
+        let cursor = self.cursor.get_or_insert(self.origin);
+        cursor.dx(dx);
+        cursor.dy(dy);
+    }
+
+    fn select_next_pattern(&mut self) {
+        let (name, s) = patterns::LIBRARY[self.library_index];
+        self.pending_stamp = format::parse(s).ok().map(|b| b.normalize());
+        self.stamp_source = Some(name);
+        self.library_index = (self.library_index + 1) % patterns::LIBRARY.len();
+    }
 }
 
 #[derive(Debug, Copy, Clone, Ord, Eq, PartialEq, PartialOrd)]
@@ -101,6 +1173,79 @@ enum ComponentId {
     Board,
 }
 
+/// Where each part of the UI goes in the terminal. Shared by `update` and `view` so clicks are
+/// mapped against the same board area that's drawn, even before the first render.
+#[derive(Debug, PartialEq, Eq)]
+struct AppLayout {
+    status: Rect,
+    /// Sections of the info panel, from the top or the left when it's a bottom bar
+    info: Vec<Rect>,
+    tick_slider: Rect,
+    /// Top and left coordinate rulers, when shown
+    rulers: Option<(Rect, Rect)>,
+    board: Rect,
+}
+
+/// Columns taken by the ruler left of the board, enough for a sign, five digits and the tick
+const RULER_WIDTH: u16 = 7;
+
+impl AppLayout {
+    /// Lays out the info panel as a column left of the board, or as a bar below it with
+    /// `info_bottom` so the board gets the full width of short terminals.
+    fn new(area: Rect, rulers: bool, info_bottom: bool) -> Self {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(1)])
+            .split(area);
+        let status = chunks[1];
+
+        let (info, mut board) = if info_bottom {
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(0), Constraint::Length(4)])
+                .split(chunks[0]);
+            (chunks[1], chunks[0])
+        } else {
+            let chunks = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Length(25), Constraint::Min(0)])
+                .split(chunks[0]);
+            (chunks[0], chunks[1])
+        };
+        let rulers = (rulers && board.width > RULER_WIDTH && board.height > 1).then(|| {
+            let top = Rect::new(board.x + RULER_WIDTH, board.y, board.width - RULER_WIDTH, 1);
+            let left = Rect::new(board.x, board.y + 1, RULER_WIDTH, board.height - 1);
+            board = Rect::new(top.x, left.y, top.width, left.height);
+            (top, left)
+        });
+
+        let info = if info_bottom {
+            Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Ratio(1, 7); 7])
+                .split(info)
+        } else {
+            Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Length(4); 7])
+                .split(info)
+        };
+        // Below the tick rate and whether it's adaptive
+        let tick_slider = Rect {
+            y: info[1].y + 2.min(info[1].height),
+            height: info[1].height.saturating_sub(2).min(1),
+            ..info[1]
+        };
+        AppLayout {
+            status,
+            info,
+            tick_slider,
+            rulers,
+            board,
+        }
+    }
+}
+
 fn contains(rect: Rect, x: u16, y: u16) -> Option<(u16, u16)> {
     if x >= rect.x && x < rect.x + rect.width && y >= rect.y && y < rect.y + rect.height {
         return Some((x - rect.x, y - rect.y));
@@ -110,79 +1255,446 @@ fn contains(rect: Rect, x: u16, y: u16) -> Option<(u16, u16)> {
 
 impl Model for App {
     fn update(&mut self, cx: &mut Context, event: Event) {
+        if let (false, Event::Key(_)) = (self.mouse_capture, &event) {
+            self.set_mouse_capture(cx, true);
+            return;
+        }
+        if let (Some(_), Event::Key(key)) = (&self.jump, &event) {
+            self.update_jump(cx, *key);
+            return;
+        }
+        if let (Some(_), Event::Mouse(_)) = (&self.jump, &event) {
+            return;
+        }
+        // Finishing the jump replaces the game, so anything done to it meanwhile would be lost
+        if self.jump.is_some() {
+            match &event {
+                Event::Action(action) => {
+                    self.status = format!("Ignored `{}` while jumping", action);
+                    return;
+                }
+                Event::BoardLoaded { path, .. } => {
+                    self.status = format!("Ignored loading {} while jumping", path.display());
+                    return;
+                }
+                _ => (),
+            }
+        }
+        if let (Some(_), Event::Key(key)) = (&self.input, &event) {
+            self.update_input(cx, *key);
+            return;
+        }
+        if let (Some(_), Event::Key(key)) = (self.scrub, &event) {
+            self.update_scrub(*key);
+            return;
+        }
+        if let (Some(_), Event::Key(key)) = (self.rule_menu, &event) {
+            self.update_rule_menu(*key);
+            return;
+        }
+        if let (Some(_), Event::Key(key)) = (&self.gallery, &event) {
+            self.update_gallery(cx, *key);
+            return;
+        }
+        let clicked = match event {
+            Event::Mouse(mouse) => self.track_press(mouse),
+            _ => false,
+        };
+
         // TODO: this is unreadable
         match event {
+            Event::Key(KeyEvent {
+                code: KeyCode::Char(':'),
+                ..
+            }) => self.input = Some(String::new()),
             Event::Key(KeyEvent {
                 code: KeyCode::Char(' '),
                 ..
-            }) => {
-                if matches!(self.state, AppState::Stopped) {
-                    self.initial_board = self.game.board.clone();
-                }
-                self.state.toggle();
-            }
+            }) => match self.state {
+                AppState::Running => self.state.pause(),
+                _ => self.play(),
+            },
             Event::Key(KeyEvent {
                 code: KeyCode::Char('r'),
                 modifiers: KeyModifiers::NONE,
                 ..
-            }) => {
-                self.state = AppState::Stopped;
-                self.game.generation = 0;
-                self.game.board = self.initial_board.clone();
+            }) if self.pending_stamp.is_some() => {
+                self.pending_stamp = self
+                    .pending_stamp
+                    .as_ref()
+                    .map(|b| b.rotate_cw().normalize());
             }
             Event::Key(KeyEvent {
-                code: KeyCode::Char('q'),
+                code: KeyCode::Char('x'),
                 modifiers: KeyModifiers::NONE,
                 ..
-            }) => cx.run(Command::Exit),
-            Event::Key(KeyEvent {
+            }) => {
+                self.pending_stamp = self
+                    .pending_stamp
+                    .as_ref()
+                    .map(|b| b.flip_horizontal().normalize());
+            }
+            Event::Key(KeyEvent {
+                code: KeyCode::Char('p'),
+                modifiers: KeyModifiers::NONE,
+                ..
+            }) => self.select_next_pattern(),
+            Event::Key(KeyEvent {
+                code: KeyCode::Char('f'),
+                modifiers: KeyModifiers::NONE,
+                ..
+            }) => self.follow = !self.follow,
+            Event::Key(KeyEvent {
+                code: KeyCode::Char('d'),
+                modifiers: KeyModifiers::NONE,
+                ..
+            }) => self.show_diff = !self.show_diff,
+            Event::Key(KeyEvent {
+                code: KeyCode::Char('i'),
+                modifiers: KeyModifiers::NONE,
+                ..
+            }) => self.show_initial = !self.show_initial,
+            Event::Key(KeyEvent {
+                code: KeyCode::Char('w'),
+                modifiers: KeyModifiers::NONE,
+                ..
+            }) => self.show_trail = !self.show_trail,
+            Event::Key(KeyEvent {
+                code: KeyCode::Char('g'),
+                modifiers: KeyModifiers::NONE,
+                ..
+            }) => {
+                self.snap = !self.snap;
+                self.snap_anchor = None;
+                self.status = format!("Snap to grid {}", if self.snap { "on" } else { "off" });
+            }
+            Event::Key(KeyEvent {
+                code: KeyCode::Char('b'),
+                modifiers: KeyModifiers::NONE,
+                ..
+            }) => self.half_blocks = !self.half_blocks,
+            Event::Key(KeyEvent {
+                code: KeyCode::Char('t'),
+                modifiers: KeyModifiers::NONE,
+                ..
+            }) => {
+                self.state.pause();
+                self.scrub_to(Some(self.game.history.len()));
+            }
+            Event::Key(KeyEvent {
+                code: KeyCode::Char('m'),
+                modifiers: KeyModifiers::NONE,
+                ..
+            }) => {
+                let current = self.game.rule.to_string();
+                self.rule_menu = Some(
+                    rule::PRESETS
+                        .iter()
+                        .position(|(_, rule)| *rule == current)
+                        .unwrap_or(0),
+                );
+            }
+            Event::Key(KeyEvent {
+                code: KeyCode::Char('a'),
+                modifiers: KeyModifiers::NONE,
+                ..
+            }) => {
+                self.adaptive = !self.adaptive;
+                if !self.adaptive && self.effective_tick != self.game_tick {
+                    self.effective_tick = self.game_tick;
+                    cx.run(Command::SetTickRate(self.game_tick));
+                }
+            }
+            Event::Key(KeyEvent {
+                code: KeyCode::Esc, ..
+            }) => {
+                self.pending_stamp = None;
+                self.discrepancies = None;
+                self.queried = None;
+            }
+            Event::Key(KeyEvent {
+                code: KeyCode::Char('r'),
+                modifiers: KeyModifiers::NONE,
+                ..
+            }) => {
+                self.state.stop();
+                self.record(Entry::Restart(self.initial_board.clone()));
+                self.game.restart(self.initial_board.clone());
+                self.drift = None;
+            }
+            Event::Key(KeyEvent {
+                code: KeyCode::Char('T'),
+                ..
+            }) => {
+                self.theme = (self.theme + 1) % theme::THEMES.len();
+                self.status = format!("Theme set to {}", theme::THEMES[self.theme].name);
+            }
+            Event::Key(KeyEvent {
+                code: KeyCode::Char('B'),
+                ..
+            }) => {
+                self.checkerboard = !self.checkerboard;
+                self.status = match self.checkerboard {
+                    true => "Checkerboard on".to_owned(),
+                    false => "Checkerboard off".to_owned(),
+                };
+            }
+            Event::Key(KeyEvent {
+                code: KeyCode::Char('H'),
+                ..
+            }) => {
+                self.hud = !self.hud;
+                self.status = match self.hud {
+                    true => "HUD on".to_owned(),
+                    false => "HUD off".to_owned(),
+                };
+            }
+            Event::Key(KeyEvent {
+                code: KeyCode::Char('M'),
+                ..
+            }) => self.set_mouse_capture(cx, false),
+            Event::Key(KeyEvent {
+                code: KeyCode::Char('R'),
+                ..
+            }) => {
+                if let Err(e) = self.reload() {
+                    self.status = e.to_string();
+                }
+            }
+            Event::Key(KeyEvent {
+                code: KeyCode::Char('q'),
+                modifiers: KeyModifiers::NONE,
+                ..
+            }) => cx.run(Command::Exit),
+            Event::Key(KeyEvent {
                 code: KeyCode::Char('h'),
                 modifiers: KeyModifiers::NONE,
                 ..
-            }) => self.origin.dx(-1),
+            }) => self.origin.dx(-(self.zoom as i64)),
             Event::Key(KeyEvent {
                 code: KeyCode::Char('j'),
                 modifiers: KeyModifiers::NONE,
                 ..
-            }) => self.origin.dy(-1),
+            }) => self.origin.dy(-(self.zoom as i64)),
             Event::Key(KeyEvent {
                 code: KeyCode::Char('k'),
                 modifiers: KeyModifiers::NONE,
                 ..
-            }) => self.origin.dy(1),
+            }) => self.origin.dy(self.zoom as i64),
             Event::Key(KeyEvent {
                 code: KeyCode::Char('l'),
                 modifiers: KeyModifiers::NONE,
                 ..
-            }) => self.origin.dx(1),
+            }) => self.origin.dx(self.zoom as i64),
+            Event::Key(KeyEvent {
+                code: KeyCode::Char('-'),
+                ..
+            }) => {
+                self.zoom = self
+                    .zoom
+                    .saturating_mul(2)
+                    .clamp(self.min_zoom, self.max_zoom)
+            }
+            Event::Key(KeyEvent {
+                code: KeyCode::Char('+' | '='),
+                ..
+            }) => self.zoom = (self.zoom / 2).clamp(self.min_zoom, self.max_zoom),
+            Event::Key(KeyEvent {
+                code: KeyCode::Left,
+                ..
+            }) => self.move_cursor(-1, 0),
+            Event::Key(KeyEvent {
+                code: KeyCode::Right,
+                ..
+            }) => self.move_cursor(1, 0),
+            Event::Key(KeyEvent {
+                code: KeyCode::Up, ..
+            }) => self.move_cursor(0, 1),
+            Event::Key(KeyEvent {
+                code: KeyCode::Down,
+                ..
+            }) => self.move_cursor(0, -1),
+            Event::Key(KeyEvent {
+                code: KeyCode::Enter,
+                ..
+            }) => {
+                if let (Some(cursor), true) = (self.cursor, self.can_edit()) {
+                    self.edit(|board| board.toggle_cell(&cursor));
+                }
+            }
             Event::Key(KeyEvent {
                 code: KeyCode::Char('c'),
                 modifiers: KeyModifiers::NONE,
                 ..
+            }) if self.can_edit() => self.clear(),
+            Event::Key(KeyEvent {
+                code: KeyCode::Char('C'),
+                ..
+            }) if self.can_edit() => self.clear_view(),
+            Event::Key(KeyEvent {
+                code: KeyCode::Char('u'),
+                modifiers: KeyModifiers::NONE,
+                ..
+            }) => self.rulers = !self.rulers,
+            Event::Key(KeyEvent {
+                code: KeyCode::Char('e'),
+                modifiers: KeyModifiers::NONE,
+                ..
             }) => {
-                if matches!(self.state, AppState::Stopped) {
-                    self.game.board.clear();
-                }
+                self.live_edit = !self.live_edit;
+                self.status = format!(
+                    "Editing while running {}",
+                    if self.live_edit { "on" } else { "off" }
+                );
             }
+            Event::Key(KeyEvent {
+                code: KeyCode::Char('z'),
+                modifiers: KeyModifiers::CONTROL,
+                ..
+            }) if self.can_edit() => self.undo(),
+            Event::Key(KeyEvent {
+                code: KeyCode::Char('y'),
+                modifiers: KeyModifiers::CONTROL,
+                ..
+            }) if self.can_edit() => self.redo(),
             Event::Mouse(MouseEvent {
                 kind: MouseEventKind::Up(MouseButton::Left),
                 column,
                 row,
                 modifiers: KeyModifiers::NONE,
-            }) => {
-                if matches!(self.state, AppState::Stopped) {
-                    if let Some((rel_x, rel_y)) = contains(self.board_area, column, row) {
-                        let board_x =
-                            rel_x as i64 - self.board_area.width as i64 / 2 + self.origin.x;
-                        let board_y =
-                            rel_y as i64 - self.board_area.height as i64 / 2 + self.origin.y;
-                        self.game.board.toggle_cell(&Point::new(board_x, board_y));
+            }) if clicked => {
+                self.mouse = (column, row);
+                if self.can_edit() {
+                    if let Some(stamp) = self.stamp_preview() {
+                        self.edit(|board| board.union_with(&stamp));
+                        self.stamped_from = self.stamp_source;
+                        if self.snap {
+                            // Keep the stamp for tiling more copies
+                            self.snap_anchor = self
+                                .snap_anchor
+                                .or(stamp.bounding_box().map(|(min, _)| min));
+                        } else {
+                            self.pending_stamp = None;
+                        }
+                    } else if let Some(pos) = self.screen_to_world(column, row) {
+                        self.edit(|board| board.birth_cell(&pos));
                     }
+                } else if matches!(self.state, AppState::Paused) {
+                    self.queried = self.screen_to_world(column, row);
+                }
+            }
+            Event::Mouse(MouseEvent {
+                kind: MouseEventKind::Up(button @ (MouseButton::Right | MouseButton::Middle)),
+                column,
+                row,
+                modifiers: KeyModifiers::NONE,
+            }) if clicked => {
+                self.mouse = (column, row);
+                if let (Some(pos), true) = (self.screen_to_world(column, row), self.can_edit()) {
+                    match button {
+                        MouseButton::Right => self.edit(|board| board.kill_cell(&pos)),
+                        _ => self.edit(|board| board.toggle_cell(&pos)),
+                    }
+                }
+            }
+            Event::Mouse(MouseEvent {
+                kind: MouseEventKind::Moved,
+                column,
+                row,
+                ..
+            }) => self.mouse = (column, row),
+            Event::Mouse(MouseEvent {
+                kind:
+                    MouseEventKind::Down(MouseButton::Left) | MouseEventKind::Drag(MouseButton::Left),
+                column,
+                row,
+                ..
+            }) => {
+                self.mouse = (column, row);
+                if let Some((x, _)) = contains(self.tick_slider, column, row) {
+                    self.set_tick(
+                        cx,
+                        ratio_to_tick(Slider::ratio_at(self.tick_slider.width, x)),
+                    );
+                }
+            }
+            Event::Resize { width, height } => {
+                let layout = AppLayout::new(
+                    Rect::new(0, 0, width, height),
+                    self.rulers,
+                    self.info_bottom,
+                );
+                // The first size is the first chance to fit the starting pattern
+                let first = self.board_area.area() == 0;
+                self.board_area = layout.board;
+                self.tick_slider = layout.tick_slider;
+                if first {
+                    self.fit_view();
                 }
             }
             Event::Tick => {
                 if matches!(self.state, AppState::Running) {
-                    self.game.step();
+                    self.request_step(cx);
+                }
+            }
+            Event::BoardPreview { board, bounds } => {
+                self.fit_view_to(bounds.0, bounds.1);
+                self.load_preview = Some(Arc::unwrap_or_clone(board));
+            }
+            Event::BoardLoaded {
+                path,
+                source,
+                board,
+            } => {
+                self.load_preview = None;
+                self.loaded_file(&path, source.to_string(), Arc::unwrap_or_clone(board));
+            }
+            Event::Action(action) => self.apply(cx, action),
+            Event::GalleryScanned { dir, gallery } => self.scanned(&dir, gallery),
+            Event::LoadFailed { error, .. } => {
+                self.load_preview = None;
+                self.status = error;
+            }
+            Event::Progress { done, total } => {
+                if let Some(jump) = &mut self.jump {
+                    jump.progress = (done, total);
+                }
+            }
+            Event::Jumped {
+                game,
+                outcome,
+                density,
+            } => {
+                let Some(jump) = self.jump.take() else {
+                    return;
+                };
+                let freezing = std::mem::take(&mut self.freezing);
+                self.game = Arc::unwrap_or_clone(game);
+                if density.is_some() {
+                    self.density = density;
+                }
+                self.drift = analysis::detect_drift(&self.game, MAX_DRIFT_PERIOD);
+                self.status = match outcome {
+                    JumpOutcome::Reached if freezing => self.frozen_status(),
+                    JumpOutcome::Reached => format!("Jumped to generation {}", jump.target),
+                    JumpOutcome::Cancelled => {
+                        format!("Jump cancelled at generation {}", self.game.generation)
+                    }
+                    JumpOutcome::Failed(e) => e,
+                };
+            }
+            Event::Board { generation, board } => {
+                let Some(pending) = self.pending_step.take() else {
+                    return;
+                };
+                // The game may have been reset or edited since the step was requested
+                if generation == self.game.generation + 1
+                    && pending.rule == self.game.rule
+                    && *pending.board == self.game.board
+                {
+                    self.game.advance(Arc::unwrap_or_clone(board));
+                    self.record_density();
+                    self.after_step(cx, pending.started.elapsed());
                 }
             }
             _ => (),
@@ -190,40 +1702,223 @@ impl Model for App {
     }
 
     fn view(&mut self, _cx: &mut Context, f: &mut terminal::Frame) {
-        let board = BoardWidget::new(&self.game.board).pan_to(self.origin);
-        let generation =
-            Paragraph::new(Text::from(format!("generation = {}", self.game.generation)));
-        let tick_rate = Paragraph::new(Text::from(format!("tick rate = {:?}", self.game_tick)));
-        let state = Paragraph::new(Text::from(format!("state = {:?}", self.state)));
+        let drift = match self.drift {
+            Some(drift) => format!("\ndrift = {}", drift),
+            None => String::new(),
+        };
+        let scrub = match self.scrub {
+            Some(i) => format!(
+                "\nscrubbing = {}",
+                self.game.generation as usize + i - self.game.history.len()
+            ),
+            None => String::new(),
+        };
+        let lapse = match self.generations_per_tick {
+            1 => String::new(),
+            n => format!(" (×{})", n),
+        };
+        let generation = Paragraph::new(Text::from(format!(
+            "generation = {}{}{}{}",
+            self.game.generation, lapse, scrub, drift
+        )));
+        // The rate ticks are actually scheduled at, see `set_tick` and `after_step`
+        let tick_rate = Paragraph::new(Text::from(match self.adaptive {
+            true => format!("tick rate = {:?}\n(adaptive)", self.effective_tick),
+            false => format!("tick rate = {:?}", self.effective_tick),
+        }));
+        let state = Paragraph::new(Text::from(format!(
+            "state = {:?}\nrule = {}{}{}{}{}",
+            self.state,
+            self.game.rule,
+            if self.follow { "\nfollowing" } else { "" },
+            if self.show_diff { "\nshowing diff" } else { "" },
+            if self.show_trail {
+                "\nshowing trails"
+            } else {
+                ""
+            },
+            if self.show_initial {
+                "\nshowing initial board"
+            } else {
+                ""
+            }
+        )));
 
-        let chunks = Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints([Constraint::Length(25), Constraint::Min(0)])
-            .split(f.size());
-        let info_panel_area = chunks[0];
-        let board_area = chunks[1];
+        let AppLayout {
+            status: status_area,
+            info: chunks,
+            tick_slider: tick_slider_area,
+            rulers,
+            board: board_area,
+        } = AppLayout::new(f.size(), self.rulers, self.info_bottom);
+        self.board_area = board_area;
+        self.tick_slider = tick_slider_area;
 
-        let chunks = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints([Constraint::Length(4); 7])
-            .split(info_panel_area);
+        let status = match &self.input {
+            Some(input) => format!(":{}", input),
+            None => self.status.clone(),
+        };
+
+        let hover = match self.screen_to_world(self.mouse.0, self.mouse.1) {
+            Some(pos) => {
+                let cell = self.game.board.inspect(&pos);
+                let shown = self.y_axis.display(pos);
+                format!(
+                    "hover = ({}, {})\nneighbors = {}\nnext = {}",
+                    shown.x,
+                    shown.y,
+                    cell.live_neighbors,
+                    self.game.rule.outcome(cell.alive, cell.live_neighbors)
+                )
+            }
+            None => String::new(),
+        };
 
         let generation_area = chunks[0];
         let tick_rate_area = chunks[1];
         let state_area = chunks[2];
+        let query_area = chunks[3];
         let origin_area = chunks[4];
-        // let click_area = chunks[5];
+        let hover_area = chunks[5];
         let mouse_area = chunks[6];
-        self.board_area = board_area;
+
+        let preview = self.stamp_preview();
+        let diff = self
+            .discrepancies
+            .clone()
+            .or_else(|| self.show_diff.then(|| self.displayed_diff()).flatten());
+        let trail = self.displayed_trail();
+        let displayed = self.displayed_board();
+        let (shown, shown_generation) = match &self.load_preview {
+            Some(preview) => (preview, 0),
+            None => (&*displayed, self.displayed_generation()),
+        };
+        let hud = format!("gen {} pop {}", shown_generation, shown.population());
+        let mut board = BoardWidget::new(shown)
+            .viewport(self.viewport())
+            .theme(theme::THEMES[self.theme])
+            .glyphs(self.cell_glyphs)
+            .ascii(self.ascii)
+            .checkerboard(self.checkerboard);
+        if let Some((added, removed)) = &diff {
+            board = board.diff(added, removed);
+        }
+        if let Some(trail) = &trail {
+            board = board.trail(trail, TRAIL_LENGTH);
+        }
+        if self.scrub.is_none() && !self.show_initial && !self.game.dying.is_empty() {
+            board = board.decaying(&self.game.dying, self.game.rule.states());
+        }
+        if let Some(preview) = &preview {
+            board = board.preview(preview);
+        }
+        if let Some(cursor) = self.cursor {
+            board = board.cursor(cursor);
+        }
+        if self.hud {
+            board = board.hud(&hud);
+        }
 
         f.render_widget(generation, generation_area);
         f.render_widget(tick_rate, tick_rate_area);
+        f.render_widget(
+            Slider::new(tick_to_ratio(self.effective_tick)).ascii(self.ascii),
+            tick_slider_area,
+        );
         f.render_widget(state, state_area);
         f.render_widget(board, board_area);
+        let label_style = Style::default().fg(theme::THEMES[self.theme].cursor);
+        for (area, text) in self.visible_labels() {
+            f.render_widget(Paragraph::new(text).style(label_style), area);
+        }
+        if let Some((top, left)) = rulers {
+            let style = Style::default().fg(theme::THEMES[self.theme].grid);
+            for (axis, area) in [(Axis::Horizontal, top), (Axis::Vertical, left)] {
+                let ruler = Ruler::new(self.viewport(), board_area, axis)
+                    .y_axis(self.y_axis)
+                    .style(style)
+                    .ascii(self.ascii);
+                f.render_widget(ruler, area);
+            }
+        }
         f.render_widget(
-            Paragraph::new(Text::from(format!("origin = \n{:?}", self.origin))),
+            Paragraph::new(Text::from(format!(
+                "origin = \n{:?}\nzoom = 1:{}",
+                self.y_axis.display(self.origin),
+                self.zoom
+            ))),
             origin_area,
         );
+        f.render_widget(Paragraph::new(Text::from(hover)), hover_area);
+        if let Some(report) = self.query_report() {
+            f.render_widget(Paragraph::new(Text::from(report)), query_area);
+        }
+        match &self.jump {
+            Some(jump) => {
+                let (done, total) = jump.progress;
+                let gauge = Gauge::default()
+                    .ratio(done as f64 / total.max(1) as f64)
+                    .label(format!(
+                        "Jumping to generation {}: {}/{} (Esc to cancel)",
+                        jump.target, done, total
+                    ));
+                f.render_widget(gauge, status_area);
+            }
+            None => f.render_widget(Paragraph::new(Text::from(status)), status_area),
+        }
+        if let Some(index) = self.rule_menu {
+            let items: Vec<_> = rule::PRESETS
+                .iter()
+                .map(|(name, rule)| ListItem::new(format!("{:<20}{}", name, rule)))
+                .collect();
+            let width = 36.min(board_area.width);
+            let height = u16::try_from(items.len())
+                .unwrap_or(u16::MAX)
+                .saturating_add(2)
+                .min(board_area.height);
+            let area = Rect::new(
+                board_area.x + (board_area.width - width) / 2,
+                board_area.y + (board_area.height - height) / 2,
+                width,
+                height,
+            );
+            let list = List::new(items)
+                .block(Block::default().title("Rules").borders(Borders::ALL))
+                .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+            let mut state = ListState::default();
+            state.select(Some(index));
+            f.render_widget(Clear, area);
+            f.render_stateful_widget(list, area, &mut state);
+        }
+        if let Some(gallery) = &self.gallery {
+            let items: Vec<_> = gallery
+                .entries
+                .iter()
+                .map(|entry| ListItem::new(entry.name.clone()))
+                .collect();
+            let width = 40.min(board_area.width);
+            let height = u16::try_from(items.len())
+                .unwrap_or(u16::MAX)
+                .saturating_add(2)
+                .min(board_area.height);
+            let area = Rect::new(
+                board_area.x + (board_area.width - width) / 2,
+                board_area.y + (board_area.height - height) / 2,
+                width,
+                height,
+            );
+            let list = List::new(items)
+                .block(
+                    Block::default()
+                        .title(format!("Gallery: {}", gallery.dir.display()))
+                        .borders(Borders::ALL),
+                )
+                .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+            let mut state = ListState::default();
+            state.select(Some(gallery.selected));
+            f.render_widget(Clear, area);
+            f.render_stateful_widget(list, area, &mut state);
+        }
         f.render_widget(
             Paragraph::new(Text::from(format!("mouse = {:?}", self.mouse))),
             mouse_area,
@@ -231,8 +1926,1623 @@ impl Model for App {
     }
 }
 
+/// Names a built-in pattern or a pattern file to start with instead of the queen bee
+const DEFAULT_PATTERN_VAR: &str = "GOL_DEFAULT_PATTERN";
+
+/// The pattern to start with when none is piped in: the one `GOL_DEFAULT_PATTERN` names, or the
+/// queen bee if it's unset. See `default_pattern_from`.
+fn default_pattern() -> Result<String, String> {
+    default_pattern_from(std::env::var_os(DEFAULT_PATTERN_VAR))
+}
+
+/// The pattern `name` names, looked up in the library first and then as a file, or the queen bee
+/// if there's no name.
+fn default_pattern_from(name: Option<OsString>) -> Result<String, String> {
+    let Some(name) = name.filter(|name| !name.is_empty()) else {
+        return Ok(patterns::QUEEN_BEE.to_owned());
+    };
+    match name.to_str().and_then(patterns::source) {
+        Some(s) => Ok(s.to_owned()),
+        None => std::fs::read_to_string(&name).map_err(|e| {
+            format!(
+                "{} is neither a built-in pattern nor a readable file: {}",
+                name.to_string_lossy(),
+                e
+            )
+        }),
+    }
+}
+
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let app = App::new(Duration::from_millis(75)).board(QUEEN_BEE_BOARD)?;
-    Program::new().run(app)?;
+    let args = cli::Args::parse(std::env::args().skip(1))?;
+    if let Some(path) = &args.validate {
+        match headless::validate(path) {
+            Ok(report) => print!("{}", report),
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
+        return Ok(());
+    }
+    if let Some(path) = &args.hash {
+        match headless::hash(path, args.generations) {
+            Ok(hash) => print!("{}", hash),
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
+        return Ok(());
+    }
+    if let Some((a, b)) = &args.compare {
+        match headless::compare(a, b, args.generations) {
+            Ok(comparison) => {
+                println!("{}", comparison);
+                if matches!(comparison, headless::Comparison::Differ { .. }) {
+                    std::process::exit(1);
+                }
+            }
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
+        return Ok(());
+    }
+    let pattern = if args.stdin {
+        let mut s = String::new();
+        std::io::stdin().read_to_string(&mut s)?;
+        s
+    } else {
+        match default_pattern() {
+            Ok(pattern) => pattern,
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
+    };
+
+    let session = match args.replay.as_deref().map(Session::load).transpose() {
+        Ok(session) => session,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+    if let (Some(session), true) = (&session, args.print) {
+        print!("{}", args.output.write(&session.replay()?.board));
+        return Ok(());
+    }
+    if let Some(path) = &args.capture {
+        let labels: Vec<_> = args
+            .labels
+            .iter()
+            .map(|(at, text)| (args.y_axis.display(*at), text.clone()))
+            .collect();
+        if let Err(e) = headless::capture(&pattern, args.generations, &labels, path) {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+    if args.print {
+        let mut density = args
+            .density
+            .as_ref()
+            .map(|_| analysis::DensityMap::default());
+        print!(
+            "{}",
+            headless::run_counting(&pattern, args.generations, args.output, density.as_mut())?
+        );
+        if let (Some(path), Some(density)) = (&args.density, &density) {
+            headless::write_density(path, density)?;
+        }
+        return Ok(());
+    }
+
+    let mut app = match App::new(Duration::from_millis(75))
+        .board(&pattern)?
+        .zoom_limits(args.min_zoom, args.max_zoom)
+    {
+        Ok(app) => app,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+    if let Some(session) = &session {
+        app = app.replaying(session);
+    }
+    if args.record.is_some() {
+        app.recording = Some(Session::new(&app.game));
+    }
+    if args.density.is_some() {
+        app.density = Some(Default::default());
+    }
+    app.ascii = args.ascii || !terminal::supports_unicode();
+    app.y_axis = args.y_axis;
+    app.info_bottom = args.info_bottom;
+    let program = Program::new()
+        .tick(app.effective_tick)
+        .max_fps(args.max_fps);
+    if let Some(addr) = &args.listen {
+        if let Err(e) = remote::serve(addr, program.sender()) {
+            eprintln!("Failed to listen on {}: {}", addr, e);
+            std::process::exit(1);
+        }
+    }
+    if let Err(e) = program.run(&mut app) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+    if let (Some(path), Some(mut session)) = (args.record, app.recording) {
+        session.end = app.game.generation;
+        std::fs::write(path, session.to_string())?;
+    }
+    if let (Some(path), Some(density)) = (&args.density, &app.density) {
+        headless::write_density(path, density)?;
+    }
     Ok(())
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn stamp_preview_follows_cursor() {
+        let mut app = App::new(Duration::from_millis(75));
+        app.board_area = Rect::new(10, 0, 20, 10);
+        app.pending_stamp = Some(Board::from([(0i64, 0i64).into(), (1, 0).into()]));
+        assert_eq!(app.stamp_preview(), None);
+
+        // Center of the board area is the origin
+        app.mouse = (20, 5);
+        assert_eq!(
+            app.stamp_preview(),
+            Some(Board::from([(0i64, 0i64).into(), (1, 0).into()]))
+        );
+
+        app.origin = Point::new(3, -2);
+        app.mouse = (11, 1);
+        assert_eq!(
+            app.stamp_preview(),
+            Some(Board::from([(-6i64, 2i64).into(), (-5, 2).into()]))
+        );
+    }
+
+    #[test]
+    fn default_pattern_by_name() {
+        let pattern = default_pattern_from(Some("glider".into())).unwrap();
+        let missing = default_pattern_from(Some("/nonexistent/pattern.rle".into()));
+
+        let app = App::new(Duration::from_millis(75)).board(&pattern).unwrap();
+        assert_eq!(app.game.board, patterns::get("Glider").unwrap());
+        assert!(missing.unwrap_err().contains("/nonexistent/pattern.rle"));
+        assert_eq!(default_pattern_from(None).unwrap(), patterns::QUEEN_BEE);
+        assert_eq!(
+            default_pattern_from(Some("".into())).unwrap(),
+            patterns::QUEEN_BEE
+        );
+    }
+
+    #[test]
+    fn mouse_capture_toggle() {
+        let mut cx = Context::default();
+        let mut app = App::new(Duration::from_millis(75));
+        let key = |c| Event::Key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE));
+        app.update(&mut cx, key('M'));
+        assert!(!app.mouse_capture);
+        assert!(matches!(cx.commands(), [Command::SetMouseCapture(false)]));
+
+        // The next key only turns capture back on
+        app.update(&mut cx, key('q'));
+        assert!(app.mouse_capture);
+        assert!(matches!(
+            cx.commands(),
+            [
+                Command::SetMouseCapture(false),
+                Command::SetMouseCapture(true)
+            ]
+        ));
+    }
+
+    #[test]
+    fn keyboard_cursor() {
+        let mut cx = Context::default();
+        let mut app = App::new(Duration::from_millis(75));
+        app.origin = Point::new(5, 5);
+        let key = |c| Event::Key(KeyEvent::new(c, KeyModifiers::NONE));
+        for code in [
+            KeyCode::Right,
+            KeyCode::Right,
+            KeyCode::Down,
+            KeyCode::Enter,
+        ] {
+            app.update(&mut cx, key(code));
+        }
+        assert_eq!(app.cursor, Some(Point::new(7, 4)));
+        assert_eq!(app.game.board, Board::from([(7i64, 4i64).into()]));
+
+        app.update(&mut cx, key(KeyCode::Enter));
+        assert!(app.game.board.is_empty());
+    }
+
+    #[test]
+    fn mouse_buttons() {
+        let mut cx = Context::default();
+        let mut app = App::new(Duration::from_millis(75));
+        app.board_area = Rect::new(0, 0, 10, 10);
+        let click = |button| {
+            Event::Mouse(MouseEvent {
+                kind: MouseEventKind::Up(button),
+                column: 5,
+                row: 5,
+                modifiers: KeyModifiers::NONE,
+            })
+        };
+        let alive = |app: &App| app.game.board.contains(&Point::new(0, 0));
+
+        // Left births and right kills, however many times they're clicked
+        app.update(&mut cx, click(MouseButton::Left));
+        app.update(&mut cx, click(MouseButton::Left));
+        assert!(alive(&app));
+        app.update(&mut cx, click(MouseButton::Right));
+        app.update(&mut cx, click(MouseButton::Right));
+        assert!(!alive(&app));
+        // Middle toggles
+        app.update(&mut cx, click(MouseButton::Middle));
+        assert!(alive(&app));
+        app.update(&mut cx, click(MouseButton::Middle));
+        assert!(!alive(&app));
+    }
+
+    #[test]
+    fn snap_stamps() {
+        let size = Point::new(2, 3);
+        let anchor = Point::new(1, 1);
+        assert_eq!(snap_to_grid(anchor, anchor, size), anchor);
+        assert_eq!(
+            snap_to_grid(Point::new(2, 2), anchor, size),
+            Point::new(3, 1)
+        );
+        assert_eq!(
+            snap_to_grid(Point::new(0, 3), anchor, size),
+            Point::new(1, 4)
+        );
+        assert_eq!(
+            snap_to_grid(Point::new(-2, -1), anchor, size),
+            Point::new(-1, -2)
+        );
+
+        // The first stamp anchors the grid and later ones tile from it
+        let mut cx = Context::default();
+        let mut app = App::new(Duration::from_millis(75));
+        app.board_area = Rect::new(0, 0, 20, 20);
+        app.pending_stamp = Some(Board::from([(0i64, 0i64).into(), (1, 1).into()]));
+        let click = |column, row| {
+            Event::Mouse(MouseEvent {
+                kind: MouseEventKind::Up(MouseButton::Left),
+                column,
+                row,
+                modifiers: KeyModifiers::NONE,
+            })
+        };
+        app.update(&mut cx, Event::Key(KeyEvent::from(KeyCode::Char('g'))));
+        app.update(&mut cx, click(10, 10));
+        app.update(&mut cx, click(13, 9));
+        assert_eq!(app.snap_anchor, Some(Point::new(0, 0)));
+        assert_eq!(
+            app.game.board,
+            Board::from([
+                (0i64, 0i64).into(),
+                (1, 1).into(),
+                (4, 2).into(),
+                (5, 3).into()
+            ])
+        );
+    }
+
+    #[test]
+    fn click_before_first_render() {
+        let mut cx = Context::default();
+        let mut app = App::new(Duration::from_millis(75));
+        app.update(
+            &mut cx,
+            Event::Resize {
+                width: 85,
+                height: 21,
+            },
+        );
+        // The board is right of the 25 column info panel and above the status line
+        assert_eq!(app.board_area, Rect::new(25, 0, 60, 20));
+        app.update(
+            &mut cx,
+            Event::Mouse(MouseEvent {
+                kind: MouseEventKind::Up(MouseButton::Left),
+                column: 55,
+                row: 10,
+                modifiers: KeyModifiers::NONE,
+            }),
+        );
+        assert_eq!(app.game.board, Board::from([(0i64, 0i64).into()]));
+    }
+
+    #[test]
+    fn click_acts_once() {
+        let mut cx = Context::default();
+        let mut app = App::new(Duration::from_millis(75));
+        app.board_area = Rect::new(0, 0, 20, 10);
+        let mouse = |kind, column| {
+            Event::Mouse(MouseEvent {
+                kind,
+                column,
+                row: 5,
+                modifiers: KeyModifiers::NONE,
+            })
+        };
+
+        // Middle click toggles, so acting on both halves would leave the cell dead
+        app.update(
+            &mut cx,
+            mouse(MouseEventKind::Down(MouseButton::Middle), 10),
+        );
+        app.update(&mut cx, mouse(MouseEventKind::Up(MouseButton::Middle), 10));
+        assert_eq!(app.game.board, Board::from([(0i64, 0i64).into()]));
+
+        // Released somewhere else isn't a click
+        app.update(
+            &mut cx,
+            mouse(MouseEventKind::Down(MouseButton::Middle), 10),
+        );
+        app.update(&mut cx, mouse(MouseEventKind::Up(MouseButton::Middle), 12));
+        assert_eq!(app.game.board, Board::from([(0i64, 0i64).into()]));
+
+        // A release without a press is still a click
+        app.update(&mut cx, mouse(MouseEventKind::Up(MouseButton::Middle), 10));
+        assert!(app.game.board.is_empty());
+    }
+
+    #[test]
+    fn state_transitions() {
+        let mut state = AppState::Stopped;
+        assert!(state.play());
+        assert!(matches!(state, AppState::Running));
+        assert!(!state.play());
+        state.pause();
+        assert!(matches!(state, AppState::Paused));
+        state.pause();
+        assert!(matches!(state, AppState::Paused));
+        assert!(!state.play());
+        state.stop();
+        assert!(matches!(state, AppState::Stopped));
+        state.pause();
+        assert!(matches!(state, AppState::Stopped));
+    }
+
+    #[test]
+    fn gen_then_restart() {
+        let r = || Event::Key(KeyEvent::from(KeyCode::Char('r')));
+        let blinker: Board = ".x.\n.x.\n.x.".parse().unwrap();
+
+        // Jumping on this thread
+        let mut cx = Context::default();
+        let mut app = App::new(Duration::from_millis(75))
+            .board(".x.\n.x.\n.x.")
+            .unwrap();
+        app.apply(&mut cx, Action::Gen(5));
+        assert_eq!(app.game.generation, 5);
+        assert!(matches!(app.state, AppState::Paused));
+        app.update(&mut cx, r());
+        assert_eq!((app.game.generation, &app.game.board), (0, &blinker));
+
+        // And on the jump worker
+        let listener = event::Listener::default();
+        let mut cx = Context::new(listener.subscribe());
+        let mut app = App::new(Duration::from_millis(75))
+            .board(".x.\n.x.\n.x.")
+            .unwrap();
+        app.apply(&mut cx, Action::Gen(5));
+        assert!(matches!(app.state, AppState::Paused));
+        while app.jump.is_some() {
+            let event = listener.next().unwrap();
+            app.update(&mut cx, event);
+        }
+        assert_eq!(app.game.generation, 5);
+        app.update(&mut cx, r());
+        assert_eq!((app.game.generation, &app.game.board), (0, &blinker));
+    }
+
+    #[test]
+    fn resuming_keeps_initial_board() {
+        let mut cx = Context::default();
+        let mut app = App::new(Duration::from_millis(75)).board("xxx").unwrap();
+        let space = || Event::Key(KeyEvent::from(KeyCode::Char(' ')));
+        let start = app.game.board.clone();
+
+        // Starting snapshots the board
+        app.update(&mut cx, space());
+        assert!(matches!(app.state, AppState::Running));
+        assert_eq!(app.initial_board, start);
+        app.game.step();
+        app.update(&mut cx, space());
+        assert!(matches!(app.state, AppState::Paused));
+
+        // Resuming doesn't
+        app.game.step();
+        app.update(&mut cx, space());
+        assert!(matches!(app.state, AppState::Running));
+        assert_eq!(app.initial_board, start);
+
+        // Starting again after stopping does
+        app.update(&mut cx, space());
+        app.state.stop();
+        app.game.board = "xx\nxx".parse().unwrap();
+        app.apply(&mut cx, Action::Play);
+        assert_eq!(app.initial_board, app.game.board);
+    }
+
+    #[test]
+    fn remote_requests() {
+        let mut cx = Context::default();
+        let mut app = App::new(Duration::from_millis(75)).board("xxx").unwrap();
+        let mut lines = "play\npause\nstep\nstep\nload x = 3, y = 3\nbo$2bo$3o!\nstep\n"
+            .lines()
+            .map(str::to_owned);
+        let mut next = || remote::read_request(&mut lines).unwrap().unwrap();
+
+        app.update(&mut cx, next());
+        assert!(matches!(app.state, AppState::Running));
+        app.update(&mut cx, next());
+        assert!(matches!(app.state, AppState::Paused));
+        app.update(&mut cx, next());
+        app.update(&mut cx, next());
+        assert_eq!(app.game.generation, 2);
+        assert_eq!(app.game.board, "xxx".parse::<Board>().unwrap());
+
+        app.update(&mut cx, next());
+        assert_eq!(app.game.generation, 0);
+        assert_eq!(app.game.board.population(), 5);
+        assert_eq!(app.status, format!("Loaded {}", remote::REMOTE_PATH));
+        app.update(&mut cx, next());
+        assert_eq!(app.game.generation, 1);
+    }
+
+    #[test]
+    fn drive_app_with_injected_events() {
+        let program = Program::new();
+        let sender = program.sender();
+        let key = |code| Event::Key(KeyEvent::from(code));
+        sender
+            .send(Event::Resize {
+                width: 85,
+                height: 21,
+            })
+            .unwrap();
+        sender.send(key(KeyCode::Char('B'))).unwrap();
+        for c in ":rule B36/S23".chars() {
+            sender.send(key(KeyCode::Char(c))).unwrap();
+        }
+        sender.send(key(KeyCode::Enter)).unwrap();
+        sender.send(key(KeyCode::Char('q'))).unwrap();
+        sender.send(key(KeyCode::Char('B'))).unwrap();
+
+        let mut app = App::new(Duration::from_millis(75)).board("xxx").unwrap();
+        program.run_headless(&mut app).unwrap();
+        assert_eq!(app.board_area, Rect::new(25, 0, 60, 20));
+        assert!(app.checkerboard);
+        assert_eq!(app.game.rule, "B36/S23".parse().unwrap());
+        assert_eq!(app.status, "Rule set to B36/S23");
+
+        // Without a quit, it stops once the events run out
+        let program = Program::new();
+        program.sender().send(key(KeyCode::Char('B'))).unwrap();
+        program.run_headless(&mut app).unwrap();
+        assert!(!app.checkerboard);
+    }
+
+    #[test]
+    fn load_fits_pattern() {
+        let mut cx = Context::default();
+        let mut app = App::new(Duration::from_millis(75));
+        app.update(
+            &mut cx,
+            Event::Resize {
+                width: 85,
+                height: 21,
+            },
+        );
+        let pulsar = patterns::source("Pulsar").unwrap();
+        let board = format::parse(pulsar)
+            .unwrap()
+            .translate(Point::new(100, -40));
+        let (min, max) = board.bounding_box().unwrap();
+        app.loaded(pulsar.to_owned(), board);
+        assert_eq!(
+            app.origin,
+            Point::new((min.x + max.x) / 2, (min.y + max.y) / 2)
+        );
+        assert_eq!(app.zoom, 1);
+
+        // Too big for the screen at zoom 1
+        let big = Board::from([Point::new(0, 0), Point::new(500, 100)]);
+        app.loaded(String::new(), big);
+        assert_eq!(app.origin, Point::new(250, 50));
+        let (shown_min, shown_max) = app
+            .viewport()
+            .bounds(app.board_area.width, app.board_area.height);
+        assert!(shown_min.x <= 0 && shown_max.x >= 500 && shown_min.y <= 0 && shown_max.y >= 100);
+    }
+
+    #[test]
+    fn query_cell_history() {
+        let mut cx = Context::default();
+        let mut app = App::new(Duration::from_millis(75)).board("xxx").unwrap();
+        app.board_area = Rect::new(0, 0, 20, 10);
+        app.game.step_n(4);
+        app.state = AppState::Paused;
+        let click = |column| {
+            Event::Mouse(MouseEvent {
+                kind: MouseEventKind::Up(MouseButton::Left),
+                column,
+                row: 5,
+                modifiers: KeyModifiers::NONE,
+            })
+        };
+
+        app.update(&mut cx, click(10));
+        assert_eq!(
+            app.query_report().unwrap(),
+            "cell = (0, 0)\nalive in 3 of 5 gens\nage = 1"
+        );
+        app.update(&mut cx, click(2));
+        assert_eq!(
+            app.query_report().unwrap(),
+            "cell = (-8, 0)\nnever alive in history"
+        );
+        assert_eq!(app.game.generation, 4);
+        assert_eq!(app.game.board.population(), 3);
+
+        app.update(&mut cx, Event::Key(KeyEvent::from(KeyCode::Esc)));
+        assert_eq!(app.query_report(), None);
+    }
+
+    #[test]
+    fn ruler_layout() {
+        let area = Rect::new(0, 0, 85, 21);
+        assert_eq!(AppLayout::new(area, false, false).rulers, None);
+        let layout = AppLayout::new(area, true, false);
+        assert_eq!(
+            layout.rulers,
+            Some((Rect::new(32, 0, 53, 1), Rect::new(25, 1, 7, 19)))
+        );
+        assert_eq!(layout.board, Rect::new(32, 1, 53, 19));
+    }
+
+    #[test]
+    fn bottom_bar_layout() {
+        let area = Rect::new(0, 0, 140, 21);
+        let layout = AppLayout::new(area, false, true);
+        assert_eq!(layout.board, Rect::new(0, 0, 140, 16));
+        assert_eq!(layout.info.len(), 7);
+        assert!(layout
+            .info
+            .iter()
+            .all(|info| info.y == 16 && info.height == 4));
+        assert_eq!(layout.info[0].x, 0);
+        assert_eq!(layout.info[1].x, 20);
+        assert_eq!(layout.tick_slider, Rect::new(20, 18, 20, 1));
+        assert_eq!(layout.status, Rect::new(0, 20, 140, 1));
+
+        let layout = AppLayout::new(area, true, true);
+        assert_eq!(layout.board, Rect::new(7, 1, 133, 15));
+    }
+
+    #[test]
+    fn top_of_pattern_is_top_of_screen() {
+        use tui::widgets::Widget;
+
+        for y_axis in [YAxis::Up, YAxis::Down] {
+            let mut app = App::new(Duration::from_millis(75)).board("x.\n..").unwrap();
+            app.y_axis = y_axis;
+            app.board_area = Rect::new(0, 0, 2, 2);
+            // The center of the area is its bottom right cell
+            app.origin = Point::new(1, 0);
+
+            let mut buf = tui::buffer::Buffer::empty(app.board_area);
+            BoardWidget::new(&app.game.board)
+                .viewport(app.viewport())
+                .render(app.board_area, &mut buf);
+            assert_eq!(buf.get(0, 0).symbol, tui::symbols::block::FULL);
+            assert_eq!(buf.get(0, 1).symbol, "·");
+
+            let top_left = app.screen_to_world(0, 0).unwrap();
+            assert!(app.game.board.contains(&top_left));
+            let shown = match y_axis {
+                YAxis::Up => Point::new(0, 1),
+                YAxis::Down => Point::new(0, -1),
+            };
+            assert_eq!(app.y_axis.display(top_left), shown);
+        }
+    }
+
+    #[test]
+    fn glider_trail_toggle() {
+        let mut cx = Context::default();
+        let mut app = App::new(Duration::from_millis(75))
+            .board(".x.\n..x\nxxx")
+            .unwrap();
+        app.game.step_n(4);
+        assert_eq!(app.displayed_trail(), None);
+
+        app.update(&mut cx, Event::Key(KeyEvent::from(KeyCode::Char('w'))));
+        let trail = app.displayed_trail().unwrap();
+        assert_eq!(trail, app.game.trail(TRAIL_LENGTH));
+        assert!(!trail.is_empty());
+
+        app.update(&mut cx, Event::Key(KeyEvent::from(KeyCode::Char('i'))));
+        assert_eq!(app.displayed_trail(), None);
+    }
+
+    #[test]
+    fn compare_with_initial_board() {
+        use tui::widgets::Widget;
+
+        let mut cx = Context::default();
+        let mut app = App::new(Duration::from_millis(75)).board("xxx").unwrap();
+        app.board_area = Rect::new(0, 0, 3, 3);
+        app.origin = Point::new(1, 0);
+        app.update(&mut cx, Event::Key(KeyEvent::from(KeyCode::Char(' '))));
+        app.state = AppState::Paused;
+        app.game.step();
+        let rendered = |app: &App| {
+            let mut buf = tui::buffer::Buffer::empty(app.board_area);
+            BoardWidget::new(&app.displayed_board())
+                .viewport(app.viewport())
+                .render(app.board_area, &mut buf);
+            (0..3)
+                .map(|x| buf.get(x, 1).symbol.clone())
+                .collect::<String>()
+        };
+        let full = tui::symbols::block::FULL;
+        let middle = format!("·{}·", full);
+        assert_eq!(rendered(&app), middle);
+
+        app.update(&mut cx, Event::Key(KeyEvent::from(KeyCode::Char('i'))));
+        assert_eq!(rendered(&app), full.repeat(3));
+        assert_eq!(app.game.generation, 1);
+        assert_eq!(app.displayed_generation(), 0);
+        assert!(matches!(app.state, AppState::Paused));
+
+        app.update(&mut cx, Event::Key(KeyEvent::from(KeyCode::Char('i'))));
+        assert_eq!(rendered(&app), middle);
+    }
+
+    #[test]
+    fn density_counts_each_generation() {
+        let mut cx = Context::default();
+        let mut app = App::new(Duration::from_millis(75)).board("xxx").unwrap();
+        app.density = Some(Default::default());
+        app.generations_per_tick = 5;
+        app.step_now(&mut cx);
+        app.generations_per_tick = 1;
+        for _ in 0..5 {
+            app.step_now(&mut cx);
+        }
+        let density = app.density.as_ref().unwrap();
+        assert_eq!(density.generations, 10);
+        assert_eq!(density.count(&Point::new(0, 0)), 5);
+    }
+
+    #[test]
+    fn region_command() {
+        let mut cx = Context::default();
+        let mut app = App::new(Duration::from_millis(75))
+            .board(".....\n.xxx.\n.....")
+            .unwrap();
+        app.apply(&mut cx, "region -2 -2 6 4".parse().unwrap());
+        assert_eq!(app.status, "Region steps the same as the full board");
+        assert_eq!(app.discrepancies, Some(Default::default()));
+
+        app.apply(&mut cx, "region 6 4 2 -2".parse().unwrap());
+        assert_eq!(app.status, "3 cells differ stepping the region alone");
+        let (missing, extra) = app.discrepancies.clone().unwrap();
+        assert_eq!(missing.population(), 3);
+        assert!(extra.is_empty());
+        assert_eq!(app.game.generation, 0);
+
+        app.step_now(&mut cx);
+        assert_eq!(app.discrepancies, None);
+    }
+
+    #[test]
+    fn zoom_stays_within_limits() {
+        let mut cx = Context::default();
+        let mut app = App::new(Duration::from_millis(75))
+            .zoom_limits(Some(2), Some(12))
+            .unwrap();
+        assert_eq!(app.zoom, 2);
+        let key = |c| Event::Key(KeyEvent::from(KeyCode::Char(c)));
+        for _ in 0..10 {
+            app.update(&mut cx, key('-'));
+            assert!((2..=12).contains(&app.zoom));
+        }
+        assert_eq!(app.zoom, 12);
+        for _ in 0..10 {
+            app.update(&mut cx, key('+'));
+            assert!((2..=12).contains(&app.zoom));
+        }
+        assert_eq!(app.zoom, 2);
+
+        let mut app = App::new(Duration::from_millis(75));
+        for _ in 0..20 {
+            app.update(&mut cx, key('-'));
+        }
+        assert_eq!(app.zoom, DEFAULT_MAX_ZOOM);
+
+        assert!(App::new(Duration::from_millis(75))
+            .zoom_limits(Some(0), None)
+            .is_err());
+        assert!(App::new(Duration::from_millis(75))
+            .zoom_limits(Some(8), Some(4))
+            .is_err());
+    }
+
+    #[test]
+    fn transform_pending_stamp() {
+        let mut cx = Context::default();
+        let mut app = App::new(Duration::from_millis(75));
+        app.board_area = Rect::new(0, 0, 10, 10);
+        app.mouse = (5, 5);
+        let glider = patterns::get("Glider").unwrap().normalize();
+        app.pending_stamp = Some(glider.clone());
+        let key = |c| Event::Key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE));
+
+        app.update(&mut cx, key('r'));
+        let rotated = app.stamp_preview().unwrap();
+        assert_ne!(rotated, glider);
+        assert_eq!(rotated.canonical(), glider.canonical());
+        for _ in 0..3 {
+            app.update(&mut cx, key('r'));
+        }
+        assert_eq!(app.pending_stamp, Some(glider.clone()));
+
+        app.update(&mut cx, key('x'));
+        assert_eq!(
+            app.pending_stamp,
+            Some(glider.flip_horizontal().normalize())
+        );
+        app.update(&mut cx, key('x'));
+        assert_eq!(app.pending_stamp, Some(glider));
+        assert!(app.game.board.is_empty());
+    }
+
+    #[test]
+    fn adaptive_tick_rate() {
+        let ms = Duration::from_millis;
+        let target = ms(50);
+        // Overrun backs off
+        assert_eq!(adapt_tick_rate(ms(50), ms(80), target), ms(120));
+        assert_eq!(adapt_tick_rate(ms(50), ms(5000), target), MAX_ADAPTIVE_TICK);
+        // Keeping up holds steady
+        assert_eq!(adapt_tick_rate(ms(120), ms(80), target), ms(120));
+        // Cheap steps recover, but never faster than the target
+        assert_eq!(adapt_tick_rate(ms(120), ms(10), target), ms(90));
+        assert_eq!(adapt_tick_rate(ms(60), ms(10), target), ms(50));
+        assert_eq!(adapt_tick_rate(ms(50), ms(1), target), ms(50));
+        // Recovering doesn't undercut the last step
+        assert_eq!(adapt_tick_rate(ms(200), ms(90), target), ms(150));
+        assert_eq!(adapt_tick_rate(ms(200), ms(99), target), ms(150));
+
+        // The rate shown is always the last one the tick timer was given
+        let mut cx = Context::default();
+        let mut app = App::new(target);
+        let scheduled = |cx: &Context| match cx.commands().last() {
+            Some(Command::SetTickRate(tick)) => Some(*tick),
+            _ => None,
+        };
+        app.update(&mut cx, Event::Key(KeyEvent::from(KeyCode::Char('a'))));
+        app.after_step(&mut cx, ms(80));
+        assert_eq!(scheduled(&cx), Some(ms(120)));
+        assert_eq!(app.effective_tick, ms(120));
+        app.update(&mut cx, Event::Key(KeyEvent::from(KeyCode::Char('a'))));
+        assert_eq!(scheduled(&cx), Some(target));
+        assert_eq!(app.effective_tick, target);
+    }
+
+    #[test]
+    fn clear_starts_over() {
+        let mut cx = Context::default();
+        let mut app = App::new(Duration::from_millis(75)).board("xxx").unwrap();
+        app.game.step_n(3);
+        app.show_diff = true;
+        app.update(
+            &mut cx,
+            Event::Key(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::NONE)),
+        );
+        assert!(app.game.board.is_empty());
+        assert_eq!(app.game.generation, 0);
+        assert_eq!(app.displayed_diff(), None);
+        assert!(app.game.killed_cells.is_empty());
+    }
+
+    #[test]
+    fn undo_redo_edits() {
+        let mut cx = Context::default();
+        let mut app = App::new(Duration::from_millis(75));
+        app.board_area = Rect::new(0, 0, 10, 10);
+        let click = |button, column| {
+            Event::Mouse(MouseEvent {
+                kind: MouseEventKind::Up(button),
+                column,
+                row: 5,
+                modifiers: KeyModifiers::NONE,
+            })
+        };
+        let ctrl = |c| Event::Key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL));
+        let cells = |app: &App| app.game.board.sorted();
+
+        app.update(&mut cx, click(MouseButton::Left, 5));
+        app.update(&mut cx, click(MouseButton::Left, 6));
+        let drawn = cells(&app);
+        assert_eq!(drawn.len(), 2);
+        app.update(
+            &mut cx,
+            Event::Key(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::NONE)),
+        );
+        assert!(app.game.board.is_empty());
+        // Clearing an empty board changes nothing, so there's nothing to undo
+        app.update(
+            &mut cx,
+            Event::Key(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::NONE)),
+        );
+        assert_eq!(app.edit_history.len(), 3);
+
+        app.update(&mut cx, ctrl('z'));
+        assert_eq!(cells(&app), drawn);
+        app.update(&mut cx, ctrl('z'));
+        assert_eq!(cells(&app), &drawn[..1]);
+        app.update(&mut cx, ctrl('y'));
+        assert_eq!(cells(&app), drawn);
+
+        // A new edit forgets what could be redone
+        app.update(&mut cx, click(MouseButton::Right, 5));
+        assert_eq!(cells(&app), &drawn[1..]);
+        app.update(&mut cx, ctrl('y'));
+        assert_eq!(cells(&app), &drawn[1..]);
+        for _ in 0..5 {
+            app.update(&mut cx, ctrl('z'));
+        }
+        assert!(app.game.board.is_empty());
+
+        // Undo follows whether the board can be edited, not just whether it's stopped
+        app.update(&mut cx, click(MouseButton::Left, 5));
+        app.state = AppState::Running;
+        app.update(&mut cx, ctrl('z'));
+        assert_eq!(cells(&app).len(), 1);
+        app.live_edit = true;
+        app.update(&mut cx, ctrl('z'));
+        assert!(app.game.board.is_empty());
+        app.update(&mut cx, ctrl('y'));
+        assert_eq!(cells(&app).len(), 1);
+
+        // The oldest edits are dropped past the limit
+        for x in 0..EDIT_HISTORY_LIMIT as i64 + 5 {
+            app.edit(|board| board.birth_cell(&Point::new(x, 100)));
+        }
+        assert_eq!(app.edit_history.len(), EDIT_HISTORY_LIMIT);
+        assert_eq!(app.edit_history[0].population(), 6);
+    }
+
+    #[test]
+    fn preview_fits_view() {
+        let mut cx = Context::default();
+        let mut app = App::new(Duration::from_millis(75));
+        app.board_area = Rect::new(0, 0, 20, 10);
+        let bounds = (Point::new(0, 0), Point::new(999, 999));
+        let fit = app.fitter();
+        let view = fit(bounds.0, bounds.1).bounds(20, 10);
+        let board: Board = [view.0, view.1].into_iter().collect();
+        app.update(
+            &mut cx,
+            Event::BoardPreview {
+                board: Arc::new(board.clone()),
+                bounds,
+            },
+        );
+        // Shown with the view the preview was cut to
+        assert_eq!(app.origin, Point::new(499, 499));
+        assert_eq!(app.viewport().bounds(20, 10), view);
+        assert_eq!(app.load_preview, Some(board));
+    }
+
+    #[test]
+    fn load_applies_declared_rule() {
+        let mut cx = Context::default();
+        let mut app = App::new(Duration::from_millis(75));
+        let dir = std::env::temp_dir().join(format!("rules-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let highlife = dir.join("replicator.rle");
+        std::fs::write(&highlife, "x = 3, y = 3, rule = B36/S23\n2o$obo$3o!\n").unwrap();
+        app.apply(&mut cx, Action::Load(highlife.clone()));
+        assert_eq!(app.game.rule.to_string(), "B36/S23");
+        assert_eq!(
+            app.status,
+            format!("Loaded {}, rule set to B36/S23", highlife.display())
+        );
+
+        // Without a rule, back to Conway's
+        let plain = dir.join("plain.rle");
+        std::fs::write(&plain, "x = 3, y = 1\n3o!\n").unwrap();
+        app.apply(&mut cx, Action::Load(plain.clone()));
+        assert_eq!(app.game.rule, rule::Rule::CONWAY);
+        assert_eq!(
+            app.status,
+            format!("Loaded {}, rule set to B3/S23", plain.display())
+        );
+        app.apply(&mut cx, Action::Load(plain.clone()));
+        assert_eq!(app.status, format!("Loaded {}", plain.display()));
+
+        // Non-totalistic rules aren't supported, so the pattern still loads under Conway's
+        app.apply(&mut cx, Action::Load(highlife));
+        let isotropic = dir.join("isotropic.rle");
+        std::fs::write(&isotropic, "x = 3, y = 1, rule = B3/S23-a\n3o!\n").unwrap();
+        app.apply(&mut cx, Action::Load(isotropic));
+        assert_eq!(app.game.rule, rule::Rule::CONWAY);
+        assert_eq!(app.game.board.population(), 3);
+        assert!(
+            app.status.ends_with("running under B3/S23 instead"),
+            "{}",
+            app.status
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn export_view() {
+        let mut cx = Context::default();
+        let mut app = App::new(Duration::from_millis(75))
+            .board(".x.\n..x\nxxx")
+            .unwrap();
+        app.board_area = Rect::new(30, 0, 10, 10);
+        app.game.board.birth_cell(&Point::new(40, 0));
+        app.game.board.birth_cell(&Point::new(0, -30));
+        let glider: Board = ".x.\n..x\nxxx".parse().unwrap();
+        assert_eq!(app.visible_board(), glider);
+
+        let path = std::env::temp_dir().join(format!("export-view-{}.rle", std::process::id()));
+        app.apply(&mut cx, Action::ExportView(path.clone()));
+        let written = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(format::parse(&written).unwrap(), glider);
+    }
+
+    #[test]
+    fn freeze_label_and_export() {
+        let path = std::env::temp_dir().join(format!("freeze-{}.svg", std::process::id()));
+
+        let mut cx = Context::default();
+        let mut app = App::new(Duration::from_millis(75))
+            .board(".x.\n.x.\n.x.")
+            .unwrap();
+        app.board_area = Rect::new(0, 0, 20, 10);
+        app.apply(&mut cx, Action::Freeze(3));
+        assert_eq!(app.game.generation, 3);
+        assert!(app.status.starts_with("Frozen at generation 3"));
+        assert!(matches!(app.state, AppState::Paused));
+        assert_eq!(app.initial_board.population(), 3);
+        assert_eq!(app.game.board.to_ascii(), "xxx\n");
+        app.apply(
+            &mut cx,
+            Action::Label {
+                at: Point::new(1, 1),
+                text: "period 2".to_owned(),
+            },
+        );
+        app.apply(&mut cx, Action::ExportView(path.clone()));
+        assert_eq!(app.status, format!("Wrote {}", path.display()));
+        let svg = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(svg.lines().filter(|l| l.starts_with("<rect x=")).count(), 3);
+        assert!(svg.contains(">period 2</text>"));
+
+        // On the jump worker, stopping at the population cap like :gen
+        let listener = event::Listener::default();
+        let mut cx = Context::new(listener.subscribe());
+        let mut app = App::new(Duration::from_millis(75))
+            .board(".x.\n.x.\n.x.")
+            .unwrap();
+        app.apply(&mut cx, Action::Freeze(3));
+        assert!(matches!(app.state, AppState::Paused));
+        while app.jump.is_some() {
+            let event = listener.next().unwrap();
+            app.update(&mut cx, event);
+        }
+        assert_eq!(app.game.generation, 3);
+        assert!(app.status.starts_with("Frozen at generation 3"));
+        app.game.board = (0..JUMP_POPULATION_CAP as i64 / 3 + 1)
+            .flat_map(|i| (0..3).map(move |x| Point::new(x, i * 4)))
+            .collect();
+        app.apply(&mut cx, Action::Freeze(u32::MAX));
+        while app.jump.is_some() {
+            let event = listener.next().unwrap();
+            app.update(&mut cx, event);
+        }
+        assert_eq!(app.game.generation, 3);
+        assert!(app.status.starts_with("Stopped at generation 3"));
+    }
+
+    #[test]
+    fn remote_requests_wait_for_jump() {
+        let listener = event::Listener::default();
+        let mut cx = Context::new(listener.subscribe());
+        let mut app = App::new(Duration::from_millis(75))
+            .board(".x.\n..x\nxxx")
+            .unwrap();
+        app.apply(&mut cx, Action::Gen(40));
+        assert!(app.jump.is_some());
+        app.update(&mut cx, Event::Action(Action::Step));
+        assert_eq!(app.status, "Ignored `step` while jumping");
+        app.update(
+            &mut cx,
+            Event::BoardLoaded {
+                path: remote::REMOTE_PATH.into(),
+                source: "o!".into(),
+                board: Arc::new(Board::from([Point::new(0, 0)])),
+            },
+        );
+        assert_eq!(app.status, "Ignored loading remote pattern while jumping");
+        assert_eq!(app.game.generation, 0);
+        while app.jump.is_some() {
+            let event = listener.next().unwrap();
+            app.update(&mut cx, event);
+        }
+        assert_eq!(app.game.generation, 40);
+        assert_eq!(app.game.board.population(), 5);
+
+        // Once it's done they go through again
+        app.update(&mut cx, Event::Action(Action::Step));
+        assert_eq!(app.game.generation, 41);
+    }
+
+    #[test]
+    fn export_keeps_library_metadata() {
+        let mut cx = Context::default();
+        let mut app = App::new(Duration::from_millis(75));
+        app.board_area = Rect::new(0, 0, 20, 10);
+        app.library_index = patterns::LIBRARY
+            .iter()
+            .position(|(name, _)| *name == "Glider")
+            .unwrap();
+        app.update(&mut cx, Event::Key(KeyEvent::from(KeyCode::Char('p'))));
+        app.update(
+            &mut cx,
+            Event::Mouse(MouseEvent {
+                kind: MouseEventKind::Up(MouseButton::Left),
+                column: 10,
+                row: 5,
+                modifiers: KeyModifiers::NONE,
+            }),
+        );
+        assert_eq!(app.game.board.population(), 5);
+        let untouched = app.view_rle();
+        assert!(untouched.starts_with("#N Glider\n#O Richard K. Guy\n"));
+        assert!(!untouched.contains("Edited"));
+
+        app.game.board.birth_cell(&Point::new(-5, 0));
+        let edited = app.view_rle();
+        assert!(edited.starts_with("#N Glider\n#O Richard K. Guy\n"));
+        assert!(edited.contains("#C Edited from Glider in the pattern library\n"));
+        assert_eq!(format::parse(&edited).unwrap().population(), 6);
+    }
+
+    #[test]
+    fn clear_view() {
+        let mut cx = Context::default();
+        let mut app = App::new(Duration::from_millis(75))
+            .board(".x.\n..x\nxxx")
+            .unwrap();
+        app.board_area = Rect::new(30, 0, 10, 10);
+        let offscreen = [Point::new(40, 0), Point::new(0, -30)];
+        for p in &offscreen {
+            app.game.board.birth_cell(p);
+        }
+        let before = app.game.board.clone();
+
+        app.update(
+            &mut cx,
+            Event::Key(KeyEvent::new(KeyCode::Char('C'), KeyModifiers::SHIFT)),
+        );
+        assert_eq!(app.game.board, Board::from(offscreen));
+        app.update(
+            &mut cx,
+            Event::Key(KeyEvent::new(KeyCode::Char('z'), KeyModifiers::CONTROL)),
+        );
+        assert_eq!(app.game.board, before);
+    }
+
+    #[test]
+    fn tick_slider() {
+        let mut cx = Context::default();
+        let mut app = App::new(Duration::from_millis(75));
+        app.update(
+            &mut cx,
+            Event::Resize {
+                width: 80,
+                height: 40,
+            },
+        );
+        let slider = app.tick_slider;
+        assert_eq!(slider.height, 1);
+
+        let drag = |kind, column| {
+            Event::Mouse(MouseEvent {
+                kind,
+                column,
+                row: slider.y,
+                modifiers: KeyModifiers::NONE,
+            })
+        };
+        app.update(
+            &mut cx,
+            drag(MouseEventKind::Down(MouseButton::Left), slider.x),
+        );
+        assert_eq!(app.game_tick, MIN_TICK);
+        let right = slider.x + slider.width - 1;
+        app.update(
+            &mut cx,
+            drag(MouseEventKind::Drag(MouseButton::Left), right),
+        );
+        assert_eq!(app.game_tick, MAX_ADAPTIVE_TICK);
+        assert_eq!(app.effective_tick, MAX_ADAPTIVE_TICK);
+
+        // Halfway along the log scale is the geometric mean of the ends
+        let middle = ratio_to_tick(0.5).as_secs_f64();
+        let mean = (MIN_TICK.as_secs_f64() * MAX_ADAPTIVE_TICK.as_secs_f64()).sqrt();
+        assert!((middle - mean).abs() < 1e-9);
+        for ms in [10, 75, 400, 2000] {
+            let tick = Duration::from_millis(ms);
+            let round_trip = ratio_to_tick(tick_to_ratio(tick)).as_secs_f64();
+            assert!((round_trip - tick.as_secs_f64()).abs() < 1e-6, "{:?}", tick);
+        }
+        assert_eq!(tick_to_ratio(Duration::from_secs(60)), 1.0);
+    }
+
+    #[test]
+    fn scrub_history() {
+        let mut cx = Context::default();
+        let mut app = App::new(Duration::from_millis(75))
+            .board(".x.\n..x\nxxx")
+            .unwrap();
+        let key = |c| Event::Key(KeyEvent::new(c, KeyModifiers::NONE));
+        app.game.step_n(5);
+        let current = app.game.board.clone();
+        let third = app.game.past(2).unwrap();
+
+        app.update(&mut cx, key(KeyCode::Char('t')));
+        assert_eq!(*app.displayed_board(), current);
+        app.update(&mut cx, key(KeyCode::Left));
+        app.update(&mut cx, key(KeyCode::Left));
+        app.update(&mut cx, key(KeyCode::Right));
+        app.update(&mut cx, key(KeyCode::Left));
+        assert_eq!(*app.displayed_board(), third);
+        // Built once for the index, not per frame
+        assert!(matches!(app.displayed_board(), Cow::Borrowed(_)));
+        assert_eq!(app.displayed_generation(), 3);
+        assert_eq!(app.scrubbed.as_ref().map(|(i, _, _)| *i), Some(3));
+        // Scrubbing doesn't touch the live game
+        assert_eq!(app.game.board, current);
+        assert_eq!(app.game.generation, 5);
+
+        app.update(&mut cx, key(KeyCode::Enter));
+        assert_eq!(app.scrub, None);
+        assert_eq!(app.game.board, third);
+        assert_eq!(app.game.generation, 3);
+    }
+
+    #[test]
+    fn rule_menu() {
+        let mut cx = Context::default();
+        let mut app = App::new(Duration::from_millis(75));
+        let key = |c| Event::Key(KeyEvent::new(c, KeyModifiers::NONE));
+        app.update(&mut cx, key(KeyCode::Char('m')));
+        assert_eq!(app.rule_menu, Some(0));
+        app.update(&mut cx, key(KeyCode::Down));
+        app.update(&mut cx, key(KeyCode::Enter));
+        assert_eq!(app.rule_menu, None);
+        assert_eq!(app.game.rule.to_string(), "B36/S23");
+
+        // Reopening starts at the current rule
+        app.update(&mut cx, key(KeyCode::Char('m')));
+        assert_eq!(app.rule_menu, Some(1));
+        app.update(&mut cx, key(KeyCode::Up));
+        app.update(&mut cx, key(KeyCode::Esc));
+        assert_eq!(app.game.rule.to_string(), "B36/S23");
+    }
+
+    #[test]
+    fn gallery_loads_selection() {
+        let dir = std::env::temp_dir().join(format!("app-gallery-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("a.cells"), "!Name: Blinker\nOOO\n").unwrap();
+        std::fs::write(dir.join("b.rle"), "#N Glider\nx = 3, y = 3\nbo$2bo$3o!\n").unwrap();
+
+        let mut cx = Context::default();
+        let mut app = App::new(Duration::from_millis(75));
+        let key = |c| Event::Key(KeyEvent::new(c, KeyModifiers::NONE));
+        app.apply(&mut cx, Action::Gallery(dir.clone()));
+        assert_eq!(app.gallery.as_ref().unwrap().entries.len(), 2);
+        app.update(&mut cx, key(KeyCode::Down));
+        app.update(&mut cx, key(KeyCode::Enter));
+        assert!(app.gallery.is_none());
+        assert_eq!(app.game.board.population(), 5);
+        assert_eq!(
+            app.status,
+            format!("Loaded {}", dir.join("b.rle").display())
+        );
+
+        app.apply(&mut cx, Action::Gallery(dir.join("missing")));
+        assert!(app.gallery.is_none());
+        assert!(app.status.starts_with("Failed to read"));
+
+        // Scanned off the event loop when there is one
+        let listener = event::Listener::default();
+        let mut cx = Context::new(listener.subscribe());
+        app.apply(&mut cx, Action::Gallery(dir.clone()));
+        assert!(app.gallery.is_none());
+        let event = listener.next().unwrap();
+        assert!(matches!(event, Event::GalleryScanned { .. }));
+        app.update(&mut cx, event);
+        assert_eq!(app.gallery.as_ref().unwrap().entries.len(), 2);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn reload_pristine_pattern() {
+        let mut cx = Context::default();
+        let mut app = App::new(Duration::from_millis(75))
+            .board(".x.\n..x\nxxx")
+            .unwrap();
+        let pristine = app.game.board.clone();
+        app.game.board.toggle_cell(&Point::new(5, 5));
+        app.update(
+            &mut cx,
+            Event::Key(KeyEvent::new(KeyCode::Char(' '), KeyModifiers::NONE)),
+        );
+        app.update(&mut cx, Event::Tick);
+        app.update(&mut cx, Event::Tick);
+        assert_eq!(app.game.generation, 2);
+
+        app.update(
+            &mut cx,
+            Event::Key(KeyEvent::new(KeyCode::Char('R'), KeyModifiers::SHIFT)),
+        );
+        assert!(matches!(app.state, AppState::Stopped));
+        assert_eq!(app.game.generation, 0);
+        assert_eq!(app.game.board, pristine);
+        assert_eq!(app.initial_board, pristine);
+    }
+
+    #[test]
+    fn rule_command() {
+        let mut cx = Context::default();
+        let mut app = App::new(Duration::from_millis(75)).board("xxx").unwrap();
+        let key = |c| Event::Key(KeyEvent::new(c, KeyModifiers::NONE));
+        let mut command = |app: &mut App, s: &str| {
+            for c in s.chars() {
+                app.update(&mut cx, key(KeyCode::Char(c)));
+            }
+            app.update(&mut cx, key(KeyCode::Enter));
+        };
+
+        command(&mut app, ":rule B36/S23");
+        assert_eq!(app.game.rule.to_string(), "B36/S23");
+        command(&mut app, ":rule B36/S9");
+        assert_eq!(app.game.rule.to_string(), "B36/S23");
+        assert_eq!(app.status, "Invalid rule `B36/S9`, expected e.g. B3/S23");
+    }
+
+    #[test]
+    fn gen_command() {
+        let mut cx = Context::default();
+        let mut app = App::new(Duration::from_millis(75)).board("xxx").unwrap();
+        let key = |c| Event::Key(KeyEvent::new(c, KeyModifiers::NONE));
+        for c in ":gen 11".chars() {
+            app.update(&mut cx, key(KeyCode::Char(c)));
+        }
+        app.update(&mut cx, key(KeyCode::Enter));
+        assert_eq!(app.input, None);
+        assert_eq!(app.game.generation, 11);
+        assert_eq!(app.game.board.to_ascii(), "x\nx\nx\n");
+    }
+
+    #[test]
+    fn until_command() {
+        let mut cx = Context::default();
+        let mut app = App::new(Duration::from_millis(75))
+            .board(".xx\nxx.\n.x.")
+            .unwrap();
+        let key = |c| Event::Key(KeyEvent::new(c, KeyModifiers::NONE));
+        for c in ":until > 20".chars() {
+            app.update(&mut cx, key(KeyCode::Char(c)));
+        }
+        app.update(&mut cx, key(KeyCode::Enter));
+        assert!(matches!(app.state, AppState::Running));
+
+        for _ in 0..30 {
+            app.update(&mut cx, Event::Tick);
+        }
+        assert!(matches!(app.state, AppState::Paused));
+        assert_eq!(app.pause_when, None);
+        // Same generation as `GameOfLife::run_until` stops at
+        assert_eq!(app.game.generation, 16);
+    }
+
+    #[test]
+    fn tile_command() {
+        let mut cx = Context::default();
+        let mut app = App::new(Duration::from_millis(75));
+        let tile = Action::Tile {
+            rows: 2,
+            columns: 2,
+            gap: 1,
+        };
+        app.apply(&mut cx, tile.clone());
+        assert_eq!(app.pending_stamp, None);
+
+        let block: Board = "xx\nxx".parse().unwrap();
+        app.pending_stamp = Some(block.clone());
+        app.apply(&mut cx, tile);
+        assert_eq!(app.pending_stamp, Some(block.tile(2, 2, 1)));
+        assert_eq!(app.pending_stamp.as_ref().unwrap().population(), 16);
+        app.apply(
+            &mut cx,
+            Action::Tile {
+                rows: 1000,
+                columns: 1000,
+                gap: 0,
+            },
+        );
+        assert_eq!(app.pending_stamp.unwrap().population(), 16);
+    }
+
+    #[test]
+    fn label_placement() {
+        let mut cx = Context::default();
+        let mut app = App::new(Duration::from_millis(75));
+        app.board_area = Rect::new(10, 2, 20, 10);
+        app.apply(&mut cx, "label 0 0 origin".parse().unwrap());
+        app.apply(&mut cx, "label 7 0 edge".parse().unwrap());
+        app.apply(&mut cx, "label 100 0 far".parse().unwrap());
+        // The origin is at the center of the board area
+        assert_eq!(
+            app.visible_labels(),
+            [
+                (Rect::new(21, 7, 6, 1), "origin"),
+                (Rect::new(28, 7, 2, 1), "edge")
+            ]
+        );
+
+        // Panning brings the far one into view and pushes the others out
+        app.origin = Point::new(100, 3);
+        assert_eq!(app.visible_labels(), [(Rect::new(21, 10, 3, 1), "far")]);
+
+        app.apply(&mut cx, "label 100 0".parse().unwrap());
+        assert!(app.visible_labels().is_empty());
+        assert_eq!(app.labels.len(), 2);
+
+        // Coordinates are entered as displayed
+        app.y_axis = YAxis::Down;
+        app.apply(&mut cx, "label 0 -5 above".parse().unwrap());
+        assert_eq!(app.labels[2], (Point::new(0, 5), "above".to_owned()));
+    }
+
+    #[test]
+    fn time_lapse() {
+        let mut cx = Context::default();
+        let mut app = App::new(Duration::from_millis(75))
+            .board(".xx\nxx.\n.x.")
+            .unwrap();
+        app.apply(&mut cx, Action::TimeLapse(5));
+        app.state = AppState::Running;
+        app.update(&mut cx, Event::Tick);
+        assert_eq!(app.game.generation, 5);
+        app.update(&mut cx, Event::Tick);
+        assert_eq!(app.game.generation, 10);
+        // Every generation is still in history
+        assert_eq!(app.game.history.len(), 10);
+
+        // Stops short at an `until` target, as run_until does at generation 16
+        app.apply(&mut cx, Action::Until(PopulationTarget::Above(20)));
+        for _ in 0..3 {
+            app.update(&mut cx, Event::Tick);
+        }
+        assert_eq!(app.game.generation, 16);
+        assert!(matches!(app.state, AppState::Paused));
+
+        app.apply(&mut cx, Action::TimeLapse(MAX_TIME_LAPSE + 1));
+        assert_eq!(app.generations_per_tick, 5);
+    }
+
+    #[test]
+    fn time_lapse_population_cap() {
+        let mut cx = Context::default();
+        let mut app = App::new(Duration::from_millis(75)).board("xxx").unwrap();
+        app.game.board = (0..JUMP_POPULATION_CAP as i64 / 3 + 1)
+            .flat_map(|i| (0..3).map(move |x| Point::new(x, i * 4)))
+            .collect();
+        app.apply(&mut cx, Action::TimeLapse(MAX_TIME_LAPSE));
+        app.state = AppState::Running;
+        app.update(&mut cx, Event::Tick);
+        assert_eq!(app.game.generation, 1);
+        assert!(matches!(app.state, AppState::Paused));
+        assert!(app.status.starts_with("Time-lapse paused at generation 1"));
+    }
+
+    #[test]
+    fn cells_command() {
+        let mut cx = Context::default();
+        let mut app = App::new(Duration::from_millis(75));
+        let key = |c| Event::Key(KeyEvent::new(c, KeyModifiers::NONE));
+        for c in ":cells circles".chars() {
+            app.update(&mut cx, key(KeyCode::Char(c)));
+        }
+        app.update(&mut cx, key(KeyCode::Enter));
+        assert_eq!(app.cell_glyphs.alive, "●");
+        assert_eq!(app.status, "Drawing cells with Circles");
+    }
+
+    #[test]
+    fn input_during_slow_step() {
+        fn slow_step(board: &Board, rule: &rule::Rule) -> Board {
+            std::thread::sleep(Duration::from_millis(300));
+            GameOfLife::next_board(board, rule)
+        }
+
+        let listener = event::Listener::default();
+        let mut cx = Context::new(listener.subscribe());
+        let mut app = App::new(Duration::from_millis(75)).board("xxx").unwrap();
+        app.simulation = Some(Simulation::with_step(listener.subscribe(), slow_step));
+        app.state = AppState::Running;
+
+        let start = Instant::now();
+        app.update(&mut cx, Event::Tick);
+        app.update(
+            &mut cx,
+            Event::Key(KeyEvent::new(KeyCode::Char('l'), KeyModifiers::NONE)),
+        );
+        assert!(start.elapsed() < Duration::from_millis(300));
+        assert_eq!(app.origin, Point::new(1, 0));
+        assert_eq!(app.game.generation, 0);
+
+        // Ticks while the step is in flight don't queue more steps
+        app.update(&mut cx, Event::Tick);
+        let event = listener.next().unwrap();
+        assert!(matches!(event, Event::Board { generation: 1, .. }));
+        app.update(&mut cx, event);
+        assert_eq!(app.game.generation, 1);
+        assert_eq!(app.game.board.to_ascii(), "x\nx\nx\n");
+
+        // Results for a game that was reset in the meantime are dropped
+        app.update(&mut cx, Event::Tick);
+        app.game.restart("xx\nxx".parse().unwrap());
+        let event = listener.next().unwrap();
+        app.update(&mut cx, event);
+        assert_eq!(app.game.generation, 0);
+        assert_eq!(app.game.board.to_ascii(), "xx\nxx\n");
+    }
+
+    #[test]
+    fn record_and_replay() {
+        let mut cx = Context::default();
+        let mut app = App::new(Duration::from_millis(75))
+            .board(".x.\n..x\nxxx")
+            .unwrap();
+        app.board_area = Rect::new(0, 0, 10, 10);
+        app.recording = Some(Session::new(&app.game));
+        let click = |column| {
+            Event::Mouse(MouseEvent {
+                kind: MouseEventKind::Up(MouseButton::Left),
+                column,
+                row: 5,
+                modifiers: KeyModifiers::NONE,
+            })
+        };
+        let ticks = |app: &mut App, cx: &mut Context, n| {
+            for _ in 0..n {
+                app.update(cx, Event::Tick);
+            }
+        };
+
+        app.update(&mut cx, click(1));
+        app.state = AppState::Running;
+        ticks(&mut app, &mut cx, 4);
+        app.live_edit = true;
+        app.update(&mut cx, click(2));
+        app.apply(&mut cx, Action::Rule("B36/S23".parse().unwrap()));
+        ticks(&mut app, &mut cx, 5);
+        app.update(
+            &mut cx,
+            Event::Key(KeyEvent::new(KeyCode::Char('r'), KeyModifiers::NONE)),
+        );
+        app.state = AppState::Running;
+        ticks(&mut app, &mut cx, 3);
+
+        let mut session = app.recording.take().unwrap();
+        session.end = app.game.generation;
+        assert_eq!(session.entries.len(), 4);
+        let session: Session = session.to_string().parse().unwrap();
+        assert_eq!(session.replay().unwrap().board, app.game.board);
+
+        let mut replay = App::new(Duration::from_millis(75)).replaying(&session);
+        replay.state = AppState::Running;
+        ticks(&mut replay, &mut cx, 20);
+        assert!(replay.replay.is_none());
+        assert!(matches!(replay.state, AppState::Paused));
+        assert_eq!(replay.game.generation, 3);
+        assert_eq!(replay.game.board, app.game.board);
+    }
+
+    #[test]
+    fn live_edit_between_ticks() {
+        let listener = event::Listener::default();
+        let mut cx = Context::new(listener.subscribe());
+        let mut app = App::new(Duration::from_millis(75)).board("xx\nx.").unwrap();
+        app.board_area = Rect::new(0, 0, 10, 10);
+        app.simulation = Some(Simulation::spawn(listener.subscribe()));
+        app.state = AppState::Running;
+        let click = Event::Mouse(MouseEvent {
+            kind: MouseEventKind::Up(MouseButton::Left),
+            column: 6,
+            row: 5,
+            modifiers: KeyModifiers::NONE,
+        });
+
+        // Ignored while running until live editing is on
+        app.update(&mut cx, click.clone());
+        assert_eq!(app.game.board.population(), 3);
+        app.update(
+            &mut cx,
+            Event::Key(KeyEvent::new(KeyCode::Char('e'), KeyModifiers::NONE)),
+        );
+        assert!(app.live_edit);
+
+        // The step requested before the edit is dropped, and the next one includes it
+        app.update(&mut cx, Event::Tick);
+        app.update(&mut cx, click);
+        let edited = app.game.board.clone();
+        assert_eq!(edited.population(), 4);
+        let event = listener.next().unwrap();
+        app.update(&mut cx, event);
+        assert_eq!(app.game.generation, 0);
+        assert_eq!(app.game.board, edited);
+
+        app.update(&mut cx, Event::Tick);
+        let event = listener.next().unwrap();
+        app.update(&mut cx, event);
+        assert_eq!(app.game.generation, 1);
+        assert_eq!(
+            app.game.board,
+            GameOfLife::next_board(&edited, &app.game.rule)
+        );
+    }
+
+    #[test]
+    fn follow_glider() {
+        let mut cx = Context::default();
+        let mut app = App::new(Duration::from_millis(75))
+            .board(".x.\n..x\nxxx")
+            .unwrap();
+        app.follow = true;
+        app.state = AppState::Running;
+        for _ in 0..40 {
+            app.update(&mut cx, Event::Tick);
+            let center = app.game.board.center_of_mass().unwrap();
+            assert_eq!(app.origin, center);
+        }
+        assert_eq!(app.origin, Point::new(11, -10));
+    }
+}
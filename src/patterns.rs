@@ -0,0 +1,55 @@
+use crate::{board::Board, format};
+
+pub const QUEEN_BEE: &str = "xx..\nx.x.\n...x\nx..x\n...x\nx.x.\nxx..";
+
+/// Built-in patterns as `(name, pattern)` pairs. Patterns may be in any format understood by
+/// `format::parse`.
+pub const LIBRARY: &[(&str, &str)] = &[
+    ("Block", "xx\nxx"),
+    ("Beehive", ".xx.\nx..x\n.xx."),
+    ("Blinker", "xxx"),
+    (
+        "Glider",
+        "#N Glider\n#O Richard K. Guy\n#C The smallest and most common spaceship.\nx = 3, y = 3\nbo$2bo$3o!",
+    ),
+    ("LWSS", ".x..x\nx....\nx...x\nxxxx."),
+    ("R-pentomino", ".xx\nxx.\n.x."),
+    ("Acorn", ".x.....\n...x...\nxx..xxx"),
+    ("Queen bee", QUEEN_BEE),
+    (
+        "Pulsar",
+        "#N Pulsar\n#O John Conway\nx = 13, y = 13\n2b3o3b3o2b2$o4bobo4bo$o4bobo4bo$o4bobo4bo$2b3o3b3o2b2$2b3o3b3o2b$o4bobo4bo$o4bobo4bo$o4bobo4bo2$2b3o3b3o!",
+    ),
+    (
+        "Gosper glider gun",
+        "#N Gosper glider gun\n#O Bill Gosper\n#C The first known gun.\nx = 36, y = 9\n24bo$22bobo$12b2o6b2o12b2o$11bo3bo4b2o12b2o$2o8bo5bo3b2o$2o8bo3bob2o4bobo$10bo5bo7bo$11bo3bo$12b2o!",
+    ),
+];
+
+/// Looks up the source of a built-in pattern by name, ignoring case.
+pub fn source(name: &str) -> Option<&'static str> {
+    LIBRARY
+        .iter()
+        .find(|(n, _)| n.eq_ignore_ascii_case(name))
+        .map(|(_, s)| *s)
+}
+
+/// Looks up a built-in pattern by name, ignoring case.
+pub fn get(name: &str) -> Option<Board> {
+    source(name).map(|s| format::parse(s).expect("built-in patterns are valid"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn library_parses() {
+        for (name, s) in LIBRARY {
+            assert!(format::parse(s).is_ok(), "{} failed to parse", name);
+        }
+        assert_eq!(get("glider").unwrap().population(), 5);
+        assert_eq!(get("Pulsar").unwrap().population(), 48);
+        assert_eq!(get("gosper glider gun").unwrap().population(), 36);
+    }
+}
@@ -1,6 +1,33 @@
+/// A line to reflect across, positioned by the coordinate it passes through.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Axis {
+    /// A line of constant x
+    Vertical,
+    /// A line of constant y
+    Horizontal,
+}
 
+/// Which way y increases when showing coordinates. The world itself is always y-up, matching
+/// how patterns are written, so this only changes how points are presented.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum YAxis {
+    #[default]
+    Up,
+    /// Like screen coordinates, with y increasing downwards
+    Down,
+}
 
-#[derive(Debug, Default, PartialEq, Eq, Hash, Clone, Copy)]
+impl YAxis {
+    /// `p` as it should be shown to the user.
+    pub fn display(self, p: Point) -> Point {
+        match self {
+            YAxis::Up => p,
+            YAxis::Down => Point::new(p.x, p.y.saturating_neg()),
+        }
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
 pub struct Point {
     pub x: i64,
     pub y: i64,
@@ -70,11 +97,21 @@ impl Point {
         }
     }
 
+    /// Moves along x, stopping at the edge of the plane.
     pub fn dx(&mut self, x: i64) {
-        self.x += x;
+        self.x = self.x.saturating_add(x);
     }
 
+    /// Moves along y, stopping at the edge of the plane.
     pub fn dy(&mut self, y: i64) {
-        self.y += y;
+        self.y = self.y.saturating_add(y);
+    }
+
+    /// Reflects across the `axis` passing through `about`.
+    pub fn reflect(self, axis: Axis, about: i64) -> Self {
+        match axis {
+            Axis::Vertical => Point::new(2 * about - self.x, self.y),
+            Axis::Horizontal => Point::new(self.x, 2 * about - self.y),
+        }
     }
 }
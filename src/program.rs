@@ -1,7 +1,8 @@
 use std::{
     fmt::{Debug, Display},
-    io,
-    time::Duration,
+    io::{self, IsTerminal},
+    sync::{Arc, Mutex, PoisonError},
+    time::{Duration, Instant},
 };
 
 use crossterm::{
@@ -11,7 +12,8 @@ use crossterm::{
 };
 use tui::backend::CrosstermBackend;
 
-use crate::event::{Event, IoProducer, Listener, Timer};
+use crate::event::{Event, EventSender, IoProducer, Listener, Scheduler};
+use crate::game::{GameOfLife, StepReport};
 use crate::{model::Model, terminal::Terminal};
 
 // TODO: Timer commands
@@ -20,13 +22,79 @@ use crate::{model::Model, terminal::Terminal};
 // logic is maintained by Program.
 pub struct Program {
     tick_rate: Duration,
+    max_fps: Option<u32>,
+    listener: Listener,
+    games: Vec<SharedGame>,
 }
 
+/// A game stepped by the event loop, shared with whatever models show it, see `Program::game`.
+pub type SharedGame = Arc<Mutex<GameOfLife>>;
+
+/// Steps each of `games` once, returning their reports in the same order. Every game is locked
+/// before any is stepped, so nothing sees one a generation ahead of the others.
+pub fn step_lockstep(games: &[SharedGame]) -> Vec<StepReport> {
+    let mut locked: Vec<_> = games
+        .iter()
+        .map(|game| game.lock().unwrap_or_else(PoisonError::into_inner))
+        .collect();
+    locked.iter_mut().map(|game| game.step()).collect()
+}
+
+/// Decides when to draw so frames are at least `1 / max_fps` apart by the wall clock. Skipped
+/// draws aren't lost: the next allowed one shows everything since.
+#[derive(Debug)]
+pub struct RenderThrottle {
+    min_interval: Duration,
+    last: Option<Instant>,
+    /// A draw was skipped, so the screen is behind
+    pending: bool,
+}
+
+impl RenderThrottle {
+    pub fn new(max_fps: Option<u32>) -> Self {
+        RenderThrottle {
+            min_interval: max_fps
+                .filter(|&fps| fps > 0)
+                .map_or(Duration::ZERO, |fps| Duration::from_secs(1) / fps),
+            last: None,
+            pending: false,
+        }
+    }
+
+    /// Whether to draw at `now`, recording the draw if so.
+    pub fn should_render(&mut self, now: Instant) -> bool {
+        let due = self
+            .last
+            .is_none_or(|last| now.duration_since(last) >= self.min_interval);
+        if due {
+            self.last = Some(now);
+            self.pending = false;
+        } else {
+            self.pending = true;
+        }
+        due
+    }
+
+    pub fn limited(&self) -> bool {
+        self.min_interval > Duration::ZERO
+    }
+
+    /// Whether a skipped draw is still owed.
+    pub fn pending(&self) -> bool {
+        self.pending
+    }
+}
+
+/// How long `Program::run_headless` waits for another event before deciding it's done
+pub const HEADLESS_IDLE_TIMEOUT: Duration = Duration::from_millis(500);
+
 type ComponentId = u64;
 
 #[derive(Debug, Clone)]
 pub enum Command {
     SetTickRate(Duration),
+    /// Turn mouse reporting on or off. Off lets the terminal select text natively.
+    SetMouseCapture(bool),
     Exit,
 }
 
@@ -34,12 +102,19 @@ pub enum Command {
 pub enum ProgramError {
     Io(io::Error),
     EventRecv,
+    /// Output is redirected, so there's no terminal to draw on
+    NotATerminal,
 }
 
 impl Display for ProgramError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        // TODO: better impl
-        Debug::fmt(&self, f)
+        match self {
+            ProgramError::NotATerminal => {
+                f.write_str("Output is not a terminal. Use --print to run without the interface.")
+            }
+            // TODO: better impl
+            e => Debug::fmt(e, f),
+        }
     }
 }
 
@@ -48,12 +123,30 @@ impl std::error::Error for ProgramError {}
 #[derive(Debug, Default)]
 pub struct Context {
     cmds: Vec<Command>,
+    sender: Option<EventSender>,
 }
 
 impl Context {
+    pub fn new(sender: EventSender) -> Self {
+        Self {
+            cmds: Vec::new(),
+            sender: Some(sender),
+        }
+    }
+
     pub fn run(&mut self, cmd: Command) {
         self.cmds.push(cmd);
     }
+
+    /// Commands run since the event loop last carried them out.
+    pub fn commands(&self) -> &[Command] {
+        &self.cmds
+    }
+
+    /// Returns a sender for feeding events back into the event loop, if there is one.
+    pub fn subscribe(&self) -> Option<EventSender> {
+        self.sender.clone()
+    }
 }
 
 impl From<io::Error> for ProgramError {
@@ -73,23 +166,66 @@ pub type ProgramResult = Result<(), ProgramError>;
 impl Program {
     pub fn new() -> Self {
         Self {
-            tick_rate: Duration::from_millis(15),
+            tick_rate: Duration::from_millis(50),
+            max_fps: None,
+            listener: Listener::default(),
+            games: Vec::new(),
+        }
+    }
+
+    /// Registers `game` to be stepped by the event loop on every `Event::Tick`, in lockstep with
+    /// the others registered, instead of by the model. Each tick is followed by an
+    /// `Event::Stepped` with their reports.
+    pub fn game(mut self, game: SharedGame) -> Self {
+        self.games.push(game);
+        self
+    }
+
+    /// Hands `event` to `model`, or carries it out if it's a command, stepping the registered
+    /// games on ticks.
+    fn dispatch<M: Model>(&self, cx: &mut Context, model: &mut M, event: Event) {
+        match event {
+            Event::Command(cmd) => cx.run(cmd),
+            Event::Tick if !self.games.is_empty() => {
+                model.update(cx, Event::Tick);
+                let reports = step_lockstep(&self.games);
+                model.update(cx, Event::Stepped { reports });
+            }
+            event => model.update(cx, event),
         }
     }
 
+    /// Returns a sender for injecting events into the event loop before it runs, e.g. from tests
+    /// or a remote controller.
+    pub fn sender(&self) -> EventSender {
+        self.listener.subscribe()
+    }
+
+    /// Caps how often the terminal is redrawn, for slow connections. Unlimited by default.
+    pub fn max_fps(mut self, max_fps: Option<u32>) -> Self {
+        self.max_fps = max_fps;
+        self
+    }
+
+    /// How often `Event::Tick` is sent until the model changes it with `Command::SetTickRate`.
     pub fn tick(mut self, tick_rate: Duration) -> Self {
         self.tick_rate = tick_rate;
         self
     }
 
-    pub fn run<M: Model>(mut self, mut model: M) -> ProgramResult {
+    /// Takes over the terminal and runs `model` until it exits. Fails with
+    /// `ProgramError::NotATerminal` before touching anything if stdout is redirected.
+    pub fn run<M: Model>(mut self, model: &mut M) -> ProgramResult {
         let mut stdout = io::stdout();
+        if !stdout.is_terminal() {
+            return Err(ProgramError::NotATerminal);
+        }
         enable_raw_mode()?;
         execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
         let backend = CrosstermBackend::new(stdout);
         let mut terminal = Terminal::new(backend)?;
 
-        let ret = self.run_event_loop(&mut terminal, &mut model);
+        let ret = self.run_event_loop(&mut terminal, model);
 
         disable_raw_mode()?;
         execute!(
@@ -101,44 +237,253 @@ impl Program {
         ret
     }
 
+    /// Runs `model` on injected events alone, without a terminal, input, timers or drawing, until
+    /// it or an injected `Event::Command` exits, or no event has come for `HEADLESS_IDLE_TIMEOUT`.
+    /// Workers the model starts, like jumps, keep it going while they report back.
+    pub fn run_headless<M: Model>(self, model: &mut M) -> ProgramResult {
+        let mut cx = Context::new(self.listener.subscribe());
+        loop {
+            let Ok(event) = self.listener.next_timeout(HEADLESS_IDLE_TIMEOUT) else {
+                return Ok(());
+            };
+            self.dispatch(&mut cx, model, event);
+            if cx.cmds.iter().any(|cmd| matches!(cmd, Command::Exit)) {
+                return Ok(());
+            }
+            cx.cmds.clear();
+        }
+    }
+
     fn run_event_loop<M: Model>(
         &mut self,
         terminal: &mut Terminal,
         model: &mut M,
     ) -> ProgramResult {
-        let mut cx = Context::default();
-        let listener: Listener = Listener::default();
+        let listener = &self.listener;
+        let mut cx = Context::new(listener.subscribe());
         let _io_producer = IoProducer::spawn(listener.subscribe());
-        let tick_producer =
-            Timer::spawn(listener.subscribe(), Duration::from_millis(50), Event::Tick);
-        let _render_tick_producer = Timer::spawn(
-            listener.subscribe(),
-            Duration::from_millis(15),
-            Event::Render,
+        let scheduler = Scheduler::spawn(listener.subscribe());
+        let tick_timer = scheduler.add(self.tick_rate, Event::Tick);
+        scheduler.add(Duration::from_millis(15), Event::Render);
+
+        // Let the model lay itself out before any input arrives
+        let size = terminal.size()?;
+        model.update(
+            &mut cx,
+            Event::Resize {
+                width: size.width,
+                height: size.height,
+            },
         );
 
-        let execute_cmd = |cmd: &Command| {
-            if let Command::SetTickRate(dur) = cmd {
-                tick_producer.set_period(*dur);
+        let execute_cmd = |terminal: &mut Terminal, cmd: &Command| -> io::Result<()> {
+            match cmd {
+                Command::SetTickRate(dur) => scheduler.reschedule(tick_timer, *dur),
+                Command::SetMouseCapture(on) => set_mouse_capture(terminal.backend_mut(), *on)?,
+                Command::Exit => (),
             }
+            Ok(())
         };
 
+        let mut throttle = RenderThrottle::new(self.max_fps);
         loop {
             let event = listener.next()?;
-            model.update(&mut cx, event);
+            // With a cap, render events only flush draws the throttle held back
+            let flush = matches!(event, Event::Render) && throttle.limited();
+            self.dispatch(&mut cx, model, event);
             for cmd in &cx.cmds {
                 match cmd {
                     Command::Exit => return Ok(()),
-                    cmd => execute_cmd(cmd),
+                    cmd => execute_cmd(terminal, cmd)?,
                 }
             }
+            cx.cmds.clear();
+            if (flush && !throttle.pending()) || !throttle.should_render(Instant::now()) {
+                continue;
+            }
             terminal.draw(|f| model.view(&mut cx, f))?;
             for cmd in &cx.cmds {
                 match cmd {
                     Command::Exit => return Ok(()),
-                    cmd => execute_cmd(cmd),
+                    cmd => execute_cmd(terminal, cmd)?,
+                }
+            }
+            cx.cmds.clear();
+        }
+    }
+}
+
+/// Writes the escape codes turning mouse reporting on or off to `out`.
+fn set_mouse_capture(out: &mut impl io::Write, on: bool) -> io::Result<()> {
+    if on {
+        execute!(out, EnableMouseCapture)
+    } else {
+        execute!(out, DisableMouseCapture)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    struct Noop;
+
+    impl Model for Noop {
+        fn update(&mut self, _cx: &mut Context, _event: Event) {}
+        fn view(&mut self, _cx: &mut Context, _f: &mut crate::terminal::Frame) {}
+    }
+
+    #[test]
+    fn refuses_redirected_output() {
+        // Only checkable when the tests' own output is redirected, as it is under CI
+        if io::stdout().is_terminal() {
+            return;
+        }
+        let e = Program::new().run(&mut Noop).unwrap_err();
+        assert!(matches!(e, ProgramError::NotATerminal));
+        assert!(e.to_string().contains("--print"));
+    }
+
+    /// Remembers the keys it's sent, exiting on `q`.
+    #[derive(Default)]
+    struct Keys(String);
+
+    impl Model for Keys {
+        fn update(&mut self, cx: &mut Context, event: Event) {
+            if let Event::Key(key) = event {
+                if let crossterm::event::KeyCode::Char(c) = key.code {
+                    self.0.push(c);
+                    if c == 'q' {
+                        cx.run(Command::Exit);
+                    }
                 }
             }
         }
+        fn view(&mut self, _cx: &mut Context, _f: &mut crate::terminal::Frame) {}
+    }
+
+    #[test]
+    fn injected_events() {
+        use crossterm::event::{KeyCode, KeyEvent};
+
+        let program = Program::new();
+        let sender = program.sender();
+        for c in "abc".chars() {
+            sender
+                .send(KeyEvent::from(KeyCode::Char(c)).into())
+                .unwrap();
+        }
+        sender.send(Event::Command(Command::Exit)).unwrap();
+        sender
+            .send(KeyEvent::from(KeyCode::Char('d')).into())
+            .unwrap();
+        let mut keys = Keys::default();
+        program.run_headless(&mut keys).unwrap();
+        assert_eq!(keys.0, "abc");
+
+        // The model can exit too
+        let program = Program::new();
+        for c in "xqz".chars() {
+            let key = KeyEvent::from(KeyCode::Char(c));
+            program.sender().send(key.into()).unwrap();
+        }
+        let mut keys = Keys::default();
+        program.run_headless(&mut keys).unwrap();
+        assert_eq!(keys.0, "xq");
+    }
+
+    /// Keeps the reports of each lockstep tick.
+    #[derive(Default)]
+    struct Reports(Vec<Vec<StepReport>>);
+
+    impl Model for Reports {
+        fn update(&mut self, _cx: &mut Context, event: Event) {
+            if let Event::Stepped { reports } = event {
+                self.0.push(reports);
+            }
+        }
+        fn view(&mut self, _cx: &mut Context, _f: &mut crate::terminal::Frame) {}
+    }
+
+    #[test]
+    fn lockstep_ticks() {
+        let conway: GameOfLife = "...\nxxx\n...".parse().unwrap();
+        let mut highlife: GameOfLife = "xxx\nx..\nx..".parse().unwrap();
+        highlife.rule = "B36/S23".parse().unwrap();
+        let games = [conway.clone(), highlife.clone()].map(|game| Arc::new(Mutex::new(game)));
+
+        let program = Program::new().game(games[0].clone()).game(games[1].clone());
+        for _ in 0..3 {
+            program.sender().send(Event::Tick).unwrap();
+        }
+        program
+            .sender()
+            .send(Event::Command(Command::Exit))
+            .unwrap();
+        let mut reports = Reports::default();
+        program.run_headless(&mut reports).unwrap();
+
+        // Both advance exactly one generation per tick, each under its own rule
+        assert_eq!(reports.0.len(), 3);
+        for (tick, reports) in (1..).zip(&reports.0) {
+            assert_eq!(reports.len(), 2);
+            assert!(reports.iter().all(|report| report.generation == tick));
+        }
+        for (game, mut expected) in games.iter().zip([conway, highlife]) {
+            for _ in 0..3 {
+                expected.step();
+            }
+            let game = game.lock().unwrap();
+            assert_eq!(game.generation, 3);
+            assert_eq!(game.board, expected.board);
+        }
+        assert_ne!(
+            games[0].lock().unwrap().board,
+            games[1].lock().unwrap().board
+        );
+    }
+
+    #[test]
+    fn mouse_capture_commands() {
+        let written = |on| {
+            let mut out = Vec::new();
+            set_mouse_capture(&mut out, on).unwrap();
+            String::from_utf8(out).unwrap()
+        };
+        let ansi = |command: &dyn Fn(&mut String) -> std::fmt::Result| {
+            let mut s = String::new();
+            command(&mut s).unwrap();
+            s
+        };
+        use crossterm::Command as _;
+        assert_eq!(written(true), ansi(&|s| EnableMouseCapture.write_ansi(s)));
+        assert_eq!(written(false), ansi(&|s| DisableMouseCapture.write_ansi(s)));
+        assert_ne!(written(true), written(false));
+    }
+
+    #[test]
+    fn throttle_skips_early_renders() {
+        let start = Instant::now();
+        let ms = Duration::from_millis;
+        let mut throttle = RenderThrottle::new(Some(20));
+        assert!(throttle.limited());
+        assert!(throttle.should_render(start));
+        assert!(!throttle.pending());
+
+        // 20 fps allows a frame every 50ms
+        assert!(!throttle.should_render(start + ms(10)));
+        assert!(throttle.pending());
+        assert!(!throttle.should_render(start + ms(49)));
+        assert!(throttle.should_render(start + ms(50)));
+        assert!(!throttle.pending());
+        assert!(!throttle.should_render(start + ms(60)));
+        assert!(throttle.should_render(start + ms(200)));
+
+        for max_fps in [None, Some(0)] {
+            let mut unlimited = RenderThrottle::new(max_fps);
+            assert!(!unlimited.limited());
+            assert!(unlimited.should_render(start));
+            assert!(unlimited.should_render(start));
+        }
     }
 }
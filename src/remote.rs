@@ -0,0 +1,220 @@
+use std::{io, path::PathBuf, sync::Arc, thread::JoinHandle};
+
+use crate::{
+    action::Action,
+    event::{Event, EventSender},
+    format,
+};
+
+/// Stands in for a file path in the status line for patterns loaded remotely
+pub const REMOTE_PATH: &str = "remote pattern";
+
+/// Longest request line accepted, in bytes
+pub const MAX_LINE: usize = 4096;
+
+/// Most bytes of RLE a single `load` may send
+pub const MAX_LOAD_BYTES: usize = 1 << 20;
+
+/// Host bound to when `--listen` is given just a port, so nothing outside this machine can
+/// connect unless asked for
+pub const DEFAULT_HOST: &str = "127.0.0.1";
+
+/// `addr` with `DEFAULT_HOST` filled in if it's only a port, e.g. `7000` or `:7000`.
+pub fn with_default_host(addr: &str) -> String {
+    match addr.strip_prefix(':').unwrap_or(addr).parse::<u16>() {
+        Ok(port) => format!("{}:{}", DEFAULT_HOST, port),
+        Err(_) => addr.to_owned(),
+    }
+}
+
+/// Whether `rle` has reached its closing `!`, which comments don't count for.
+fn complete(rle: &str) -> bool {
+    rle.lines()
+        .any(|line| !line.trim_start().starts_with('#') && line.contains('!'))
+}
+
+/// Reads the next request from `lines`, returning the event to inject for it or why it's
+/// invalid, or `None` once the lines run out. Requests are one per line:
+///
+/// - `load <rle>` replaces the board with an RLE pattern, which carries on over the following
+///   lines up to its closing `!`
+/// - `step`, `play` or `pause`, as typed after `:`
+///
+/// Nothing else is accepted, since anyone who can connect can send requests and other commands
+/// touch files. Lines over `MAX_LINE` and patterns over `MAX_LOAD_BYTES` are rejected.
+pub fn read_request(lines: &mut impl Iterator<Item = String>) -> Option<Result<Event, String>> {
+    let line = lines.find(|line| !line.trim().is_empty())?;
+    if line.len() > MAX_LINE {
+        return Some(Err(format!("Line longer than {} bytes", MAX_LINE)));
+    }
+    let Some(rle) = line.trim().strip_prefix("load ") else {
+        return Some(match line.parse::<Action>() {
+            Ok(action @ (Action::Step | Action::Play | Action::Pause)) => Ok(Event::Action(action)),
+            Ok(action) => Err(format!("`{}` isn't allowed remotely", action)),
+            Err(e) => Err(e.to_string()),
+        });
+    };
+    let mut rle = rle.to_owned();
+    while !complete(&rle) {
+        let Some(line) = lines.next() else {
+            break;
+        };
+        if line.len() > MAX_LINE || rle.len() + line.len() >= MAX_LOAD_BYTES {
+            // Drop the rest of the pattern so it isn't read as more requests
+            if !complete(&line) {
+                lines.find(|line| complete(line));
+            }
+            return Some(Err(format!("Pattern longer than {} bytes", MAX_LOAD_BYTES)));
+        }
+        rle.push('\n');
+        rle.push_str(&line);
+    }
+    Some(
+        format::rle::parse(&rle)
+            .map(|board| Event::BoardLoaded {
+                path: PathBuf::from(REMOTE_PATH),
+                source: rle.into(),
+                board: Arc::new(board),
+            })
+            .map_err(|e| e.to_string()),
+    )
+}
+
+/// Listens on `addr` for connections sending requests as read by `read_request`, injecting them
+/// into the program through `sender`. Each request is answered with `ok` or `error: <reason>`.
+#[cfg(feature = "remote")]
+pub fn serve(addr: &str, sender: EventSender) -> io::Result<JoinHandle<()>> {
+    use std::{net::TcpListener, thread};
+
+    let listener = TcpListener::bind(with_default_host(addr))?;
+    Ok(thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let sender = sender.clone();
+            thread::spawn(move || handle(stream, sender));
+        }
+    }))
+}
+
+#[cfg(feature = "remote")]
+fn handle(stream: std::net::TcpStream, sender: EventSender) -> io::Result<()> {
+    use std::io::{BufRead, BufReader, Read, Write};
+
+    let mut reply = stream.try_clone()?;
+    let mut reader = BufReader::new(stream);
+    // Reads at most a byte over `MAX_LINE` of each line so a client can't exhaust memory
+    let mut lines = std::iter::from_fn(|| {
+        let mut line = Vec::new();
+        let read = (&mut reader)
+            .take(MAX_LINE as u64 + 1)
+            .read_until(b'\n', &mut line)
+            .ok()?;
+        if read == 0 {
+            return None;
+        }
+        if line.last() != Some(&b'\n') && line.len() > MAX_LINE {
+            // Skip the rest of the overlong line
+            let mut rest = Vec::new();
+            while (&mut reader)
+                .take(MAX_LINE as u64)
+                .read_until(b'\n', &mut rest)
+                .is_ok_and(|n| n > 0 && rest.last() != Some(&b'\n'))
+            {
+                rest.clear();
+            }
+        }
+        let line = String::from_utf8_lossy(&line);
+        Some(line.trim_end_matches(['\r', '\n']).to_owned())
+    });
+    while let Some(request) = read_request(&mut lines) {
+        match request {
+            Ok(event) => {
+                if sender.send(event).is_err() {
+                    // The program exited
+                    return Ok(());
+                }
+                writeln!(reply, "ok")?;
+            }
+            Err(e) => writeln!(reply, "error: {}", e)?,
+        }
+    }
+    Ok(())
+}
+
+/// Stand-in for `serve` in builds without the `remote` feature.
+#[cfg(not(feature = "remote"))]
+pub fn serve(_addr: &str, _sender: EventSender) -> io::Result<JoinHandle<()>> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "Remote control needs a build with the `remote` feature",
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn requests(s: &str) -> Vec<Result<Event, String>> {
+        let mut lines = s.lines().map(str::to_owned);
+        std::iter::from_fn(|| read_request(&mut lines)).collect()
+    }
+
+    #[test]
+    fn protocol_lines() {
+        let requests = requests(
+            "step\n\nplay\npause\nload #C a glider!\nx = 3, y = 3\nbo$2bo$\n3o!\nbogus\n\
+             export /tmp/x.rle\nload x = 1",
+        );
+        assert_eq!(requests.len(), 7);
+        assert!(matches!(requests[0], Ok(Event::Action(Action::Step))));
+        assert!(matches!(requests[1], Ok(Event::Action(Action::Play))));
+        assert!(matches!(requests[2], Ok(Event::Action(Action::Pause))));
+        let Ok(Event::BoardLoaded { source, board, .. }) = &requests[3] else {
+            panic!("expected a loaded board, got {:?}", requests[3]);
+        };
+        assert_eq!(board.population(), 5);
+        assert!(source.ends_with("3o!"));
+        assert_eq!(requests[4].as_ref().unwrap_err(), "Unknown command `bogus`");
+        assert_eq!(
+            requests[5].as_ref().unwrap_err(),
+            "`export /tmp/x.rle` isn't allowed remotely"
+        );
+        // Runs out before the closing `!`
+        assert!(requests[6].is_err());
+    }
+
+    #[test]
+    fn limits() {
+        let long = format!("{}\nstep", "s".repeat(MAX_LINE + 1));
+        let long = requests(&long);
+        assert_eq!(long.len(), 2);
+        assert!(long[0].as_ref().unwrap_err().starts_with("Line longer"));
+        assert!(matches!(long[1], Ok(Event::Action(Action::Step))));
+
+        let row = format!("{}$", "o".repeat(MAX_LINE - 1));
+        let huge = format!(
+            "load x = 1, y = 1\n{}\n!\npause",
+            vec![row.clone(); MAX_LOAD_BYTES / MAX_LINE + 1].join("\n")
+        );
+        let huge = requests(&huge);
+        assert_eq!(huge.len(), 2);
+        assert!(huge[0].as_ref().unwrap_err().starts_with("Pattern longer"));
+        assert!(matches!(huge[1], Ok(Event::Action(Action::Pause))));
+        // The line going over ends the pattern, so the next request is read as usual
+        let ending = format!(
+            "load x = 1, y = 1\n{}\n{}!\npause",
+            vec![row; MAX_LOAD_BYTES / MAX_LINE - 1].join("\n"),
+            "o".repeat(MAX_LINE - 1)
+        );
+        let ending = requests(&ending);
+        assert_eq!(ending.len(), 2);
+        assert!(ending[0]
+            .as_ref()
+            .unwrap_err()
+            .starts_with("Pattern longer"));
+        assert!(matches!(ending[1], Ok(Event::Action(Action::Pause))));
+
+        assert_eq!(with_default_host("7000"), "127.0.0.1:7000");
+        assert_eq!(with_default_host(":7000"), "127.0.0.1:7000");
+        assert_eq!(with_default_host("0.0.0.0:7000"), "0.0.0.0:7000");
+    }
+}
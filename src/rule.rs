@@ -0,0 +1,262 @@
+use std::{fmt::Display, str::FromStr};
+
+use crate::game::{GameError, GameErrorKind};
+
+/// What happens to a cell in the next generation.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Survive,
+    Die,
+    Birth,
+    StayDead,
+}
+
+impl Display for Outcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Outcome::Survive => "survive",
+            Outcome::Die => "die",
+            Outcome::Birth => "birth",
+            Outcome::StayDead => "stay dead",
+        })
+    }
+}
+
+/// Outer-totalistic rule in B/S notation, e.g. `B3/S23` for Conway's Game of Life. Bit `n` of
+/// `birth`/`survive` is set when a cell with `n` live neighbors is born/survives.
+///
+/// Rules from the Generations family, e.g. `B2/S/3` for Brian's Brain, have more than two
+/// `states`. Cells that don't survive decay through the extra states before dying, during which
+/// they neither count as live neighbors nor can be born into.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Rule {
+    birth: u16,
+    survive: u16,
+    states: u8,
+}
+
+impl Default for Rule {
+    fn default() -> Self {
+        Rule::CONWAY
+    }
+}
+
+/// Well known rules as `(name, rule)` pairs.
+pub const PRESETS: &[(&str, &str)] = &[
+    ("Conway", "B3/S23"),
+    ("HighLife", "B36/S23"),
+    ("Day & Night", "B3678/S34678"),
+    ("Seeds", "B2/S"),
+    ("Replicator", "B1357/S1357"),
+    ("Life without Death", "B3/S012345678"),
+    ("Brian's Brain", "B2/S/3"),
+    ("Star Wars", "B2/S345/4"),
+];
+
+impl Rule {
+    /// Looks up a rule from `PRESETS` by name, ignoring case.
+    pub fn preset(name: &str) -> Option<Rule> {
+        PRESETS
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, rule)| rule.parse().expect("presets are valid"))
+    }
+
+    pub const CONWAY: Rule = Rule {
+        birth: 1 << 3,
+        survive: 1 << 2 | 1 << 3,
+        states: 2,
+    };
+
+    /// Number of cell states including dead and alive, which is 2 outside of Generations rules.
+    pub fn states(&self) -> u8 {
+        self.states
+    }
+
+    pub fn births(&self, live_neighbors: u8) -> bool {
+        self.birth & (1 << live_neighbors) != 0
+    }
+
+    pub fn survives(&self, live_neighbors: u8) -> bool {
+        self.survive & (1 << live_neighbors) != 0
+    }
+
+    /// Whether the center of each 3×3 neighborhood is alive next generation, indexed by the
+    /// neighborhood as a bitmask: bits 0-2 are the left column top to bottom, 3-5 the middle
+    /// (the center cell is bit 4) and 6-8 the right. Only meaningful for two-state rules.
+    pub fn lookup_table(&self) -> [bool; 512] {
+        std::array::from_fn(|mask| {
+            let alive = mask & (1 << 4) != 0;
+            let live_neighbors = (mask & !(1 << 4)).count_ones() as u8;
+            if alive {
+                self.survives(live_neighbors)
+            } else {
+                self.births(live_neighbors)
+            }
+        })
+    }
+
+    pub fn outcome(&self, alive: bool, live_neighbors: u8) -> Outcome {
+        match (
+            alive,
+            alive && self.survives(live_neighbors) || !alive && self.births(live_neighbors),
+        ) {
+            (true, true) => Outcome::Survive,
+            (true, false) => Outcome::Die,
+            (false, true) => Outcome::Birth,
+            (false, false) => Outcome::StayDead,
+        }
+    }
+}
+
+/// A rule where cells are born or survive with a probability depending on their number of live
+/// neighbors, indexed by that number. See `GameOfLife::stochastic`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct StochasticRule {
+    pub birth: [f64; 9],
+    pub survive: [f64; 9],
+}
+
+impl From<Rule> for StochasticRule {
+    /// The rule that always does what `rule` does.
+    fn from(rule: Rule) -> Self {
+        let probabilities = |mask: u16| std::array::from_fn(|n| ((mask >> n) & 1) as f64);
+        StochasticRule {
+            birth: probabilities(rule.birth),
+            survive: probabilities(rule.survive),
+        }
+    }
+}
+
+fn digits(s: &str) -> Option<u16> {
+    s.chars().try_fold(0, |mask, c| match c.to_digit(10) {
+        Some(n @ 0..=8) => Some(mask | 1 << n),
+        _ => None,
+    })
+}
+
+/// Parses `B3/S23` notation (case insensitive, either order) or the older `23/3` S/B notation,
+/// optionally followed by a number of states for Generations rules, e.g. `B2/S/3`, `B2/S/G3` or
+/// `/2/C3`.
+/// Rules with `B0` are rejected since the board can't represent infinitely many births.
+impl FromStr for Rule {
+    type Err = GameError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || GameError::new(GameErrorKind::InvalidRule { s: s.to_owned() });
+        let mut parts = s.trim().split('/');
+        let (a, b) = match (parts.next(), parts.next()) {
+            (Some(a), Some(b)) => (a, b),
+            _ => return Err(err()),
+        };
+        let states = match parts.next() {
+            None => 2,
+            Some(states) => states
+                .trim_start_matches(['C', 'c', 'G', 'g'])
+                .parse()
+                .ok()
+                .filter(|&states| states >= 2)
+                .ok_or_else(err)?,
+        };
+        if parts.next().is_some() {
+            return Err(err());
+        }
+        let (birth, survive) = match (a.chars().next(), b.chars().next()) {
+            (Some('B' | 'b'), Some('S' | 's')) => (digits(&a[1..]), digits(&b[1..])),
+            (Some('S' | 's'), Some('B' | 'b')) => (digits(&b[1..]), digits(&a[1..])),
+            _ => (digits(b), digits(a)),
+        };
+        match (birth, survive) {
+            (Some(birth), Some(survive)) if birth & 1 == 0 => Ok(Rule {
+                birth,
+                survive,
+                states,
+            }),
+            _ => Err(err()),
+        }
+    }
+}
+
+impl Display for Rule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let digits = |mask: u16| -> String {
+            (0..=8)
+                .filter(|n| mask & (1 << n) != 0)
+                .map(|n| char::from(b'0' + n as u8))
+                .collect()
+        };
+        f.write_fmt(format_args!(
+            "B{}/S{}",
+            digits(self.birth),
+            digits(self.survive)
+        ))?;
+        if self.states > 2 {
+            f.write_fmt(format_args!("/{}", self.states))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn conway_outcomes() {
+        let rule = Rule::default();
+        for n in 0..=8 {
+            let alive = match n {
+                2 | 3 => Outcome::Survive,
+                _ => Outcome::Die,
+            };
+            let dead = match n {
+                3 => Outcome::Birth,
+                _ => Outcome::StayDead,
+            };
+            assert_eq!(rule.outcome(true, n), alive, "alive with {}", n);
+            assert_eq!(rule.outcome(false, n), dead, "dead with {}", n);
+        }
+    }
+
+    #[test]
+    fn presets() {
+        for (name, rule) in PRESETS {
+            assert!(rule.parse::<Rule>().is_ok(), "{} is invalid", name);
+        }
+        assert_eq!(Rule::preset("conway"), Some(Rule::CONWAY));
+        assert_eq!(Rule::preset("Seeds").unwrap().to_string(), "B2/S");
+        assert_eq!(Rule::preset("Brian's Brain").unwrap().states(), 3);
+        assert_eq!(Rule::preset("Wireworld"), None);
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!("B3/S23".parse::<Rule>().unwrap(), Rule::CONWAY);
+        assert_eq!("s23/b3".parse::<Rule>().unwrap(), Rule::CONWAY);
+        assert_eq!("23/3".parse::<Rule>().unwrap(), Rule::CONWAY);
+        assert_eq!("B36/S23".parse::<Rule>().unwrap().to_string(), "B36/S23");
+        assert_eq!("B2/S".parse::<Rule>().unwrap().to_string(), "B2/S");
+        assert_eq!("b2/s/c3".parse::<Rule>().unwrap().to_string(), "B2/S/3");
+        assert_eq!("/2/3".parse::<Rule>().unwrap().to_string(), "B2/S/3");
+        assert_eq!("B2/S/G3".parse::<Rule>().unwrap().to_string(), "B2/S/3");
+        assert_eq!("B3/S23/2".parse::<Rule>().unwrap(), Rule::CONWAY);
+        assert!("B2/S/1".parse::<Rule>().is_err());
+        assert!("B2/S/3/4".parse::<Rule>().is_err());
+        assert!("B9/S23".parse::<Rule>().is_err());
+        assert!("B03/S23".parse::<Rule>().is_err());
+        assert!("Conway".parse::<Rule>().is_err());
+    }
+
+    #[test]
+    fn lookup_table() {
+        let table = Rule::CONWAY.lookup_table();
+        // Dead center with three neighbors is born, with two it stays dead
+        assert!(table[0b000_000_111]);
+        assert!(!table[0b000_000_011]);
+        // Live center survives with two neighbors and dies alone or crowded
+        assert!(table[0b001_010_001]);
+        assert!(!table[0b000_010_000]);
+        assert!(!table[0b111_010_111]);
+        assert_eq!(table.iter().filter(|&&alive| alive).count(), 140);
+    }
+}
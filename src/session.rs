@@ -0,0 +1,311 @@
+use std::{collections::VecDeque, fmt::Display, path::Path, str::FromStr};
+
+use crate::{
+    action::Action,
+    board::Board,
+    game::{Diff, GameError, GameOfLife},
+    point::Point,
+    rule::Rule,
+};
+
+/// First line of a session file
+const HEADER: &str = "#session";
+
+/// Something done to the game that stepping alone can't reproduce.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Entry {
+    /// A command from the command input
+    Action(Action),
+    /// Cells drawn or erased, including undo and redo
+    Edit(Diff),
+    /// Back to generation 0 with this board, from loading a pattern or resetting
+    Restart(Board),
+}
+
+/// A recorded run: the starting board and rule, and everything done to the game along with the
+/// generation it happened at, ending at generation `end`. Replaying it reproduces the run exactly.
+///
+/// Sessions are saved as text, one line per entry:
+///
+/// ```text
+/// #session
+/// rule B3/S23
+/// board 0,0 1,0 2,0
+/// end 40
+/// @3 edit +5,5 -0,0
+/// @12 rule B36/S23
+/// ```
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Session {
+    pub board: Board,
+    pub rule: Rule,
+    pub entries: Vec<(u32, Entry)>,
+    pub end: u32,
+}
+
+impl Session {
+    /// Starts recording from `game` as it is now.
+    pub fn new(game: &GameOfLife) -> Self {
+        Session {
+            board: game.board.clone(),
+            rule: game.rule,
+            entries: Vec::new(),
+            end: game.generation,
+        }
+    }
+
+    /// Reads the session saved at `path`.
+    pub fn load(path: &Path) -> Result<Session, String> {
+        std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?
+            .parse()
+    }
+
+    /// Runs the session without a UI, returning the game as it was when recording ended.
+    pub fn replay(&self) -> Result<GameOfLife, GameError> {
+        let (mut game, mut replay) = Replay::start(self);
+        loop {
+            replay.apply_due(&mut game)?;
+            match replay.next_stop(&game) {
+                Some(generation) => game.jump_to(generation, usize::MAX)?,
+                None => return Ok(game),
+            }
+        }
+    }
+}
+
+/// Writes cells as space separated `x,y` pairs, each prefixed with `prefix`.
+fn write_cells<'a>(
+    f: &mut std::fmt::Formatter<'_>,
+    prefix: &str,
+    cells: impl IntoIterator<Item = &'a Point>,
+) -> std::fmt::Result {
+    let mut cells: Vec<_> = cells.into_iter().collect();
+    cells.sort();
+    for p in cells {
+        f.write_fmt(format_args!(" {}{},{}", prefix, p.x, p.y))?;
+    }
+    Ok(())
+}
+
+fn parse_cell(s: &str) -> Option<Point> {
+    let (x, y) = s.split_once(',')?;
+    Some(Point::new(x.parse().ok()?, y.parse().ok()?))
+}
+
+fn parse_board<'a>(cells: impl Iterator<Item = &'a str>) -> Option<Board> {
+    cells.map(parse_cell).collect()
+}
+
+impl Display for Entry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Entry::Action(action) => action.fmt(f),
+            Entry::Edit(diff) => {
+                f.write_str("edit")?;
+                write_cells(f, "+", &diff.birthed)?;
+                write_cells(f, "-", &diff.killed)
+            }
+            Entry::Restart(board) => {
+                f.write_str("restart")?;
+                write_cells(f, "", board.iter())
+            }
+        }
+    }
+}
+
+impl FromStr for Entry {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid session entry `{}`", s);
+        let mut words = s.split_whitespace();
+        match words.next() {
+            Some("edit") => {
+                let mut diff = Diff::default();
+                for word in words {
+                    let (cells, cell) = match word.split_at_checked(1) {
+                        Some(("+", cell)) => (&mut diff.birthed, cell),
+                        Some(("-", cell)) => (&mut diff.killed, cell),
+                        _ => return Err(invalid()),
+                    };
+                    cells.insert(parse_cell(cell).ok_or_else(invalid)?);
+                }
+                Ok(Entry::Edit(diff))
+            }
+            Some("restart") => parse_board(words).map(Entry::Restart).ok_or_else(invalid),
+            _ => s.parse().map(Entry::Action).map_err(|e| e.to_string()),
+        }
+    }
+}
+
+impl Display for Session {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!("{}\nrule {}\nboard", HEADER, self.rule))?;
+        write_cells(f, "", self.board.iter())?;
+        f.write_fmt(format_args!("\nend {}\n", self.end))?;
+        for (generation, entry) in &self.entries {
+            f.write_fmt(format_args!("@{} {}\n", generation, entry))?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Session {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut lines = s.lines().map(str::trim).filter(|line| !line.is_empty());
+        if lines.next() != Some(HEADER) {
+            return Err(format!("Sessions start with `{}`", HEADER));
+        }
+        let mut session = Session::default();
+        for line in lines {
+            let invalid = || format!("Invalid session line `{}`", line);
+            let (key, rest) = line.split_once(' ').unwrap_or((line, ""));
+            if let Some(generation) = key.strip_prefix('@') {
+                let generation = generation.parse().map_err(|_| invalid())?;
+                session.entries.push((generation, rest.parse()?));
+                continue;
+            }
+            match key {
+                "rule" => session.rule = rest.parse().map_err(|e: GameError| e.to_string())?,
+                "board" => {
+                    session.board = parse_board(rest.split_whitespace()).ok_or_else(invalid)?
+                }
+                "end" => session.end = rest.parse().map_err(|_| invalid())?,
+                _ => return Err(invalid()),
+            }
+        }
+        Ok(session)
+    }
+}
+
+/// Plays a session's entries back into a game as it reaches their generations.
+#[derive(Debug)]
+pub struct Replay {
+    entries: VecDeque<(u32, Entry)>,
+    end: u32,
+}
+
+impl Replay {
+    /// The game at the start of `session`, and the replay of what follows.
+    pub fn start(session: &Session) -> (GameOfLife, Replay) {
+        let game = GameOfLife {
+            board: session.board.clone(),
+            rule: session.rule,
+            ..Default::default()
+        };
+        let replay = Replay {
+            entries: session.entries.iter().cloned().collect(),
+            end: session.end,
+        };
+        (game, replay)
+    }
+
+    /// Applies the entries due at the game's generation. Entries recorded after stepping back
+    /// rewind the game to their generation first.
+    pub fn apply_due(&mut self, game: &mut GameOfLife) -> Result<(), GameError> {
+        while let Some((generation, _)) = self.entries.front() {
+            if *generation > game.generation {
+                break;
+            }
+            if *generation < game.generation {
+                game.jump_to(*generation, usize::MAX)?;
+            }
+            let (_, entry) = self.entries.pop_front().expect("front exists");
+            Replay::apply(game, entry)?;
+        }
+        Ok(())
+    }
+
+    fn apply(game: &mut GameOfLife, entry: Entry) -> Result<(), GameError> {
+        match entry {
+            Entry::Action(Action::Rule(rule)) => game.rule = rule,
+            Entry::Action(Action::Gen(generation)) => game.jump_to(generation, usize::MAX)?,
+            // Nothing else changes the game, and files may have changed since
+            Entry::Action(_) => (),
+            Entry::Edit(diff) => {
+                let before = game.board.clone();
+                for pos in &diff.killed {
+                    game.board.kill_cell(pos);
+                }
+                for pos in &diff.birthed {
+                    game.board.birth_cell(pos);
+                }
+                game.edited(&before);
+            }
+            Entry::Restart(board) => game.restart(board),
+        }
+        Ok(())
+    }
+
+    /// The generation to run to next: that of the next entry, or the end of the recording. `None`
+    /// once the replay is over.
+    pub fn next_stop(&self, game: &GameOfLife) -> Option<u32> {
+        match self.entries.front() {
+            Some((generation, _)) => Some(*generation),
+            None => (game.generation != self.end).then_some(self.end),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn session() -> Session {
+        let mut game: GameOfLife = "...\nxxx\n...".parse().unwrap();
+        let mut session = Session::new(&game);
+        game.step_n(3);
+        let diff = Diff {
+            birthed: [Point::new(5, 5), Point::new(5, 6), Point::new(5, 7)].into(),
+            killed: Default::default(),
+        };
+        session.entries.push((3, Entry::Edit(diff)));
+        session
+            .entries
+            .push((6, Entry::Action(Action::Rule("B36/S23".parse().unwrap()))));
+        session
+            .entries
+            .push((8, Entry::Restart("xx\nxx".parse().unwrap())));
+        session.end = 9;
+        session
+    }
+
+    #[test]
+    fn round_trip() {
+        let session = session();
+        let text = session.to_string();
+        assert!(text.starts_with("#session\nrule B3/S23\nboard 0,1 1,1 2,1\nend 9\n"));
+        assert!(text.contains("@3 edit +5,5 +5,6 +5,7\n"));
+        assert_eq!(text.parse::<Session>().unwrap(), session);
+
+        assert!("rule B3/S23".parse::<Session>().is_err());
+        assert!("#session\n@x restart".parse::<Session>().is_err());
+        assert!("#session\n@1 restart 0,x".parse::<Session>().is_err());
+        assert!("#session\n@1 edit 5,5".parse::<Session>().is_err());
+        assert!("#session\n@1 dance".parse::<Session>().is_err());
+    }
+
+    #[test]
+    fn replay() {
+        let game = session().replay().unwrap();
+        assert_eq!(game.rule.to_string(), "B36/S23");
+        // Restarted with a block at generation 8, then run to the end
+        assert_eq!(game.generation, 9);
+        assert_eq!(game.board, "xx\nxx".parse().unwrap());
+
+        let mut session = session();
+        session.entries.truncate(2);
+        let game = session.replay().unwrap();
+        let mut expected: GameOfLife = "...\nxxx\n...".parse().unwrap();
+        expected.step_n(3);
+        expected.board.draw_line(Point::new(5, 5), Point::new(5, 7));
+        expected.step_n(3);
+        expected.rule = "B36/S23".parse().unwrap();
+        expected.step_n(3);
+        assert_eq!(game.board, expected.board);
+        assert_eq!(game.generation, 9);
+    }
+}
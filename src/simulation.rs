@@ -0,0 +1,91 @@
+use std::{
+    sync::{
+        mpsc::{channel, Sender},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+};
+
+use crate::{
+    board::Board,
+    event::{Event, EventSender},
+    game::GameOfLife,
+    rule::Rule,
+};
+
+/// Computes the next generation of a board.
+pub type StepFn = fn(&Board, &Rule) -> Board;
+
+struct StepRequest {
+    generation: u32,
+    board: Arc<Board>,
+    rule: Rule,
+}
+
+/// Steps boards on a worker thread so an expensive generation doesn't block input. Each
+/// requested step is answered with an `Event::Board` holding the next generation.
+pub struct Simulation {
+    pub thread: JoinHandle<()>,
+    requests: Sender<StepRequest>,
+}
+
+impl Simulation {
+    pub fn spawn(events: EventSender) -> Self {
+        Self::with_step(events, GameOfLife::next_board)
+    }
+
+    pub fn with_step(events: EventSender, step: StepFn) -> Self {
+        let (requests, receiver) = channel::<StepRequest>();
+        let thread = thread::spawn(move || {
+            for request in receiver.iter() {
+                let board = step(&request.board, &request.rule);
+                let event = Event::Board {
+                    generation: request.generation + 1,
+                    board: Arc::new(board),
+                };
+                if events.send(event).is_err() {
+                    break;
+                }
+            }
+        });
+        Self { thread, requests }
+    }
+
+    /// Requests the generation after `board`, which is at `generation`.
+    pub fn request_step(&self, generation: u32, board: Arc<Board>, rule: Rule) {
+        self.requests
+            .send(StepRequest {
+                generation,
+                board,
+                rule,
+            })
+            .ok();
+    }
+}
+
+impl std::fmt::Debug for Simulation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Simulation").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::event::Listener;
+
+    #[test]
+    fn steps_on_worker() {
+        let listener = Listener::default();
+        let sim = Simulation::spawn(listener.subscribe());
+        let board: Board = "xxx".parse().unwrap();
+        sim.request_step(7, Arc::new(board), Rule::default());
+        match listener.next().unwrap() {
+            Event::Board { generation, board } => {
+                assert_eq!(generation, 8);
+                assert_eq!(board.to_ascii(), "x\nx\nx\n");
+            }
+            e => panic!("unexpected event {:?}", e),
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use std::collections::HashMap;
+
+use crate::{
+    analysis::{self, MAX_CLASSIFY_PERIOD},
+    board::Board,
+    game::GameOfLife,
+    point::Point,
+    random::Rng,
+};
+
+/// Generations a soup is given to settle before its ash is catalogued regardless
+pub const MAX_SOUP_STEPS: u32 = 2000;
+
+/// Cycles the population must repeat for before a soup that never repeats exactly (e.g. because
+/// it emitted a glider) is considered settled
+const POPULATION_REPEATS: usize = 4;
+
+/// Probability each cell in a soup starts alive
+pub const SOUP_DENSITY: f64 = 0.5;
+
+/// Whether the population has cycled with a period of at most `MAX_CLASSIFY_PERIOD` for the last
+/// `POPULATION_REPEATS` cycles. Spaceships keep the board from ever repeating, but not its
+/// population.
+fn population_settled(game: &GameOfLife) -> bool {
+    let populations: Vec<_> = game.stats.iter().map(|s| s.population).collect();
+    (1..=MAX_CLASSIFY_PERIOD as usize).any(|period| {
+        let window = period * POPULATION_REPEATS;
+        populations.len() > window
+            && populations[populations.len() - window..]
+                .windows(period + 1)
+                .all(|w| w[0] == w[period])
+    })
+}
+
+/// Runs `trials` random `size`×`size` soups until they settle and tallies the objects left
+/// behind by their `analysis::classify` names. The same seed always produces the same tally.
+pub fn soup_search(trials: u64, size: u16, seed: u64) -> HashMap<String, usize> {
+    let mut rng = Rng::new(seed);
+    let mut tally = HashMap::new();
+    for _ in 0..trials {
+        let mut game = GameOfLife {
+            board: Board::random(Point::default(), size, size, SOUP_DENSITY, &mut rng),
+            ..Default::default()
+        };
+        while game.generation < MAX_SOUP_STEPS
+            && !game.board.is_empty()
+            && analysis::detect_period(&game, MAX_CLASSIFY_PERIOD).is_none()
+            && !population_settled(&game)
+        {
+            game.step();
+        }
+        for object in analysis::objects(&game.board) {
+            *tally
+                .entry(analysis::classify(&object, &game.rule))
+                .or_default() += 1;
+        }
+    }
+    tally
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn reproducible() {
+        let tally = soup_search(5, 5, 42);
+        assert_eq!(tally, soup_search(5, 5, 42));
+        assert!(tally.values().sum::<usize>() > 0);
+        // Blocks are by far the most common ash
+        assert!(tally.contains_key("xs4_2o$2o"));
+        assert_ne!(tally, soup_search(5, 5, 43));
+    }
+}
@@ -4,6 +4,23 @@ use tui::{backend::CrosstermBackend, layout::Rect};
 pub type Frame<'a> = tui::Frame<'a, CrosstermBackend<Stdout>>;
 pub type Terminal = tui::Terminal<CrosstermBackend<Stdout>>;
 
+/// Guesses whether the terminal can display Unicode from the locale, which on Unix is the first
+/// of `LC_ALL`, `LC_CTYPE` and `LANG` that's set. Windows terminals are assumed to.
+pub fn supports_unicode() -> bool {
+    if cfg!(windows) {
+        return true;
+    }
+    ["LC_ALL", "LC_CTYPE", "LANG"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|value| !value.is_empty())
+        .map(|locale| {
+            let locale = locale.to_ascii_lowercase();
+            locale.contains("utf-8") || locale.contains("utf8")
+        })
+        .unwrap_or(false)
+}
+
 pub fn within(rect: &Rect, x: u16, y: u16) -> bool {
     x >= rect.x && x < rect.x + rect.width && y >= rect.y && y < rect.y + rect.height
 }
@@ -0,0 +1,145 @@
+use tui::{style::Color, symbols};
+
+/// Colors and symbols used to draw the board.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Theme {
+    pub name: &'static str,
+    pub background: Color,
+    /// Background of every other cell when drawing a checkerboard to help count cells
+    pub checker: Color,
+    /// Dots marking dead cells
+    pub grid: Color,
+    pub alive: Color,
+    /// Cells born since the previous generation, when showing the diff
+    pub birth: Color,
+    /// Cells that died since the previous generation, when showing the diff
+    pub death: Color,
+    /// Drawn for dead cells, so deaths can be told from births by shape as well as color
+    pub death_symbol: &'static str,
+    /// Pattern waiting to be stamped
+    pub preview: Color,
+    pub cursor: Color,
+    /// Cells decaying under a Generations rule, fading from the freshest state to the oldest
+    pub decay: &'static [Color],
+}
+
+pub const DEFAULT: Theme = Theme {
+    name: "Default",
+    background: Color::LightBlue,
+    checker: Color::Cyan,
+    grid: Color::Black,
+    alive: Color::Reset,
+    birth: Color::Green,
+    death: Color::Red,
+    death_symbol: symbols::bar::FULL,
+    preview: Color::DarkGray,
+    cursor: Color::Yellow,
+    decay: &[Color::Gray, Color::DarkGray],
+};
+
+/// Uses the Okabe-Ito palette, picking colors for live cells, births and deaths that differ in
+/// brightness and not just hue. Deaths are also drawn with a shaded block.
+pub const COLORBLIND: Theme = Theme {
+    name: "Colorblind",
+    background: Color::Rgb(0, 0, 0),
+    checker: Color::Rgb(28, 28, 28),
+    grid: Color::Rgb(90, 90, 90),
+    // Yellow
+    alive: Color::Rgb(240, 228, 66),
+    // Sky blue
+    birth: Color::Rgb(86, 180, 233),
+    // Vermillion
+    death: Color::Rgb(213, 94, 0),
+    death_symbol: "▒",
+    // Blue
+    preview: Color::Rgb(0, 114, 178),
+    // Reddish purple
+    cursor: Color::Rgb(204, 121, 167),
+    // Orange, fading
+    decay: &[
+        Color::Rgb(230, 159, 0),
+        Color::Rgb(160, 110, 0),
+        Color::Rgb(95, 65, 0),
+    ],
+};
+
+pub const THEMES: &[&Theme] = &[&DEFAULT, &COLORBLIND];
+
+/// Glyphs a board is drawn with, independent of its colors. Half blocks keep their own glyphs
+/// since they pack two cells into one.
+#[derive(Debug, PartialEq, Eq)]
+pub struct CellGlyphs {
+    pub name: &'static str,
+    pub alive: &'static str,
+    pub dead: &'static str,
+    /// Drawn for cells that just died when showing the diff, or the theme's `death_symbol` if
+    /// `None`
+    pub died: Option<&'static str>,
+}
+
+pub const BLOCKS: CellGlyphs = CellGlyphs {
+    name: "Blocks",
+    alive: symbols::block::FULL,
+    dead: "·",
+    died: None,
+};
+
+pub const CIRCLES: CellGlyphs = CellGlyphs {
+    name: "Circles",
+    alive: "●",
+    dead: "·",
+    died: Some("○"),
+};
+
+pub const ASCII: CellGlyphs = CellGlyphs {
+    name: "ASCII",
+    alive: "#",
+    dead: " ",
+    died: Some("o"),
+};
+
+pub const CELL_GLYPHS: &[&CellGlyphs] = &[&BLOCKS, &CIRCLES, &ASCII];
+
+impl CellGlyphs {
+    /// The preset called `name`, ignoring case.
+    pub fn named(name: &str) -> Option<&'static CellGlyphs> {
+        CELL_GLYPHS
+            .iter()
+            .copied()
+            .find(|glyphs| glyphs.name.eq_ignore_ascii_case(name))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Relative luminance out of 255 for an RGB color.
+    fn luminance(color: Color) -> f64 {
+        match color {
+            Color::Rgb(r, g, b) => 0.2126 * r as f64 + 0.7152 * g as f64 + 0.0722 * b as f64,
+            _ => panic!("expected an RGB color, got {:?}", color),
+        }
+    }
+
+    #[test]
+    fn colorblind_luminance() {
+        let t = &COLORBLIND;
+        let colors = [t.background, t.alive, t.birth, t.death];
+        for (i, a) in colors.iter().enumerate() {
+            for b in &colors[i + 1..] {
+                let contrast = (luminance(*a) - luminance(*b)).abs();
+                assert!(contrast > 40.0, "{:?} and {:?} are too similar", a, b);
+            }
+        }
+        assert_ne!(t.death_symbol, symbols::bar::FULL);
+    }
+
+    #[test]
+    fn cell_glyphs_by_name() {
+        assert_eq!(CellGlyphs::named("circles").unwrap().alive, "●");
+        assert_eq!(CellGlyphs::named("ASCII"), Some(&ASCII));
+        assert_eq!(CellGlyphs::named("hexagons"), None);
+        assert!(ASCII.alive.is_ascii() && ASCII.dead.is_ascii());
+    }
+}
@@ -1,46 +1,1024 @@
+use std::collections::{HashMap, HashSet};
+
 use tui::{
-    style::{Color, Style},
+    buffer::Buffer,
+    layout::Rect,
+    style::{Modifier, Style},
+    symbols,
     widgets::Widget,
 };
 
-use crate::{board::Board, point::Point};
+use crate::{
+    board::Board,
+    point::{Axis, Point, YAxis},
+    theme::{self, CellGlyphs, Theme},
+};
+
+/// How the world is laid out on screen: which point is centered, how far zoomed out, and how
+/// many world rows each terminal row holds. World y increases upwards, as in parsed patterns, so
+/// this is the one place screen rows are flipped.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Viewport {
+    pub origin: Point,
+    /// World cells per screen cell along each axis
+    pub zoom: u16,
+    /// Pack two rows of world cells into each terminal row using half-block glyphs
+    pub half_blocks: bool,
+}
+
+impl Viewport {
+    /// World rows per terminal row
+    fn rows_per_cell(&self) -> i64 {
+        if self.half_blocks {
+            2
+        } else {
+            1
+        }
+    }
+
+    /// The world point shown at `(x, y)` within a `width`×`height` area. When zoomed out this is
+    /// the lowest corner of the block under the cell, and with half blocks it's in the upper half.
+    pub fn screen_to_world(&self, width: u16, height: u16, x: u16, y: u16) -> Point {
+        let zoom = self.zoom.max(1) as i64;
+        let rows = self.rows_per_cell();
+        Point::new(
+            ((x as i64 - width as i64 / 2) * zoom).saturating_add(self.origin.x),
+            ((height as i64 / 2 - y as i64) * rows * zoom).saturating_add(self.origin.y),
+        )
+    }
+
+    /// Centers the view on the rectangle from `min` to `max` and picks the closest zoom that
+    /// shows all of it within a `width`×`height` area. A rectangle too big for the widest zoom,
+    /// which can happen near the edges of the world, gets the widest zoom.
+    pub fn fit(&mut self, min: Point, max: Point, width: u16, height: u16) {
+        // Spans can be wider than an i64 holds
+        let middle = |from: i64, to: i64| (from as i128 + to as i128).div_euclid(2) as i64;
+        self.origin = Point::new(middle(min.x, max.x), middle(min.y, max.y));
+        if width == 0 || height == 0 {
+            return;
+        }
+        // Near enough from the size alone, then out a little more if centering cut off an edge
+        let rows = height as i128 * self.rows_per_cell() as i128;
+        let span = |from: i64, to: i64| (to as i128 - from as i128).max(0);
+        let zoom = (span(min.x, max.x) / width as i128 + 1).max(span(min.y, max.y) / rows + 1);
+        self.zoom = zoom.clamp(1, u16::MAX as i128) as u16;
+        while self.zoom < u16::MAX {
+            let (shown_min, shown_max) = self.bounds(width, height);
+            if shown_min.x <= min.x
+                && shown_min.y <= min.y
+                && shown_max.x >= max.x
+                && shown_max.y >= max.y
+            {
+                break;
+            }
+            self.zoom += 1;
+        }
+    }
+
+    /// Corners `(min, max)` of the world cells shown within a `width`×`height` area, inclusive.
+    pub fn bounds(&self, width: u16, height: u16) -> (Point, Point) {
+        let zoom = self.zoom.max(1) as i64;
+        let rows = height as i64 * self.rows_per_cell();
+        let center = height as i64 / 2 * self.rows_per_cell();
+        let min = Point::new(
+            self.origin.x.saturating_sub(width as i64 / 2 * zoom),
+            self.origin.y.saturating_add((center - (rows - 1)) * zoom),
+        );
+        let max = Point::new(
+            min.x.saturating_add(width as i64 * zoom - 1),
+            min.y.saturating_add(rows * zoom - 1),
+        );
+        (min, max)
+    }
+
+    /// The screen cell within a `width`×`height` area showing the world point `p`, or `None` if
+    /// it's out of view. The inverse of `screen_to_world`.
+    pub fn world_to_screen(&self, width: u16, height: u16, p: Point) -> Option<(u16, u16)> {
+        let (min, max) = self.bounds(width, height);
+        if !(min.x..=max.x).contains(&p.x) || !(min.y..=max.y).contains(&p.y) {
+            return None;
+        }
+        let zoom = self.zoom.max(1) as i64;
+        Some((
+            ((p.x - min.x) / zoom) as u16,
+            ((max.y - p.y) / (zoom * self.rows_per_cell())) as u16,
+        ))
+    }
+
+    /// Columns of a `width`×`height` area to label on a ruler, with the world x each is labeled
+    /// with. Labels are multiples of `RULER_SPACING` screen cells' worth of world cells, so they
+    /// stay the same distance apart at any zoom.
+    pub fn column_ticks(&self, width: u16, height: u16) -> Vec<(u16, i64)> {
+        let zoom = self.zoom.max(1) as i64;
+        (0..width)
+            .filter_map(|x| {
+                let start = self.screen_to_world(width, height, x, 0).x;
+                Some((x, ruler_label(start, zoom, RULER_SPACING * zoom)?))
+            })
+            .collect()
+    }
+
+    /// Rows of a `width`×`height` area to label on a ruler, with the world y each is labeled
+    /// with, like `column_ticks`.
+    pub fn row_ticks(&self, width: u16, height: u16) -> Vec<(u16, i64)> {
+        let zoom = self.zoom.max(1) as i64;
+        let span = zoom * self.rows_per_cell();
+        (0..height)
+            .filter_map(|y| {
+                // `screen_to_world` gives the upper half, so the block starts further down
+                let upper = self.screen_to_world(width, height, 0, y).y;
+                let bottom = upper.saturating_sub(span - zoom);
+                Some((y, ruler_label(bottom, span, RULER_SPACING * span)?))
+            })
+            .collect()
+    }
+
+    /// Positions within a `width`×`height` area of rows of world cells holding any live cell of
+    /// `board`. Rows are counted from the top in units of `zoom` world rows, so with half blocks
+    /// there are two per terminal row.
+    fn lit(&self, board: &Board, width: u16, height: u16) -> Vec<(u16, u16)> {
+        let rows = height.saturating_mul(self.rows_per_cell() as u16);
+        // The bottom left of the area, where `window_scaled` counts from
+        let (min, _) = self.bounds(width, height);
+        board
+            .window_scaled(min, width, rows, self.zoom)
+            .map(|(x, y)| (x, rows - 1 - y))
+            .collect()
+    }
+}
+
+/// Screen cells between labels on the rulers
+const RULER_SPACING: i64 = 10;
+
+/// The multiple of `spacing` within the `len` world cells from `start`, if any.
+fn ruler_label(start: i64, len: i64, spacing: i64) -> Option<i64> {
+    let label = match start.rem_euclid(spacing) {
+        0 => start,
+        rem => start.checked_add(spacing - rem)?,
+    };
+    (label - start < len).then_some(label)
+}
+
+/// Coordinate ruler drawn along the top or left of the board, with labels at the positions from
+/// `Viewport::column_ticks` or `Viewport::row_ticks`.
+pub struct Ruler {
+    viewport: Viewport,
+    /// Size of the board area the ruler runs along
+    board: (u16, u16),
+    /// `Axis::Horizontal` runs along the top labeling columns, `Axis::Vertical` down the left
+    /// labeling rows
+    axis: Axis,
+    y_axis: YAxis,
+    style: Style,
+    ascii: bool,
+}
+
+impl Ruler {
+    pub fn new(viewport: Viewport, board: Rect, axis: Axis) -> Self {
+        Ruler {
+            viewport,
+            board: (board.width, board.height),
+            axis,
+            y_axis: YAxis::Up,
+            style: Style::default(),
+            ascii: false,
+        }
+    }
+
+    /// Which way row labels count.
+    pub fn y_axis(mut self, y_axis: YAxis) -> Self {
+        self.y_axis = y_axis;
+        self
+    }
+
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn ascii(mut self, ascii: bool) -> Self {
+        self.ascii = ascii;
+        self
+    }
+}
+
+impl Widget for Ruler {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        if area.width == 0 || area.height == 0 {
+            return;
+        }
+        let (width, height) = self.board;
+        match self.axis {
+            Axis::Horizontal => {
+                let track = if self.ascii {
+                    "-"
+                } else {
+                    symbols::line::HORIZONTAL
+                };
+                let y = area.bottom() - 1;
+                for x in area.left()..area.right() {
+                    buf.get_mut(x, y).set_symbol(track).set_style(self.style);
+                }
+                // Labels start at their column and run over the track to the right
+                for (dx, label) in self.viewport.column_ticks(width, height) {
+                    let x = area.x + dx;
+                    if x < area.right() {
+                        buf.set_stringn(
+                            x,
+                            y,
+                            label.to_string(),
+                            (area.right() - x) as usize,
+                            self.style,
+                        );
+                    }
+                }
+            }
+            Axis::Vertical => {
+                let (track, tick) = if self.ascii {
+                    ("|", "+")
+                } else {
+                    (symbols::line::VERTICAL, symbols::line::VERTICAL_LEFT)
+                };
+                let x = area.right() - 1;
+                for y in area.top()..area.bottom() {
+                    buf.get_mut(x, y).set_symbol(track).set_style(self.style);
+                }
+                for (dy, label) in self.viewport.row_ticks(width, height) {
+                    let y = area.y + dy;
+                    if y >= area.bottom() {
+                        continue;
+                    }
+                    let label = self.y_axis.display(Point::new(0, label)).y.to_string();
+                    // Right aligned against the tick, keeping the least significant digits
+                    let room = (area.width - 1) as usize;
+                    let label = &label[label.len().saturating_sub(room)..];
+                    buf.set_string(x - label.len() as u16, y, label, self.style);
+                    buf.get_mut(x, y).set_symbol(tick);
+                }
+            }
+        }
+    }
+}
 
 pub struct BoardWidget<'b> {
     board: &'b Board,
-    origin: Point,
-    // TODO: zoom
+    viewport: Viewport,
+    preview: Option<&'b Board>,
+    /// Cells added and removed since the previous generation
+    diff: Option<(&'b Board, &'b Board)>,
+    /// Cells decaying under a Generations rule by state, and the rule's number of states
+    decaying: Option<(&'b HashMap<Point, u8>, u8)>,
+    /// Cells recently alive by how many generations ago, and the longest trail kept
+    trail: Option<(&'b HashMap<Point, u8>, u8)>,
+    cursor: Option<Point>,
+    theme: &'b Theme,
+    glyphs: &'b CellGlyphs,
+    /// Only draw ASCII characters, for terminals without Unicode
+    ascii: bool,
+    /// Alternate the background color of neighboring world cells
+    checkerboard: bool,
+    /// Text drawn over a corner of the board, e.g. the generation
+    hud: Option<&'b str>,
 }
 
 impl<'b> BoardWidget<'b> {
     pub fn new(board: &'b Board) -> Self {
         BoardWidget {
             board,
-            origin: Default::default(),
+            viewport: Viewport {
+                zoom: 1,
+                ..Default::default()
+            },
+            preview: None,
+            diff: None,
+            decaying: None,
+            trail: None,
+            cursor: None,
+            theme: &theme::DEFAULT,
+            glyphs: &theme::BLOCKS,
+            ascii: false,
+            checkerboard: false,
+            hud: None,
         }
     }
 
+    pub fn viewport(mut self, viewport: Viewport) -> Self {
+        self.viewport = Viewport {
+            zoom: viewport.zoom.max(1),
+            ..viewport
+        };
+        self
+    }
+
     pub fn pan_to(mut self, origin: Point) -> Self {
-        self.origin = origin;
+        self.viewport.origin = origin;
+        self
+    }
+
+    /// Zooms out so each screen cell shows a `zoom`×`zoom` block of world cells.
+    pub fn zoom(mut self, zoom: u16) -> Self {
+        self.viewport.zoom = zoom.max(1);
+        self
+    }
+
+    /// Draws the cells of `preview` dimmed on top of the board.
+    pub fn preview(mut self, preview: &'b Board) -> Self {
+        self.preview = Some(preview);
+        self
+    }
+
+    /// Doubles vertical resolution by drawing each vertical pair of world cells as a half block.
+    pub fn half_blocks(mut self, half_blocks: bool) -> Self {
+        self.viewport.half_blocks = half_blocks;
+        self
+    }
+
+    /// Draws with `#` and spaces in place of block characters and dots.
+    pub fn ascii(mut self, ascii: bool) -> Self {
+        self.ascii = ascii;
+        self
+    }
+
+    /// Shades the background of every other world cell in the theme's checker color, to help
+    /// count cells. Zoomed out, it's every other block of cells.
+    pub fn checkerboard(mut self, checkerboard: bool) -> Self {
+        self.checkerboard = checkerboard;
+        self
+    }
+
+    /// Draws `text` over whichever corner of the board has the fewest live cells under it, so
+    /// screen recordings show it without the info panel.
+    pub fn hud(mut self, text: &'b str) -> Self {
+        self.hud = Some(text);
+        self
+    }
+
+    pub fn theme(mut self, theme: &'b Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    /// Draws live and dead cells with `glyphs` instead of blocks and dots.
+    pub fn glyphs(mut self, glyphs: &'b CellGlyphs) -> Self {
+        self.glyphs = glyphs;
+        self
+    }
+
+    /// Highlights the screen cell containing the world point `cursor`.
+    pub fn cursor(mut self, cursor: Point) -> Self {
+        self.cursor = Some(cursor);
+        self
+    }
+
+    /// Highlights `added` and `removed` cells in the theme's birth and death colors.
+    pub fn diff(mut self, added: &'b Board, removed: &'b Board) -> Self {
+        self.diff = Some((added, removed));
+        self
+    }
+
+    /// Draws cells decaying through the states of a Generations rule in the theme's fading decay
+    /// colors, older states fainter.
+    pub fn decaying(mut self, dying: &'b HashMap<Point, u8>, states: u8) -> Self {
+        self.decaying = Some((dying, states));
+        self
+    }
+
+    /// Draws a trail behind moving cells in the theme's decay colors, fading out over `length`
+    /// generations.
+    pub fn trail(mut self, trail: &'b HashMap<Point, u8>, length: u8) -> Self {
+        self.trail = Some((trail, length));
         self
     }
 }
 
+const UPPER_HALF: &str = "▀";
+const LOWER_HALF: &str = "▄";
+
+/// Stand-in for `symbol` on terminals without Unicode.
+fn ascii_glyph(symbol: &'static str) -> &'static str {
+    match symbol {
+        s if s.is_ascii() => s,
+        UPPER_HALF => "\"",
+        LOWER_HALF => ",",
+        _ => "#",
+    }
+}
+
+/// Glyph for a terminal cell showing a vertical pair of world cells, if either is alive.
+fn half_block(top: bool, bottom: bool) -> Option<&'static str> {
+    match (top, bottom) {
+        (true, true) => Some(symbols::block::FULL),
+        (true, false) => Some(UPPER_HALF),
+        (false, true) => Some(LOWER_HALF),
+        (false, false) => None,
+    }
+}
+
+impl<'b> BoardWidget<'b> {
+    fn glyph(&self, symbol: &'static str) -> &'static str {
+        if self.ascii {
+            ascii_glyph(symbol)
+        } else {
+            symbol
+        }
+    }
+
+    /// Draws `cells` in the theme's decay colors by age, from 0 for the freshest up to `oldest`.
+    fn fade(
+        &self,
+        cells: impl Iterator<Item = (Point, usize)>,
+        oldest: usize,
+        area: Rect,
+        buf: &mut Buffer,
+    ) {
+        let decay = self.theme.decay;
+        if decay.is_empty() {
+            return;
+        }
+        // Group ages by color, drawing the freshest last so they show when zoomed out
+        let mut fading = vec![Board::default(); decay.len()];
+        for (p, age) in cells {
+            let shade = age * decay.len() / oldest.max(1);
+            fading[shade.min(decay.len() - 1)].birth_cell(&p);
+        }
+        for (board, &color) in fading.iter().zip(decay).rev() {
+            for (dx, dy, symbol) in self.cells(board, area) {
+                buf.get_mut(area.x + dx, area.y + dy)
+                    .set_symbol(self.glyph(symbol))
+                    .set_fg(color);
+            }
+        }
+    }
+
+    /// Terminal cells within `area` showing live cells of `board`, relative to the area, and the
+    /// glyph to draw in each.
+    fn cells(&self, board: &Board, area: Rect) -> Vec<(u16, u16, &'static str)> {
+        let lit = self.viewport.lit(board, area.width, area.height);
+        if !self.viewport.half_blocks {
+            return lit
+                .into_iter()
+                .map(|(x, y)| (x, y, self.glyphs.alive))
+                .collect();
+        }
+        let mut pairs: HashMap<(u16, u16), (bool, bool)> = HashMap::new();
+        for (x, y) in lit {
+            let pair = pairs.entry((x, y / 2)).or_default();
+            if y % 2 == 0 {
+                pair.0 = true;
+            } else {
+                pair.1 = true;
+            }
+        }
+        pairs
+            .into_iter()
+            .filter_map(|((x, y), (top, bottom))| Some((x, y, half_block(top, bottom)?)))
+            .collect()
+    }
+}
+
 impl<'b> Widget for BoardWidget<'b> {
-    fn render(self, area: tui::layout::Rect, buf: &mut tui::buffer::Buffer) {
-        buf.set_style(area, Style::default().bg(Color::LightBlue));
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let theme = self.theme;
+        buf.set_style(area, Style::default().bg(theme.background));
 
+        let zoom = self.viewport.zoom as i64;
+        let rows = self.viewport.rows_per_cell();
         for x in area.x..area.x + area.width {
             for y in area.y..area.y + area.height {
-                buf.get_mut(x, y).set_symbol("·").set_fg(Color::Black);
+                let dot = if self.ascii { " " } else { self.glyphs.dead };
+                let cell = buf.get_mut(x, y).set_symbol(dot).set_fg(theme.grid);
+                if self.checkerboard {
+                    // By world position, so the pattern moves with the cells when panning
+                    let p = self.viewport.screen_to_world(
+                        area.width,
+                        area.height,
+                        x - area.x,
+                        y - area.y,
+                    );
+                    if (p.x.div_euclid(zoom) + p.y.div_euclid(zoom * rows)) % 2 != 0 {
+                        cell.set_bg(theme.checker);
+                    }
+                }
             }
         }
-        for (_point, dx, dy) in self.board.window(
-            self.origin - Point::new(area.width as i64 / 2, area.height as i64 / 2),
-            area.width,
-            area.height,
-        ) {
+        if let Some((trail, length)) = self.trail {
+            let ages = trail.iter().map(|(&p, &age)| (p, age as usize - 1));
+            self.fade(ages, length as usize, area, buf);
+        }
+        if let Some((dying, states)) = self.decaying {
+            let ages = dying
+                .iter()
+                .map(|(&p, &state)| (p, state.saturating_sub(2) as usize));
+            self.fade(ages, states.saturating_sub(2) as usize, area, buf);
+        }
+        for (dx, dy, symbol) in self.cells(self.board, area) {
             buf.get_mut(area.x + dx, area.y + dy)
-                .set_symbol(tui::symbols::bar::FULL);
+                .set_symbol(self.glyph(symbol))
+                .set_fg(theme.alive);
+        }
+        if let Some((added, removed)) = self.diff {
+            for (dx, dy, symbol) in self.cells(added, area) {
+                buf.get_mut(area.x + dx, area.y + dy)
+                    .set_symbol(self.glyph(symbol))
+                    .set_fg(theme.birth);
+            }
+            for (dx, dy, symbol) in self.cells(removed, area) {
+                let symbol = match symbol {
+                    symbols::block::FULL => theme.death_symbol,
+                    alive if alive == self.glyphs.alive => {
+                        self.glyphs.died.unwrap_or(theme.death_symbol)
+                    }
+                    half => half,
+                };
+                buf.get_mut(area.x + dx, area.y + dy)
+                    .set_symbol(self.glyph(symbol))
+                    .set_fg(theme.death);
+            }
+        }
+        if let Some(preview) = self.preview {
+            for (dx, dy, symbol) in self.cells(preview, area) {
+                buf.get_mut(area.x + dx, area.y + dy)
+                    .set_symbol(self.glyph(symbol))
+                    .set_fg(theme.preview);
+            }
+        }
+        if let Some(cursor) = self.cursor {
+            for (dx, dy, _) in self.cells(&Board::from([cursor]), area) {
+                buf.get_mut(area.x + dx, area.y + dy).set_bg(theme.cursor);
+            }
+        }
+        if let Some(text) = self.hud {
+            self.render_hud(text, area, buf);
+        }
+    }
+}
+
+impl<'b> BoardWidget<'b> {
+    fn render_hud(&self, text: &str, area: Rect, buf: &mut Buffer) {
+        let width = u16::try_from(text.chars().count())
+            .unwrap_or(u16::MAX)
+            .min(area.width);
+        if width == 0 || area.height == 0 {
+            return;
+        }
+        let lit: HashSet<(u16, u16)> = self
+            .cells(self.board, area)
+            .into_iter()
+            .map(|(x, y, _)| (x, y))
+            .collect();
+        let (right, bottom) = (area.width - width, area.height - 1);
+        let (x, y) = [(0, 0), (right, 0), (0, bottom), (right, bottom)]
+            .into_iter()
+            .min_by_key(|&(x, y)| (x..x + width).filter(|&x| lit.contains(&(x, y))).count())
+            .unwrap();
+        buf.set_stringn(
+            area.x + x,
+            area.y + y,
+            text,
+            width as usize,
+            Style::default()
+                .fg(self.theme.cursor)
+                .add_modifier(Modifier::BOLD),
+        );
+    }
+}
+
+/// Horizontal slider showing a position between 0 and 1 as a handle on a track.
+pub struct Slider {
+    ratio: f64,
+    style: Style,
+    ascii: bool,
+}
+
+impl Slider {
+    pub fn new(ratio: f64) -> Self {
+        Slider {
+            ratio: ratio.clamp(0.0, 1.0),
+            style: Style::default(),
+            ascii: false,
+        }
+    }
+
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn ascii(mut self, ascii: bool) -> Self {
+        self.ascii = ascii;
+        self
+    }
+
+    /// Column within an area `width` cells wide that the handle is drawn at.
+    fn handle(ratio: f64, width: u16) -> u16 {
+        (ratio * width.saturating_sub(1) as f64).round() as u16
+    }
+
+    /// Position between 0 and 1 for column `x` of an area `width` cells wide, the inverse of
+    /// where the handle is drawn.
+    pub fn ratio_at(width: u16, x: u16) -> f64 {
+        match width {
+            0 | 1 => 0.0,
+            width => x.min(width - 1) as f64 / (width - 1) as f64,
+        }
+    }
+}
+
+impl Widget for Slider {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        if area.height == 0 || area.width == 0 {
+            return;
+        }
+        let (track, handle) = if self.ascii {
+            ("-", "|")
+        } else {
+            (symbols::line::HORIZONTAL, "●")
+        };
+        let y = area.y + area.height / 2;
+        for x in area.x..area.x + area.width {
+            buf.get_mut(x, y).set_symbol(track).set_style(self.style);
+        }
+        buf.get_mut(area.x + Slider::handle(self.ratio, area.width), y)
+            .set_symbol(handle)
+            .set_style(self.style);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn half_block_pairs() {
+        let board = Board::from([
+            (0i64, 4i64).into(),
+            (0, 2).into(),
+            (0, 1).into(),
+            (1, 3).into(),
+        ]);
+        // Each terminal row shows world rows 4 - 2y (top) and 3 - 2y (bottom)
+        let area = Rect::new(0, 0, 2, 2);
+        let mut buf = Buffer::empty(area);
+        BoardWidget::new(&board)
+            .pan_to(Point::new(1, 2))
+            .half_blocks(true)
+            .render(area, &mut buf);
+
+        let cell = |x, y| {
+            let cell = buf.get(x, y);
+            (cell.symbol.as_str(), cell.fg, cell.bg)
+        };
+        let alive = theme::DEFAULT.alive;
+        let background = theme::DEFAULT.background;
+        assert_eq!(cell(0, 0), ("▀", alive, background));
+        assert_eq!(cell(1, 0), ("▄", alive, background));
+        assert_eq!(cell(0, 1), ("█", alive, background));
+        assert_eq!(cell(1, 1), ("·", theme::DEFAULT.grid, background));
+    }
+
+    #[test]
+    fn bounds_match_corners() {
+        for (zoom, half_blocks) in [(1, false), (1, true), (3, false), (4, true)] {
+            let viewport = Viewport {
+                origin: Point::new(5, -7),
+                zoom,
+                half_blocks,
+            };
+            let (min, max) = viewport.bounds(9, 6);
+            let top_left = viewport.screen_to_world(9, 6, 0, 0);
+            let bottom_right = viewport.screen_to_world(9, 6, 8, 5);
+            let rows = if half_blocks { 2 } else { 1 };
+            assert_eq!(
+                min,
+                Point::new(top_left.x, bottom_right.y - (rows - 1) * zoom as i64)
+            );
+            assert_eq!(
+                max,
+                Point::new(
+                    bottom_right.x + zoom as i64 - 1,
+                    top_left.y + zoom as i64 - 1
+                )
+            );
+        }
+    }
+
+    #[test]
+    fn fit_shows_everything() {
+        for (min, max, half_blocks) in [
+            (Point::new(0, 0), Point::new(12, 12), false),
+            (Point::new(-50, 3), Point::new(49, 20), false),
+            (Point::new(-3, -400), Point::new(3, 400), true),
+        ] {
+            let mut viewport = Viewport {
+                half_blocks,
+                ..Default::default()
+            };
+            viewport.fit(min, max, 40, 20);
+            let (shown_min, shown_max) = viewport.bounds(40, 20);
+            assert!(shown_min.x <= min.x && shown_min.y <= min.y);
+            assert!(shown_max.x >= max.x && shown_max.y >= max.y);
+            // Any closer and it wouldn't
+            if viewport.zoom > 1 {
+                viewport.zoom -= 1;
+                let (shown_min, shown_max) = viewport.bounds(40, 20);
+                assert!(
+                    shown_min.x > min.x
+                        || shown_min.y > min.y
+                        || shown_max.x < max.x
+                        || shown_max.y < max.y
+                );
+            }
+        }
+        let mut viewport = Viewport::default();
+        viewport.fit(Point::new(0, 0), Point::new(12, 12), 40, 20);
+        assert_eq!((viewport.origin, viewport.zoom), (Point::new(6, 6), 1));
+    }
+
+    #[test]
+    fn fit_extreme_coordinates() {
+        let (low, high) = (
+            Point::new(i64::MIN, i64::MIN),
+            Point::new(i64::MAX, i64::MAX),
+        );
+        let mut viewport = Viewport::default();
+        viewport.fit(low, high, 40, 20);
+        assert_eq!(
+            (viewport.origin, viewport.zoom),
+            (Point::new(-1, -1), u16::MAX)
+        );
+
+        // A small pattern right at the edge still fits closely
+        let corner = Point::new(i64::MAX - 12, i64::MAX - 12);
+        viewport.fit(corner, high, 40, 20);
+        assert_eq!(viewport.zoom, 1);
+        let (shown_min, shown_max) = viewport.bounds(40, 20);
+        assert!(shown_min.x <= corner.x && shown_min.y <= corner.y);
+        assert_eq!(shown_max, high);
+
+        viewport.fit(low, Point::new(i64::MIN + 12, i64::MIN + 12), 40, 20);
+        assert_eq!(viewport.bounds(40, 20).0, low);
+        // Backwards corners don't panic either
+        viewport.fit(high, low, 40, 20);
+    }
+
+    #[test]
+    fn world_to_screen_inverts() {
+        for (zoom, half_blocks) in [(1, false), (1, true), (3, false), (4, true)] {
+            let viewport = Viewport {
+                origin: Point::new(5, -7),
+                zoom,
+                half_blocks,
+            };
+            for x in 0..9 {
+                for y in 0..6 {
+                    let p = viewport.screen_to_world(9, 6, x, y);
+                    assert_eq!(viewport.world_to_screen(9, 6, p), Some((x, y)));
+                }
+            }
+            let (min, max) = viewport.bounds(9, 6);
+            assert_eq!(viewport.world_to_screen(9, 6, max), Some((8, 0)));
+            assert_eq!(viewport.world_to_screen(9, 6, min), Some((0, 5)));
+            assert_eq!(viewport.world_to_screen(9, 6, min - Point::new(1, 0)), None);
+            assert_eq!(viewport.world_to_screen(9, 6, max + Point::new(0, 1)), None);
+        }
+    }
+
+    #[test]
+    fn ruler_ticks() {
+        let mut viewport = Viewport {
+            origin: Point::new(3, 0),
+            zoom: 1,
+            half_blocks: false,
+        };
+        // Columns show x from -17 to 22, rows y from 5 down to -4
+        assert_eq!(
+            viewport.column_ticks(40, 10),
+            vec![(7, -10), (17, 0), (27, 10), (37, 20)]
+        );
+        assert_eq!(viewport.row_ticks(40, 10), vec![(5, 0)]);
+
+        // Zoomed out, labels stay 10 screen cells apart and fall inside their blocks
+        viewport.zoom = 4;
+        assert_eq!(
+            viewport.column_ticks(40, 10),
+            vec![(9, -40), (19, 0), (29, 40), (39, 80)]
+        );
+        viewport.half_blocks = true;
+        viewport.origin = Point::new(0, 7);
+        let ticks = viewport.row_ticks(40, 30);
+        assert_eq!(
+            ticks.iter().map(|(_, y)| *y).collect::<Vec<_>>(),
+            [80, 0, -80]
+        );
+        for (row, y) in ticks {
+            let top = viewport.screen_to_world(40, 30, 0, row).y;
+            assert!(
+                top - 4 <= y && y < top + 4,
+                "{} isn't shown at row {}",
+                y,
+                row
+            );
+        }
+    }
+
+    #[test]
+    fn ruler_labels() {
+        let viewport = Viewport {
+            origin: Point::new(3, -7),
+            zoom: 1,
+            half_blocks: false,
+        };
+        let board = Rect::new(7, 1, 40, 10);
+        let area = Rect::new(0, 0, 47, 11);
+        let mut buf = Buffer::empty(area);
+        Ruler::new(viewport, board, Axis::Horizontal)
+            .ascii(true)
+            .render(Rect::new(7, 0, 40, 1), &mut buf);
+        Ruler::new(viewport, board, Axis::Vertical)
+            .y_axis(YAxis::Down)
+            .render(Rect::new(0, 1, 7, 10), &mut buf);
+        let row = |y| -> String { (0..47).map(|x| buf.get(x, y).symbol.as_str()).collect() };
+        assert_eq!(row(0), "       --------10-------0---------10--------20-");
+        // Row 8 of the board shows y = -10, and the label is negated for y-down
+        assert_eq!(row(9), format!("    10┤{}", " ".repeat(40)));
+        assert_eq!(buf.get(6, 8).symbol, "│");
+    }
+
+    #[test]
+    fn far_from_origin() {
+        let board = Board::from([(i64::MIN, i64::MIN).into(), (i64::MAX, i64::MAX).into()]);
+        let area = Rect::new(0, 0, 8, 8);
+        for origin in [
+            Point::new(i64::MAX, i64::MAX),
+            Point::new(i64::MIN, i64::MAX),
+            Point::new(i64::MIN, i64::MIN),
+        ] {
+            for zoom in [1, 4] {
+                let mut buf = Buffer::empty(area);
+                let viewport = Viewport {
+                    origin,
+                    zoom,
+                    half_blocks: zoom > 1,
+                };
+                BoardWidget::new(&board)
+                    .viewport(viewport)
+                    .render(area, &mut buf);
+                viewport.screen_to_world(8, 8, 0, 0);
+                viewport.screen_to_world(8, 8, 7, 7);
+                viewport.bounds(8, 8);
+            }
+        }
+    }
+
+    #[test]
+    fn decaying_fades() {
+        let board = Board::from([(0i64, 0i64).into()]);
+        let dying = HashMap::from([(Point::new(1, 0), 2), (Point::new(2, 0), 3)]);
+        let area = Rect::new(0, 0, 3, 1);
+        let mut buf = Buffer::empty(area);
+        BoardWidget::new(&board)
+            .pan_to(Point::new(1, 0))
+            .decaying(&dying, 4)
+            .render(area, &mut buf);
+
+        let fg = |x| buf.get(x, 0).fg;
+        assert_eq!(fg(0), theme::DEFAULT.alive);
+        assert_eq!(fg(1), theme::DEFAULT.decay[0]);
+        assert_eq!(fg(2), theme::DEFAULT.decay[1]);
+    }
+
+    #[test]
+    fn hud_avoids_cells() {
+        let area = Rect::new(2, 1, 12, 4);
+        let row = |buf: &Buffer, y| {
+            (area.x..area.x + area.width)
+                .map(|x| buf.get(x, y).symbol.clone())
+                .collect::<String>()
+        };
+        let mut buf = Buffer::empty(Rect::new(0, 0, 16, 6));
+        BoardWidget::new(&Board::default())
+            .hud("gen 5 pop 0")
+            .render(area, &mut buf);
+        assert_eq!(row(&buf, 1), "gen 5 pop 0·");
+        assert_eq!(buf.get(2, 1).fg, theme::DEFAULT.cursor);
+
+        // World (-6, 2) is at the top left of the area
+        let board = Board::from([Point::new(-6, 2), Point::new(5, 2)]);
+        let mut buf = Buffer::empty(Rect::new(0, 0, 16, 6));
+        BoardWidget::new(&board)
+            .hud("gen 5 pop 2")
+            .render(area, &mut buf);
+        assert_eq!(row(&buf, 4), "gen 5 pop 2·");
+        assert!(row(&buf, 1).starts_with(symbols::block::FULL));
+    }
+
+    #[test]
+    fn checkerboard_follows_world() {
+        let board = Board::default();
+        let area = Rect::new(0, 0, 4, 3);
+        let render = |origin| {
+            let mut buf = Buffer::empty(area);
+            BoardWidget::new(&board)
+                .pan_to(origin)
+                .checkerboard(true)
+                .render(area, &mut buf);
+            buf
+        };
+        let buf = render(Point::new(0, 0));
+        for x in 0..3 {
+            for y in 0..2 {
+                assert_ne!(buf.get(x, y).bg, buf.get(x + 1, y).bg);
+                assert_ne!(buf.get(x, y).bg, buf.get(x, y + 1).bg);
+            }
+        }
+        // World (0, 0) is at the center, (2, 1)
+        assert_eq!(buf.get(2, 1).bg, theme::DEFAULT.background);
+        assert_eq!(buf.get(3, 1).bg, theme::DEFAULT.checker);
+
+        // Panning one cell right moves the pattern with it
+        let panned = render(Point::new(1, 0));
+        assert_eq!(panned.get(1, 1).bg, theme::DEFAULT.background);
+        assert_eq!(panned.get(2, 1).bg, theme::DEFAULT.checker);
+
+        let mut plain = Buffer::empty(area);
+        BoardWidget::new(&board).render(area, &mut plain);
+        assert!(plain
+            .content
+            .iter()
+            .all(|cell| cell.bg == theme::DEFAULT.background));
+    }
+
+    #[test]
+    fn trail_fades() {
+        let board = Board::from([(0i64, 0i64).into()]);
+        let trail = HashMap::from([(Point::new(1, 0), 1), (Point::new(2, 0), 4)]);
+        let area = Rect::new(0, 0, 4, 1);
+        let mut buf = Buffer::empty(area);
+        BoardWidget::new(&board)
+            .pan_to(Point::new(2, 0))
+            .trail(&trail, 4)
+            .render(area, &mut buf);
+
+        let fg = |x| buf.get(x, 0).fg;
+        assert_eq!(fg(0), theme::DEFAULT.alive);
+        assert_eq!(fg(1), theme::DEFAULT.decay[0]);
+        assert_eq!(fg(2), theme::DEFAULT.decay[1]);
+        assert_eq!(fg(3), theme::DEFAULT.grid);
+    }
+
+    #[test]
+    fn circle_glyphs() {
+        let board = Board::from([(0i64, 0i64).into()]);
+        let removed = Board::from([(1i64, 0i64).into()]);
+        let area = Rect::new(0, 0, 3, 1);
+        let mut buf = Buffer::empty(area);
+        BoardWidget::new(&board)
+            .pan_to(Point::new(1, 0))
+            .glyphs(&theme::CIRCLES)
+            .diff(&Board::default(), &removed)
+            .render(area, &mut buf);
+        let symbols: Vec<_> = (0..3).map(|x| buf.get(x, 0).symbol.clone()).collect();
+        assert_eq!(symbols, ["●", "○", "·"]);
+
+        // Terminals without Unicode still get ASCII
+        BoardWidget::new(&board)
+            .pan_to(Point::new(1, 0))
+            .glyphs(&theme::CIRCLES)
+            .ascii(true)
+            .render(area, &mut buf);
+        assert_eq!(buf.get(0, 0).symbol, "#");
+    }
+
+    #[test]
+    fn slider_handle() {
+        let area = Rect::new(2, 0, 11, 1);
+        for x in 0..area.width {
+            let mut buf = Buffer::empty(area);
+            Slider::new(Slider::ratio_at(area.width, x)).render(area, &mut buf);
+            assert_eq!(buf.get(area.x + x, 0).symbol, "●");
+        }
+        assert_eq!(Slider::ratio_at(11, 5), 0.5);
+        assert_eq!(Slider::ratio_at(11, 20), 1.0);
+        assert_eq!(Slider::ratio_at(1, 0), 0.0);
+    }
+
+    #[test]
+    fn ascii_only() {
+        let board: Board = "x.x\n.xx\nxx.\n".parse().unwrap();
+        let removed = Board::from([(1i64, 0i64).into()]);
+        let area = Rect::new(0, 0, 4, 4);
+        for half_blocks in [false, true] {
+            let mut buf = Buffer::empty(area);
+            BoardWidget::new(&board)
+                .pan_to(Point::new(2, 2))
+                .diff(&board, &removed)
+                .preview(&removed)
+                .theme(&theme::COLORBLIND)
+                .half_blocks(half_blocks)
+                .ascii(true)
+                .render(area, &mut buf);
+            assert!(buf.content.iter().all(|cell| cell.symbol.is_ascii()));
+            assert!(buf.content.iter().any(|cell| cell.symbol != " "));
         }
     }
 }
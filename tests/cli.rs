@@ -0,0 +1,34 @@
+use std::process::Command;
+
+#[test]
+fn validate_exit_status() {
+    let dir = std::env::temp_dir().join(format!("game-of-life-rs-cli-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let validate = |name: &str, contents: &str| {
+        let path = dir.join(name);
+        std::fs::write(&path, contents).unwrap();
+        Command::new(env!("CARGO_BIN_EXE_game-of-life-rs"))
+            .arg("--validate")
+            .arg(&path)
+            .output()
+            .unwrap()
+    };
+
+    let good = validate("glider.rle", "x = 3, y = 3, rule = B3/S23\nbo$2bo$3o!\n");
+    assert!(good.status.success());
+    assert!(String::from_utf8_lossy(&good.stdout).contains("population = 5\n"));
+
+    let malformed = validate("malformed.rle", "x = 3, y = 3\nbo$2bz$3o!\n");
+    assert!(!malformed.status.success());
+    assert!(malformed.stdout.is_empty());
+    assert!(!malformed.stderr.is_empty());
+
+    let missing = Command::new(env!("CARGO_BIN_EXE_game-of-life-rs"))
+        .arg("--validate")
+        .arg(dir.join("missing.rle"))
+        .output()
+        .unwrap();
+    assert!(!missing.status.success());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}